@@ -1,5 +1,7 @@
 #![allow(clippy::or_fun_call)]
 use std::ffi::OsString;
+use std::io::BufRead as _;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use anyhow::Context as _;
@@ -7,10 +9,15 @@ use radicle_common::signer::ToSigner;
 use zeroize::Zeroizing;
 
 use librad::crypto::keystore::pinentry::SecUtf8;
+use librad::git::refs::Refs;
+use librad::git::storage::Storage;
+use librad::git::Urn;
 use librad::profile::ProfileId;
+use librad::{PeerId, SecretKey};
 
 use radicle_common::args::{Args, Error, Help};
-use radicle_common::{config, git, keys, person, profile};
+use radicle_common::profile::archive::{self, Archive};
+use radicle_common::{config, git, keys, person, profile, project, tokio};
 use radicle_terminal as term;
 
 pub const HELP: Help = Help {
@@ -21,22 +28,60 @@ pub const HELP: Help = Help {
 Usage
 
     rad auth [--init | --active] [<options>...] [<profile>]
+    rad auth export <file> [--passphrase <phrase>]
+    rad auth export --paper [--passphrase <phrase>]
+    rad auth import <file> [--passphrase <phrase>]
+    rad auth import --paper [--passphrase <phrase>]
+    rad auth rotate [--passphrase <phrase>] [<repo>...]
 
     If `--init` is used, name and passphrase may be given via the `--name`
     and `--passphrase` option. Using these disables the respective input prompt.
 
+    `rad auth export` saves the active profile's key, identity and configuration
+    to an archive encrypted with the key passphrase. The archive can be restored
+    on another device with `rad auth import`, which creates a new profile.
+
+    With `--paper`, the secret key is printed in a form suitable for writing down,
+    and restored from it. A paper backup doesn't include the identity.
+
+    `rad auth rotate` replaces the device key of the active profile, eg. if it was
+    compromised. A new key is generated and added to your identity, which is
+    synced to your seeds, and a new profile is created for it with a copy of the
+    current profile's storage. The old key is then removed from your identity.
+    The `.gitsigners` file and signing configuration of the given repositories,
+    or of the current repository if none are given, are updated to the new key.
+    The new passphrase may be given via `--passphrase`.
+
 Options
 
     --init                  Initialize a new identity
     --active                Authenticate with the currently active profile
     --name <name>           Use given name (default: none)
     --passphrase <phrase>   Use given passphrase (default: none)
+    --paper                 Export or import a paper backup of the secret key
     --help                  Print help
 "#,
 };
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum Operation {
+    Authenticate,
+    Export {
+        output: Option<PathBuf>,
+        paper: bool,
+    },
+    Import {
+        input: Option<PathBuf>,
+        paper: bool,
+    },
+    Rotate {
+        repos: Vec<PathBuf>,
+    },
+}
+
 #[derive(Debug)]
 pub struct Options {
+    pub op: Operation,
     pub init: bool,
     pub active: bool,
     pub name: Option<String>,
@@ -53,6 +98,9 @@ impl Args for Options {
         let mut name = None;
         let mut passphrase = None;
         let mut profile = None;
+        let mut op: Option<String> = None;
+        let mut paths: Vec<PathBuf> = Vec::new();
+        let mut paper = false;
         let mut parser = lexopt::Parser::from_args(args);
 
         while let Some(arg) = parser.next()? {
@@ -72,7 +120,10 @@ impl Args for Options {
 
                     name = Some(val);
                 }
-                Long("passphrase") if init && passphrase.is_none() => {
+                Long("paper") if matches!(op.as_deref(), Some("export" | "import")) => {
+                    paper = true;
+                }
+                Long("passphrase") if (init || op.is_some()) && passphrase.is_none() => {
                     let val = parser
                         .value()?
                         .to_str()
@@ -91,6 +142,16 @@ impl Args for Options {
                 Long("help") => {
                     return Err(Error::Help.into());
                 }
+                Value(val)
+                    if op.is_none()
+                        && profile.is_none()
+                        && (val == "export" || val == "import" || val == "rotate") =>
+                {
+                    op = val.to_str().map(|s| s.to_owned());
+                }
+                Value(val) if op.is_some() => {
+                    paths.push(PathBuf::from(val));
+                }
                 Value(val) => {
                     let string = val.to_str().ok_or_else(|| {
                         anyhow::anyhow!("invalid UTF-8 string specified for profile")
@@ -103,8 +164,47 @@ impl Args for Options {
             }
         }
 
+        let op = match op.as_deref() {
+            Some(op) if init || active || profile.is_some() => {
+                anyhow::bail!("`rad auth {}` cannot be combined with other options", op)
+            }
+            Some("rotate") => Operation::Rotate { repos: paths },
+            Some(op) if paths.len() > 1 => {
+                anyhow::bail!("`rad auth {}` takes at most one file", op)
+            }
+            Some("export") => Operation::Export {
+                output: paths.pop(),
+                paper,
+            },
+            Some("import") => Operation::Import {
+                input: paths.pop(),
+                paper,
+            },
+            _ => Operation::Authenticate,
+        };
+        match &op {
+            Operation::Export {
+                output: None,
+                paper: false,
+            }
+            | Operation::Import {
+                input: None,
+                paper: false,
+            } => anyhow::bail!("an archive file must be specified, or `--paper`"),
+            Operation::Export {
+                output: Some(_),
+                paper: true,
+            }
+            | Operation::Import {
+                input: Some(_),
+                paper: true,
+            } => anyhow::bail!("`--paper` cannot be used with an archive file"),
+            _ => {}
+        }
+
         Ok((
             Options {
+                op,
                 init,
                 active,
                 name,
@@ -117,6 +217,13 @@ impl Args for Options {
 }
 
 pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    match options.op {
+        Operation::Export { .. } => return export(options, ctx),
+        Operation::Import { .. } => return import(options),
+        Operation::Rotate { .. } => return rotate(options, ctx),
+        Operation::Authenticate => {}
+    }
+
     let profiles = match profile::list() {
         Ok(profiles) => profiles,
         _ => vec![],
@@ -296,6 +403,380 @@ pub fn authenticate(
     Ok(())
 }
 
+pub fn export(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let profile = ctx.profile()?;
+    let passphrase = options
+        .passphrase
+        .map_or_else(term::secret_input, SecUtf8::from);
+
+    match options.op {
+        Operation::Export {
+            output: Some(output),
+            ..
+        } => {
+            if output.exists() {
+                anyhow::bail!("file {:?} already exists", output);
+            }
+            let spinner = term::spinner("Exporting profile...");
+            let archive = archive::export(&profile, passphrase)?;
+            archive.write(&output)?;
+            spinner.finish();
+
+            term::success!(
+                "Profile {} exported to {}",
+                term::format::highlight(profile.id()),
+                term::format::highlight(output.display())
+            );
+            term::tip!(
+                "To restore it, run {} on the other device.",
+                term::format::secondary(format!("`rad auth import {}`", output.display()))
+            );
+        }
+        _ => {
+            let key = keys::load_secret_key(&profile, passphrase).context("invalid passphrase")?;
+
+            term::warning("Anyone with access to this backup can impersonate you.");
+            term::blank();
+            term::info!("Write down the following lines, and keep them in a safe place:");
+            term::blank();
+            for line in keys::to_paper(key.secret_key().as_ref()).lines() {
+                term::indented(line);
+            }
+            term::blank();
+            term::tip!(
+                "To restore your key, run {}.",
+                term::format::secondary("`rad auth import --paper`")
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn import(options: Options) -> anyhow::Result<()> {
+    let home = profile::home();
+
+    let (profile, person) = match options.op {
+        Operation::Import {
+            input: Some(input), ..
+        } => {
+            let archive = Archive::read(&input)?;
+
+            term::info!(
+                "Importing profile of {}",
+                term::format::highlight(archive.peer)
+            );
+            let passphrase = options
+                .passphrase
+                .map_or_else(term::secret_input, SecUtf8::from);
+            let spinner = term::spinner("Importing profile...");
+            let result = archive::import(&home, archive, passphrase)?;
+            spinner.finish();
+
+            result
+        }
+        _ => {
+            term::info!("Enter your paper backup, one line at a time:");
+
+            // Blank lines are skipped, eg. when pasting the backup.
+            let mut paper = Zeroizing::new(String::new());
+            let stdin = std::io::stdin();
+            let lines = stdin
+                .lock()
+                .lines()
+                .filter(|line| !matches!(line, Ok(l) if l.trim().is_empty()))
+                .take(keys::PAPER_LINES);
+            for line in lines {
+                paper.push_str(&line?);
+                paper.push('\n');
+            }
+            let key = keys::secret_key_from_paper(&paper)?;
+            let peer = PeerId::from(key.clone());
+
+            if let Some(existing) = profile::find(&home, &peer)? {
+                anyhow::bail!("profile {} already uses key {}", existing.id(), peer);
+            }
+            let passphrase = options
+                .passphrase
+                .map_or_else(term::secret_input_with_confirmation, SecUtf8::from);
+            let (profile, _) = profile::create_with_key(home, keys::pwhash(passphrase), key)?;
+            config::Config::init(&profile)?;
+
+            (profile, None)
+        }
+    };
+    let storage = profile::read_only(&profile)?;
+
+    term::success!(
+        "Profile {} created for {}",
+        term::format::highlight(profile.id()),
+        term::format::highlight(storage.peer_id())
+    );
+    match person {
+        Some(urn) => term::success!("Identity {} restored", term::format::highlight(urn)),
+        None => term::tip!(
+            "Your identity isn't part of the backup. Fetch it from a seed with {}.",
+            term::format::secondary("`rad sync --fetch <urn>`")
+        ),
+    }
+    term::tip!(
+        "To add your key to ssh-agent, run {}.",
+        term::format::secondary("`rad auth --active`")
+    );
+
+    Ok(())
+}
+
+pub fn rotate(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let repos = match options.op {
+        Operation::Rotate { repos } if repos.is_empty() => {
+            if git::Repository::open(".").is_ok() {
+                vec![PathBuf::from(".")]
+            } else {
+                vec![]
+            }
+        }
+        Operation::Rotate { repos } => repos,
+        _ => vec![],
+    };
+    let old_profile = ctx.profile()?;
+    let old_signer = term::signer(&old_profile)?;
+    let old_storage = keys::storage(&old_profile, old_signer.clone())?;
+    let old_peer = *old_storage.peer_id();
+    let identity = person::local(&old_storage).context("no local identity found")?;
+    let urn = identity.urn();
+    let rt = tokio::runtime::Runtime::new()?;
+
+    term::headline(&format!(
+        "Rotating the device key {} of {}",
+        term::format::tertiary(old_peer),
+        term::format::highlight(&urn)
+    ));
+    let passphrase = options
+        .passphrase
+        .map_or_else(term::secret_input_with_confirmation, SecUtf8::from);
+
+    let key = SecretKey::new();
+    let new_peer = PeerId::from(key.clone());
+
+    // Add the new key to the identity, and publish it before removing the old key, so
+    // that peers can follow the rotation.
+    let spinner = term::spinner("Adding new device key...");
+    let new_profile = match add_device_key(&old_profile, &old_storage, key, passphrase.clone()) {
+        Ok(profile) => {
+            spinner.finish();
+            profile
+        }
+        Err(err) => {
+            spinner.failed();
+            return Err(err);
+        }
+    };
+    term::success!(
+        "Added new device key {} to identity",
+        term::format::tertiary(new_peer)
+    );
+    sync_identity(&urn, &old_profile, old_signer.clone(), &rt);
+
+    // From here on, the identity delegating to the new key may have been published, so
+    // the new profile is kept, though inactive, if the rotation fails.
+    let spinner = term::spinner("Removing old device key...");
+    if let Err(err) =
+        remove_device_key(&old_profile, &old_storage, &new_profile, passphrase.clone())
+    {
+        spinner.failed();
+        term::warning(&format!(
+            "The new profile {} was kept, as your identity may already delegate to its key",
+            new_profile.id()
+        ));
+        return Err(err);
+    }
+    spinner.finish();
+
+    let new_signer = keys::load_secret_key(&new_profile, passphrase.clone())?;
+    if sync_identity(&urn, &new_profile, new_signer, &rt) {
+        term::success!(
+            "Removed old device key {} from identity",
+            term::format::tertiary(old_peer)
+        );
+    } else {
+        term::warning(&format!(
+            "Removed old device key {} from identity locally, but it couldn't be synced",
+            old_peer
+        ));
+        term::tip!(
+            "To sync your identity, run {}.",
+            term::format::secondary("`rad sync --self`")
+        );
+    }
+    sync_identity(&urn, &old_profile, old_signer, &rt);
+    profile::set(new_profile.id())?;
+
+    // Update working copies.
+    for repo in &repos {
+        match git::rotate_gitsigner(repo, &old_peer, &new_peer) {
+            Ok(true) => term::success!(
+                "Updated {} in {}",
+                term::format::tertiary(".gitsigners"),
+                repo.display()
+            ),
+            Ok(false) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+        let signing_key = git::git(repo, ["config", "--local", git::CONFIG_SIGNING_KEY]);
+        if signing_key.map_or(false, |k| {
+            k.trim() == keys::to_ssh_key(&old_peer).unwrap_or_default()
+        }) {
+            git::configure_signing(repo, &new_peer)?;
+            term::success!("Signing configured in {}", repo.display());
+        }
+    }
+
+    if let Ok(sock) = keys::ssh_auth_sock() {
+        keys::add(&new_profile, keys::pwhash(passphrase), sock)?;
+        term::success!("New key added to ssh-agent");
+    }
+    term::success!(
+        "Profile {} created and activated",
+        term::format::highlight(new_profile.id())
+    );
+    term::tip!(
+        "The previous profile {} is kept, but can no longer sign for your identity.",
+        term::format::dim(old_profile.id())
+    );
+
+    Ok(())
+}
+
+/// Add a new device key to the local identity, creating an inactive profile for it,
+/// with the identity and the projects it delegates. The profile is removed on error.
+///
+/// Identity revisions must be signed by a majority of the previous and the new
+/// delegations, so the revision adding the new key is countersigned by the new key.
+fn add_device_key(
+    old_profile: &profile::Profile,
+    old_storage: &Storage,
+    key: SecretKey,
+    passphrase: SecUtf8,
+) -> anyhow::Result<profile::Profile> {
+    let (new_profile, _) =
+        profile::create_inactive(&profile::home(), keys::pwhash(passphrase.clone()), key)?;
+
+    match copy_identity(old_profile, old_storage, &new_profile, passphrase) {
+        Ok(()) => Ok(new_profile),
+        Err(err) => {
+            profile::remove(&new_profile).ok();
+            Err(err)
+        }
+    }
+}
+
+fn copy_identity(
+    old_profile: &profile::Profile,
+    old_storage: &Storage,
+    new_profile: &profile::Profile,
+    passphrase: SecUtf8,
+) -> anyhow::Result<()> {
+    let old_peer = *old_storage.peer_id();
+    let new_signer = keys::load_secret_key(new_profile, passphrase)?;
+    let new_storage = keys::storage(new_profile, new_signer)?;
+    let new_peer = *new_storage.peer_id();
+    let identity = person::local(old_storage).context("no local identity found")?;
+    let urn = identity.urn();
+    let delegations = identity
+        .delegations()
+        .iter()
+        .copied()
+        .filter(|k| *k != *new_peer.as_public_key())
+        .chain(std::iter::once(*new_peer.as_public_key()))
+        .collect::<Vec<_>>();
+
+    // Add the new key to the identity, signing with the old key.
+    person::set_delegations(old_storage, delegations)?;
+
+    // Copy the identity and the projects it delegates to the new profile.
+    let mut urns = vec![urn.clone()];
+    for (project, meta, _) in project::list(old_storage)? {
+        if meta.delegates.iter().any(
+            |d| matches!(d, project::Delegate::Indirect { urn: delegate, .. } if *delegate == urn),
+        ) {
+            urns.push(project);
+        }
+    }
+    profile::copy_storage(old_profile, new_profile, &urns)?;
+    if let Ok(config) = config::Config::profile(old_profile) {
+        config.write(config::Config::path(new_profile))?;
+    } else {
+        config::Config::init(new_profile)?;
+    }
+
+    // Countersign the revision adding the new key.
+    let person = person::merge(&new_storage, &urn, old_peer)
+        .with_context(|| format!("couldn't countersign identity {}", urn))?;
+    person::set_local(&new_storage, &person)?;
+
+    // Sign the copied refs with the new key.
+    for urn in &urns {
+        Refs::update(&new_storage, urn)?;
+    }
+    Ok(())
+}
+
+/// Remove the old device key from the local identity, once the new key was added with
+/// [`add_device_key`]. The revision removing the old key is countersigned by the old key.
+fn remove_device_key(
+    old_profile: &profile::Profile,
+    old_storage: &Storage,
+    new_profile: &profile::Profile,
+    passphrase: SecUtf8,
+) -> anyhow::Result<()> {
+    let old_peer = *old_storage.peer_id();
+    let new_signer = keys::load_secret_key(new_profile, passphrase)?;
+    let new_storage = keys::storage(new_profile, new_signer)?;
+    let new_peer = *new_storage.peer_id();
+    let identity = person::local(&new_storage).context("no local identity found")?;
+    let urn = identity.urn();
+
+    person::set_delegations(
+        &new_storage,
+        identity
+            .delegations()
+            .iter()
+            .copied()
+            .filter(|k| *k != *old_peer.as_public_key()),
+    )?;
+
+    // Countersign the revision removing the old key, and adopt it in the new profile.
+    profile::copy_refs(new_profile, old_profile, &urn)?;
+    person::merge(old_storage, &urn, new_peer)
+        .with_context(|| format!("couldn't countersign identity {}", urn))?;
+    profile::copy_refs(old_profile, new_profile, &urn)?;
+    let person = person::merge(&new_storage, &urn, old_peer)
+        .with_context(|| format!("couldn't adopt identity {}", urn))?;
+    person::set_local(&new_storage, &person)?;
+    Refs::update(&new_storage, &urn)?;
+
+    Ok(())
+}
+
+/// Push an identity to the configured seeds, or queue it if none can be reached. Other
+/// failures are reported, but not fatal. Returns whether the identity was pushed or
+/// queued.
+fn sync_identity(
+    urn: &Urn,
+    profile: &profile::Profile,
+    signer: impl ToSigner,
+    rt: &tokio::runtime::Runtime,
+) -> bool {
+    let result = term::sync::seeds(profile, urn)
+        .and_then(|seeds| term::sync::push_or_queue(urn.clone(), seeds, profile, signer, rt));
+    if let Err(err) = result {
+        term::warning(&format!("Could not sync identity: {}", err));
+        return false;
+    }
+    true
+}
+
 fn sanitize_name(name: String) -> anyhow::Result<String> {
     if name.contains(char::is_whitespace) {
         anyhow::bail!("Name cannot contain whitespaces");
@@ -314,6 +795,7 @@ mod tests {
 
     fn create_auth_options(name: &str) -> Options {
         Options {
+            op: Operation::Authenticate,
             active: false,
             init: true,
             name: Some(name.to_owned()),
@@ -335,6 +817,39 @@ mod tests {
         assert_eq!(profile::name(None).unwrap(), "user");
     }
 
+    #[assay(
+        setup = test::setup::lnk_home()?,
+        teardown = test::teardown::profiles()?,
+    )]
+    fn rotated_identity_verifies() {
+        let (storage, profile, whoami, nakamoto) = test::setup::profile();
+        let urn = whoami.urn();
+        let key = SecretKey::new();
+        let new_peer = PeerId::from(key.clone());
+        let passphrase = SecUtf8::from(test::USER_PASS);
+
+        let new_profile = add_device_key(&profile, &storage, key, passphrase.clone()).unwrap();
+        // The new profile is only activated once the rotation is complete.
+        assert_eq!(profile::default().unwrap().id(), profile.id());
+
+        remove_device_key(&profile, &storage, &new_profile, passphrase.clone()).unwrap();
+        let signer = keys::load_secret_key(&new_profile, passphrase).unwrap();
+        let new_storage = keys::storage(&new_profile, signer).unwrap();
+
+        for storage in [&storage, &new_storage] {
+            let person = person::verify(storage, &urn).unwrap().unwrap();
+
+            assert_eq!(
+                person.delegations().iter().copied().collect::<Vec<_>>(),
+                vec![*new_peer.as_public_key()]
+            );
+        }
+        assert_eq!(person::local(&new_storage).unwrap().urn(), urn);
+        assert!(project::get(&new_storage, &nakamoto.urn())
+            .unwrap()
+            .is_some());
+    }
+
     #[assay(
         setup = test::setup::lnk_home()?,
     )]
@@ -8,7 +8,7 @@ use librad::git::Urn;
 
 use radicle_common::args::{Args, Error, Help};
 use radicle_common::Interactive;
-use radicle_common::{fmt, keys, profile, project};
+use radicle_common::{checkouts, fmt, keys, profile, project};
 use radicle_terminal as term;
 
 pub const HELP: Help = Help {
@@ -141,6 +141,9 @@ pub fn execute(options: Options, profile: &profile::Profile) -> anyhow::Result<P
             if let Err(err) = rad_init::setup_signing(storage.peer_id(), &repo, interactive) {
                 term::warning(&format!("Could not setup signing: {:#}", err));
             }
+            if let Err(err) = checkouts::register(profile, options.urn.clone(), &path) {
+                term::warning(&format!("Could not register working copy: {:#}", err));
+            }
 
             // Setup a remote and tracking branch for all project delegates except yourself.
             let setup = project::SetupRemote {
@@ -149,6 +152,7 @@ pub fn execute(options: Options, profile: &profile::Profile) -> anyhow::Result<P
                 signer,
                 fetch: true,
                 upstream: true,
+                upstream_prefix: None,
             };
             for peer in &project.remotes {
                 if peer != storage.peer_id() {
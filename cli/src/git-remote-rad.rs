@@ -1,18 +1,18 @@
 #![allow(clippy::extra_unused_lifetimes)]
 use librad::crypto::keystore::pinentry::SecUtf8;
-#[cfg(not(feature = "ethereum"))]
+use librad::crypto::BoxedSigner;
 use librad::git::local::url::LocalUrl;
-use librad::profile::{LnkHome, LNK_HOME};
+use librad::profile::{LnkHome, Profile, ProfileId, LNK_HOME, LNK_PROFILE};
 use link_identities::git::Urn;
 use radicle_git_helpers::remote_helper;
 
-use radicle_common::{keys, profile, signer::ToSigner as _};
+use radicle_common::{git, keys, profile, signer::ToSigner as _};
 
 use anyhow::anyhow;
-#[cfg(feature = "ethereum")]
-use futures_lite::future;
 
 use std::env;
+use std::os::unix::process::CommandExt as _;
+use std::path::Path;
 use std::process;
 use std::str::FromStr;
 
@@ -105,6 +105,27 @@ fn main() {
         fatal(anyhow!("Not enough arguments supplied"));
     };
 
+    // The remote helper library reads the remote URL from the command line, so if a
+    // profile is specified in the URL, we re-execute ourselves without it, and pass the
+    // profile via the environment instead.
+    let (url, id) = split_profile(&url);
+    if let Some(id) = id {
+        if let Err(err) = ProfileId::from_str(&id) {
+            fatal(anyhow!("Invalid profile {:?} in remote URL: {}", id, err));
+        }
+        let exe = match env::current_exe() {
+            Ok(exe) => exe,
+            Err(err) => fatal(err.into()),
+        };
+        let err = process::Command::new(exe)
+            .args(env::args().nth(1))
+            .arg(url)
+            .env(LNK_PROFILE, id)
+            .exec();
+
+        fatal(anyhow!("Failed to execute remote helper: {}", err));
+    }
+
     match Remote::from_str(&url) {
         Ok(url) => {
             if let Err(err) = run(url) {
@@ -121,118 +142,291 @@ fn run(remote: Remote) -> anyhow::Result<()> {
     match remote {
         #[cfg(feature = "ethereum")]
         Remote::Org { org, urn } => {
-            use std::process::Command;
-            use std::process::Stdio;
-
-            let domain = future::block_on(ethereum::resolve(org))?;
-            let http_url = format!("https://{}/{}", domain, urn.encode_id());
-
-            // TODO: Use `exec` here.
-            let mut child = Command::new("git")
-                .arg("remote-https")
-                .arg(http_url)
-                .stdout(Stdio::inherit())
-                .stderr(Stdio::inherit())
-                .stdin(Stdio::inherit())
-                .spawn()?;
-
-            let status = child.wait()?;
+            use radicle_common::tokio;
+
+            let profile = self::profile()?;
+            let rt = tokio::runtime::Runtime::new()?;
+            let seed = rt.block_on(ethereum::resolve(org))?;
+
+            // Fetch the project from the org's seed into local storage, so that it can be
+            // served like any other project. Connecting to the seed requires a signer, but
+            // if there is none, a project that is already in local storage can still be
+            // served.
+            let storage = profile::read_only(&profile)?;
+            let exists = radicle_common::project::get(&storage, &urn)?.is_some();
+
+            match signer(&profile)? {
+                Some(signer) => {
+                    eprintln!(
+                        "Fetching {} from {}...",
+                        urn,
+                        radicle_common::fmt::peer(&seed.peer)
+                    );
+                    ethereum::fetch(&urn, seed, signer, &profile, &rt)?;
+                }
+                None if exists => {
+                    eprintln!(
+                        "No signer found, skipping fetch of {} from {}",
+                        urn,
+                        radicle_common::fmt::peer(&seed.peer)
+                    );
+                }
+                None => {
+                    return Err(anyhow!(
+                        "no signers found to fetch {}: ssh-agent is not running",
+                        urn
+                    ))
+                }
+            }
 
-            process::exit(status.code().unwrap_or(EXIT_FAILURE))
+            // Hand over to the project remote helper, by re-executing ourselves with the
+            // project's local URL.
+            let url = LocalUrl::from(urn);
+            let remote = env::args().nth(1).unwrap_or_else(|| url.to_string());
+            let err = process::Command::new(env::current_exe()?)
+                .arg(remote)
+                .arg(url.to_string())
+                .env(LNK_PROFILE, profile.id().to_string())
+                .exec();
+
+            Err(anyhow!("failed to execute remote helper: {}", err))
         }
         Remote::Project { urn: _urn } => {
-            let profile = profile::default()?;
-            let signer = if let Ok(sock) = keys::ssh_auth_sock() {
-                sock.to_signer(&profile)?
-            } else if let Ok(pass) = env::var(keys::RAD_PASSPHRASE) {
-                keys::load_secret_key(&profile, SecUtf8::from(pass))?.to_signer(&profile)?
-            } else {
-                fatal(anyhow!("no signers found: ssh-agent is not running"));
-            };
+            let profile = self::profile()?;
+            // A signer is only needed to push.
             let config = remote_helper::Config {
-                signer: Some(signer),
+                signer: signer(&profile)?,
             };
 
             // This is a workaround because the remote helper library
             // doesn't take a profile as config parameter, so we have
-            // to configure it this way.
+            // to configure it this way. Since the environment is
+            // per-process, this is safe with concurrent helpers
+            // running on different profiles.
             if let LnkHome::Root(root) = profile::home() {
                 env::set_var(LNK_HOME, root);
             }
+            env::set_var(LNK_PROFILE, profile.id().to_string());
+
             remote_helper::run(config)
         }
     }
 }
 
+/// Split the `profile` query parameter off a remote URL, eg.
+/// `rad://hnrkyghsrokxzxpy9pww69xr11dr9q7edbxfo.git?profile=<id>`.
+fn split_profile(url: &str) -> (String, Option<String>) {
+    let (base, query) = match url.split_once('?') {
+        Some((base, query)) => (base, query),
+        None => return (url.to_owned(), None),
+    };
+    let mut profile = None;
+    let params = query
+        .split('&')
+        .filter(|param| match param.strip_prefix("profile=") {
+            Some(id) => {
+                profile = Some(id.to_owned());
+                false
+            }
+            None => !param.is_empty(),
+        })
+        .collect::<Vec<_>>();
+
+    if params.is_empty() {
+        (base.to_owned(), profile)
+    } else {
+        (format!("{}?{}", base, params.join("&")), profile)
+    }
+}
+
+/// Get the profile to use. In order of precedence, this is the profile set via the
+/// `LNK_PROFILE` environment variable, eg. from the remote URL, the profile set in the
+/// repository's `rad.profile` configuration, or the default profile.
+fn profile() -> anyhow::Result<Profile> {
+    let id = match env::var(LNK_PROFILE) {
+        Ok(id) if !id.is_empty() => Some(id),
+        _ => git::git(Path::new("."), ["config", profile::CONFIG_PROFILE_KEY])
+            .ok()
+            .map(|id| id.trim().to_owned())
+            .filter(|id| !id.is_empty()),
+    };
+
+    match id {
+        Some(id) => {
+            let id = ProfileId::from_str(&id)
+                .map_err(|e| anyhow!("invalid profile id {:?}: {}", id, e))?;
+            profile::get(&id)
+        }
+        None => profile::default(),
+    }
+}
+
+/// Get a signer, from the SSH agent, the profile's signer configuration or the
+/// `RAD_PASSPHRASE` environment variable. Returns `None` if no signer is available.
+fn signer(profile: &Profile) -> anyhow::Result<Option<BoxedSigner>> {
+    if let Some(signer) = keys::ssh_auth_sock()
+        .ok()
+        .and_then(|sock| sock.to_signer(profile).ok())
+    {
+        return Ok(Some(signer));
+    }
+    if let Some(signer) = radicle_common::signer::configured(profile)? {
+        Ok(Some(signer))
+    } else if let Ok(pass) = env::var(keys::RAD_PASSPHRASE) {
+        Ok(Some(
+            keys::load_secret_key(profile, SecUtf8::from(pass))?.to_signer(profile)?,
+        ))
+    } else {
+        Ok(None)
+    }
+}
+
 #[cfg(feature = "ethereum")]
 pub mod ethereum {
     use std::convert::TryFrom;
-    use std::env;
-    use std::future;
+    use std::time;
+
+    use anyhow::{anyhow, Context as _};
+    use ethers::providers::Middleware;
+    use ethers::types::NameOrAddress;
 
-    use anyhow::Context as _;
-    use ethers::abi::{Detokenize, ParamType};
-    use ethers::contract::abigen;
-    use ethers::prelude::*;
-    use ethers::types::{Address, NameOrAddress};
+    use librad::crypto::BoxedSigner;
+    use librad::git::Urn;
+    use librad::profile::Profile;
+    use librad::PeerId;
+
+    use radicle_common::ethereum::resolver::{self, PublicResolver};
+    use radicle_common::ethereum::{self as eth, ProviderOptions};
+    use radicle_common::seed::{api::SeedApiClient, Address, Protocol};
+    use radicle_common::{sync, tokio};
 
-    /// Text record key that holds the Git server address.
-    pub const ENS_SEED_HOST: &str = "eth.radicle.seed.host";
     /// URL scheme supported.
     pub const URL_SCHEME: &str = "rad";
     /// Ethereum TLD.
     pub const ETH_TLD: &str = ".eth";
 
-    // Generated contract to query ENS resolver.
-    abigen!(
-        EnsTextResolver,
-        "[function text(bytes32,string) external view returns (string)]",
-    );
-
-    pub async fn resolve(org: NameOrAddress) -> anyhow::Result<String> {
-        // Only resolve ENS names.
-        if let NameOrAddress::Name(ref domain) = org {
-            if !domain.ends_with(ETH_TLD) {
-                return Ok(domain.clone());
-            }
-        }
+    /// Seed of an org, as resolved from its ENS records.
+    #[derive(Debug, Clone)]
+    pub struct OrgSeed {
+        /// The org's name, eg. `radicle.eth`.
+        pub name: String,
+        /// Seed host, from the [`resolver::RADICLE_SEED_HOST_KEY`] record.
+        pub host: url::Host,
+        /// Seed peer id, from the [`resolver::RADICLE_SEED_ID_KEY`] record.
+        pub peer: PeerId,
+    }
 
-        let rpc_url = env::var("ETH_RPC_URL")
-            .ok()
-            .and_then(|url| if url.is_empty() { None } else { Some(url) })
-            .ok_or_else(|| {
-                anyhow::anyhow!("'ETH_RPC_URL' must be set to an Ethereum JSON-RPC URL")
-            })?;
-
-        let provider =
-            Provider::<Http>::try_from(rpc_url.as_str()).context("JSON-RPC URL parsing failed")?;
-
-        let (_address, name) = match org {
-            NameOrAddress::Name(name) => (provider.resolve_name(name.as_str()).await?, name),
-            NameOrAddress::Address(addr) => (
-                future::ready(addr).await,
-                provider.lookup_address(addr).await?,
-            ),
+    /// Resolve the seed of an org.
+    ///
+    /// ENS names and addresses are resolved via the ENS text records of the name. Other
+    /// domain names are used as the seed host directly.
+    pub async fn resolve(org: NameOrAddress) -> anyhow::Result<OrgSeed> {
+        let (name, host, peer) = match org {
+            NameOrAddress::Name(domain) if !domain.ends_with(ETH_TLD) => {
+                (domain.clone(), domain, None)
+            }
+            org => {
+                let provider = eth::provider(ProviderOptions::default())?;
+                let name = match org {
+                    NameOrAddress::Name(name) => name,
+                    NameOrAddress::Address(addr) => {
+                        provider.lookup_address(addr).await.with_context(|| {
+                            format!("could not resolve address {:?} to an ENS name", addr)
+                        })?
+                    }
+                };
+                eprintln!("Resolving ENS records for {}", name);
+
+                let resolver = PublicResolver::get(&name, provider)
+                    .await
+                    .with_context(|| format!("could not resolve ENS name '{}'", name))?;
+                let host = resolver
+                    .text(&name, resolver::RADICLE_SEED_HOST_KEY)
+                    .await?
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "ENS name '{}' has no '{}' record: the org doesn't have a seed",
+                            name,
+                            resolver::RADICLE_SEED_HOST_KEY
+                        )
+                    })?;
+                let peer = resolver.text(&name, resolver::RADICLE_SEED_ID_KEY).await?;
+
+                (name, host, peer)
+            }
         };
-        eprintln!("Resolving ENS record {} for {}", ENS_SEED_HOST, name);
-
-        let resolver = {
-            let bytes = provider
-                .call(&ens::get_resolver(ens::ENS_ADDRESS, &name).into(), None)
-                .await?;
-            let tokens = ethers::abi::decode(&[ParamType::Address], bytes.as_ref())?;
 
-            Address::from_tokens(tokens).unwrap()
+        let host = url::Host::parse(&host)
+            .with_context(|| format!("invalid seed host '{}' for '{}'", host, name))?;
+        let peer = match peer {
+            Some(peer) => peer
+                .parse()
+                .map_err(|_| anyhow!("invalid seed id '{}' for '{}'", peer, name))?,
+            // If the seed id isn't known, ask the seed.
+            None => {
+                let addr = Address::new(host.clone(), Protocol::Api { local: false });
+                let api = SeedApiClient::from_address(&addr, std::iter::empty());
+
+                api.peer()
+                    .with_context(|| format!("could not get seed id from {}", api.url()))?
+                    .id
+            }
         };
+        eprintln!("Resolved {} to {}@{}", name, peer, host);
 
-        let contract = EnsTextResolver::new(resolver, provider.into());
-        let host = contract
-            .text(ens::namehash(&name).0, ENS_SEED_HOST.to_owned())
-            .call()
-            .await?;
-
-        eprintln!("Resolved {} to {}", ENS_SEED_HOST, host);
+        Ok(OrgSeed { name, host, peer })
+    }
 
-        Ok(host)
+    /// Fetch a project from an org's seed into local storage.
+    pub fn fetch(
+        urn: &Urn,
+        seed: OrgSeed,
+        signer: BoxedSigner,
+        profile: &Profile,
+        rt: &tokio::runtime::Runtime,
+    ) -> anyhow::Result<()> {
+        let name = seed.name;
+        let addr = Address::new(
+            seed.host,
+            Protocol::Link {
+                peer: Some(seed.peer),
+            },
+        );
+        let seed = sync::Seed::try_from(addr)?;
+
+        let results = rt.block_on(async {
+            let (seeds, _errors) = sync::Seeds::resolve([seed].iter()).await;
+            if seeds.0.is_empty() {
+                anyhow::bail!("could not resolve seed address of '{}'", name);
+            }
+            let client = sync::client(signer, profile).await?;
+            let results = sync::sync(
+                &client,
+                urn.clone(),
+                seeds,
+                sync::Mode::Fetch,
+                time::Duration::from_secs(9),
+                |_| {},
+            )
+            .await;
+
+            Ok(results)
+        })?;
+
+        for result in results {
+            match result.fetch {
+                Some(Ok(_)) => {}
+                Some(Err(err)) => {
+                    return Err(anyhow!(
+                        "failed to fetch {} from the seed of '{}': {}",
+                        urn,
+                        name,
+                        err
+                    ))
+                }
+                None => {}
+            }
+        }
+        Ok(())
     }
 }
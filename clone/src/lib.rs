@@ -9,7 +9,9 @@ use librad::git::Urn;
 use url::Url;
 
 use radicle_common::args::{Args, Error, Help};
+use radicle_common::config::Config;
 use radicle_common::seed;
+use radicle_common::seed::api::{self, SeedApiClient};
 use radicle_common::Interactive;
 use radicle_common::{git, identity, keys, profile, project, sync};
 use radicle_terminal as term;
@@ -132,6 +134,33 @@ pub fn clone_project(
     ctx: impl term::Context,
 ) -> anyhow::Result<()> {
     let profile = ctx.profile()?;
+    let seeds = match &seed {
+        Some(seed) => vec![seed.clone()],
+        None => term::sync::seeds(&profile, &urn)?.into_iter().collect(),
+    };
+
+    // Check that the seeds have the project before syncing, via their HTTP API.
+    let config = Config::load(&profile).unwrap_or_default();
+    let apis = seeds
+        .iter()
+        .filter_map(|seed| SeedApiClient::from_seed(seed, config.seeds()).ok())
+        .collect::<Vec<_>>();
+    let spinner = term::spinner(&format!("Looking up {}...", term::format::highlight(&urn)));
+    match lookup(&urn, &apis) {
+        Ok(Some(project)) => {
+            spinner.finish();
+            term::info!(
+                "Found {} {}",
+                term::format::bold(&project.name),
+                term::format::dim(&project.description)
+            );
+        }
+        Ok(None) => spinner.finish(),
+        Err(err) => {
+            spinner.failed();
+            return Err(err);
+        }
+    }
 
     rad_sync::run(
         rad_sync::Options {
@@ -139,6 +168,7 @@ pub fn clone_project(
                 urn: urn.clone(),
                 seed,
             }),
+            seeds,
             verbose: true,
             ..rad_sync::Options::default()
         },
@@ -178,6 +208,25 @@ pub fn clone_project(
     Ok(())
 }
 
+/// Look up a project on seeds, via their HTTP API. Fails if the project isn't found on
+/// any of the seeds, unless some of them couldn't be reached, in which case `None` is
+/// returned and syncing is left to find out.
+pub fn lookup(urn: &Urn, apis: &[SeedApiClient]) -> anyhow::Result<Option<api::Project>> {
+    let mut missing = Vec::new();
+
+    for api in apis {
+        match api.project(urn) {
+            Ok(project) => return Ok(Some(project)),
+            Err(err) if err.is_not_found() => missing.push(api.url().to_string()),
+            Err(_) => {}
+        }
+    }
+    if !apis.is_empty() && missing.len() == apis.len() {
+        anyhow::bail!("project {} was not found on {}", urn, missing.join(", "));
+    }
+    Ok(None)
+}
+
 pub fn clone_repository(url: Url, profile: &profile::Profile) -> anyhow::Result<()> {
     let proj = url
         .path_segments()
@@ -211,6 +260,8 @@ pub fn clone_repository(url: Url, profile: &profile::Profile) -> anyhow::Result<
 mod test {
     use super::*;
     use librad::PeerId;
+    use radicle_common::json::json;
+    use radicle_common::test::seed::FakeSeed;
 
     #[test]
     fn test_args_ok() {
@@ -251,6 +302,44 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_lookup() {
+        let urn = Urn::from_str("rad:git:hnrkfbrd7y9674d8ow8uioki16fniwcyoz67y").unwrap();
+        let peer =
+            PeerId::from_str("hyb5to4rshftx4apgmu9s6wnsp4ddmp1mz6ijh4qqey7fb8wrpawxa").unwrap();
+        let seed = sync::Seed {
+            peer,
+            addrs: String::from("127.0.0.1:8776"),
+            label: None,
+        };
+        let empty = FakeSeed::spawn().unwrap();
+        let fake = FakeSeed::spawn().unwrap();
+        fake.route(
+            &format!("/v1/projects/{}", urn),
+            json!({
+                "urn": urn.to_string(),
+                "name": "nakamoto",
+                "description": "Bitcoin light-client",
+                "defaultBranch": "master",
+            }),
+        );
+
+        // The seed's API is found via its configuration.
+        let api = SeedApiClient::from_seed(&seed, [fake.config(Some(peer))].iter()).unwrap();
+        let project = lookup(&urn, &[api.clone()]).unwrap().unwrap();
+        assert_eq!(project.name, "nakamoto");
+        assert_eq!(project.default_branch, "master");
+
+        let other = SeedApiClient::new(empty.url());
+        assert!(lookup(&urn, &[other.clone(), api]).unwrap().is_some());
+        assert!(lookup(&urn, &[other.clone()]).is_err());
+
+        // Seeds that can't be reached are left to the sync.
+        let unreachable = SeedApiClient::new(Url::parse("http://127.0.0.1:1").unwrap());
+        assert!(lookup(&urn, &[other, unreachable]).unwrap().is_none());
+        assert!(lookup(&urn, &[]).unwrap().is_none());
+    }
+
     #[test]
     fn test_args_error() {
         let tests = vec![
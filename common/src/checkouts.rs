@@ -0,0 +1,152 @@
+//! Registry of project working copies, persisted in the profile.
+//!
+//! Updated when projects are initialized, cloned or checked out, so that commands
+//! can find where a project lives on disk.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use librad::git::Urn;
+use librad::profile::Profile;
+use serde::{Deserialize, Serialize};
+
+use crate::project::deserialize_urn;
+
+/// Registry file name, in the profile directory.
+pub const FILE_NAME: &str = "checkouts.toml";
+
+/// A working copy of a project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    #[serde(deserialize_with = "deserialize_urn")]
+    pub urn: Urn,
+    /// Absolute path of the working copy.
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Checkouts {
+    #[serde(default)]
+    entries: Vec<Entry>,
+}
+
+impl Checkouts {
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        let content = fs::read_to_string(path)?;
+        let checkouts = toml::from_str(&content)?;
+
+        Ok(checkouts)
+    }
+
+    /// Load the profile's registry. Returns an empty registry if there is none.
+    pub fn load(profile: &Profile) -> Result<Self, io::Error> {
+        match Self::read(Self::path(profile)) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            result => result,
+        }
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), anyhow::Error> {
+        let contents = toml::to_string_pretty(self)?;
+        fs::write(path, contents)?;
+
+        Ok(())
+    }
+
+    /// Write the registry to the profile.
+    pub fn save(&self, profile: &Profile) -> Result<(), anyhow::Error> {
+        self.write(Self::path(profile))
+    }
+
+    pub fn path(profile: &Profile) -> PathBuf {
+        profile.paths().seeds_file().with_file_name(FILE_NAME)
+    }
+
+    /// Register a working copy. A path can only be the working copy of one project.
+    pub fn add(&mut self, urn: Urn, path: PathBuf) {
+        self.entries.retain(|e| e.path != path);
+        self.entries.push(Entry { urn, path });
+    }
+
+    /// Remove the working copies of a project from the registry, returning them.
+    pub fn remove(&mut self, urn: &Urn) -> Vec<Entry> {
+        let (removed, kept) = self.entries.drain(..).partition(|e| &e.urn == urn);
+        self.entries = kept;
+
+        removed
+    }
+
+    /// Remove the entries whose directories no longer exist, returning them.
+    pub fn prune(&mut self) -> Vec<Entry> {
+        let (kept, pruned) = self.entries.drain(..).partition(|e| e.path.is_dir());
+        self.entries = kept;
+
+        pruned
+    }
+
+    /// Get the working copies of a project.
+    pub fn get<'a>(&'a self, urn: &'a Urn) -> impl Iterator<Item = &'a Path> + 'a {
+        self.entries
+            .iter()
+            .filter(move |e| &e.urn == urn)
+            .map(|e| e.path.as_path())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Register a working copy in the profile, pruning deleted working copies.
+pub fn register(profile: &Profile, urn: Urn, path: &Path) -> Result<(), anyhow::Error> {
+    let mut checkouts = Checkouts::load(profile)?;
+
+    checkouts.prune();
+    checkouts.add(urn, path.canonicalize()?);
+    checkouts.save(profile)
+}
+
+/// Load the profile's registry, pruning working copies that were deleted.
+pub fn load(profile: &Profile) -> Result<Checkouts, anyhow::Error> {
+    let mut checkouts = Checkouts::load(profile)?;
+
+    if !checkouts.prune().is_empty() {
+        checkouts.save(profile)?;
+    }
+    Ok(checkouts)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tempdir::TempDir;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_prune() {
+        let urn = Urn::from_str("rad:git:hnrkbtw9t1of4ykjy6er4qqwxtc54k9943eto").unwrap();
+        let tmp = TempDir::new("rad-checkouts").unwrap();
+        let existing = tmp.path().join("acme");
+        fs::create_dir_all(&existing).unwrap();
+
+        let mut checkouts = Checkouts::default();
+        checkouts.add(urn.clone(), existing.clone());
+        checkouts.add(urn.clone(), tmp.path().join("deleted"));
+        checkouts.add(urn.clone(), existing.clone());
+
+        let pruned = checkouts.prune();
+
+        assert_eq!(pruned.len(), 1);
+        assert_eq!(pruned[0].path, tmp.path().join("deleted"));
+        assert_eq!(
+            checkouts.get(&urn).collect::<Vec<_>>(),
+            vec![existing.as_path()]
+        );
+        assert_eq!(checkouts.remove(&urn).len(), 1);
+        assert!(checkouts.is_empty());
+    }
+}
@@ -47,6 +47,38 @@ pub struct SeedConfig {
     pub api: Url,
 }
 
+impl SeedConfig {
+    /// Create a seed configuration for the given host, using the default ports.
+    pub fn new(host: Host, peer: Option<PeerId>) -> Self {
+        let mut p2p: Url = Address::new(host.clone(), Protocol::Link { peer }).into();
+        let mut git: Url = Address::new(host.clone(), Protocol::Git { local: false }).into();
+        let mut api: Url = Address::new(host.clone(), Protocol::Api { local: false }).into();
+
+        p2p.set_port(Some(DEFAULT_SEED_P2P_PORT)).ok();
+        git.set_port(Some(DEFAULT_SEED_GIT_PORT)).ok();
+        api.set_port(Some(DEFAULT_SEED_API_PORT)).ok();
+
+        SeedConfig {
+            name: Some(host.to_string()),
+            p2p,
+            git,
+            api,
+        }
+    }
+
+    /// Create a seed configuration from a P2P seed address, eg. `rad://<peer>@<host>`.
+    pub fn from_address(addr: &Address) -> Result<Self, anyhow::Error> {
+        if addr.peer().is_none() {
+            anyhow::bail!("seed address '{}' is missing a peer id", addr);
+        }
+        let mut config = Self::new(addr.host.clone(), addr.peer());
+        config.p2p = addr.url();
+        config.p2p.set_port(Some(addr.port())).ok();
+
+        Ok(config)
+    }
+}
+
 impl TryFrom<SeedConfig> for Seed<String> {
     type Error = anyhow::Error;
 
@@ -58,9 +90,56 @@ impl TryFrom<SeedConfig> for Seed<String> {
     }
 }
 
+/// Non-interactive signer configuration, for headless environments such as CI.
+///
+/// ```toml
+/// [signer]
+/// type = "key-file"
+/// passphrase_fd = 3
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum SignerConfig {
+    /// Encrypted key file, unsealed with a passphrase read from a file descriptor, or
+    /// from the `RAD_PASSPHRASE` environment variable.
+    KeyFile {
+        /// Path to the key file. Defaults to the profile's key.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<PathBuf>,
+        /// File descriptor to read the passphrase from.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        passphrase_fd: Option<i32>,
+    },
+    /// Base64-encoded encrypted key file, passed via an environment variable. The
+    /// passphrase is obtained like for [`SignerConfig::KeyFile`].
+    Env {
+        /// Environment variable holding the key.
+        #[serde(default = "default_signer_var")]
+        var: String,
+        /// File descriptor to read the passphrase from.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        passphrase_fd: Option<i32>,
+    },
+    /// External signing command. See [`crate::signer::CommandSigner`].
+    Command {
+        /// Program to run.
+        command: String,
+        /// Arguments passed to the program, before the operation.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<String>,
+    },
+}
+
+fn default_signer_var() -> String {
+    crate::signer::RAD_SECRET_KEY.to_owned()
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub seed: Vec<SeedConfig>,
+    /// Signer to use when ssh-agent isn't running, or doesn't hold the profile's key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<SignerConfig>,
 }
 
 impl Default for Config {
@@ -69,30 +148,13 @@ impl Default for Config {
             seed: DEFAULT_SEEDS
                 .iter()
                 .map(|(host, peer)| {
-                    let host = String::from(*host);
-                    let peer = PeerId::from_str(peer).ok();
-
-                    let mut p2p: Url =
-                        Address::new(Host::Domain(host.clone()), Protocol::Link { peer }).into();
-                    let mut git: Url =
-                        Address::new(Host::Domain(host.clone()), Protocol::Git { local: false })
-                            .into();
-                    let mut api: Url =
-                        Address::new(Host::Domain(host.clone()), Protocol::Api { local: false })
-                            .into();
-
-                    p2p.set_port(Some(DEFAULT_SEED_P2P_PORT)).ok();
-                    git.set_port(Some(DEFAULT_SEED_GIT_PORT)).ok();
-                    api.set_port(Some(DEFAULT_SEED_API_PORT)).ok();
-
-                    SeedConfig {
-                        name: Some(host),
-                        p2p,
-                        git,
-                        api,
-                    }
+                    SeedConfig::new(
+                        Host::Domain(String::from(*host)),
+                        PeerId::from_str(peer).ok(),
+                    )
                 })
                 .collect(),
+            signer: None,
         }
     }
 }
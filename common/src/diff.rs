@@ -0,0 +1,86 @@
+//! Line-based text diffs.
+
+/// A line of a diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Line<'a> {
+    /// Line present in both texts.
+    Unchanged(&'a str),
+    /// Line only present in the new text.
+    Added(&'a str),
+    /// Line only present in the old text.
+    Removed(&'a str),
+}
+
+/// Compute the line diff between two texts, based on their longest common subsequence.
+pub fn lines<'a>(old: &'a str, new: &'a str) -> Vec<Line<'a>> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    // Length of the longest common subsequence of `old[i..]` and `new[j..]`.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(Line::Unchanged(old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(Line::Removed(old[i]));
+            i += 1;
+        } else {
+            diff.push(Line::Added(new[j]));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|l| Line::Removed(l)));
+    diff.extend(new[j..].iter().map(|l| Line::Added(l)));
+
+    diff
+}
+
+/// Whether a diff has any changes.
+pub fn is_changed(diff: &[Line]) -> bool {
+    diff.iter().any(|l| !matches!(l, Line::Unchanged(_)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        let old = "{\n  \"name\": \"acme\",\n  \"description\": \"\"\n}";
+        let new = "{\n  \"name\": \"acme\",\n  \"description\": \"Rockets\"\n}";
+
+        assert_eq!(
+            lines(old, new),
+            vec![
+                Line::Unchanged("{"),
+                Line::Unchanged("  \"name\": \"acme\","),
+                Line::Removed("  \"description\": \"\""),
+                Line::Added("  \"description\": \"Rockets\""),
+                Line::Unchanged("}"),
+            ]
+        );
+        assert!(is_changed(&lines(old, new)));
+        assert!(!is_changed(&lines(old, old)));
+    }
+
+    #[test]
+    fn test_lines_empty() {
+        assert_eq!(lines("", "a\nb"), vec![Line::Added("a"), Line::Added("b")]);
+        assert_eq!(lines("a", ""), vec![Line::Removed("a")]);
+        assert!(lines("", "").is_empty());
+    }
+}
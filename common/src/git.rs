@@ -1,6 +1,8 @@
 //! Git-related functions and types.
+pub mod verify;
+
 use std::collections::HashSet;
-use std::fs::{File, OpenOptions};
+use std::fs::{self, File, OpenOptions};
 use std::io;
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -16,8 +18,8 @@ use librad::reflike;
 use librad::{crypto::BoxedSigner, PeerId};
 
 pub use git2::{
-    build::CheckoutBuilder, AnnotatedCommit, Commit, Direction, ErrorCode, MergeAnalysis,
-    MergeOptions, Oid, Reference, Repository, Signature,
+    build::CheckoutBuilder, AnnotatedCommit, BranchType, Commit, Direction, ErrorCode,
+    MergeAnalysis, MergeOptions, Oid, Reference, Repository, Signature,
 };
 pub use librad::git::local::transport;
 pub use librad::git::types::remote::LocalFetchspec;
@@ -194,13 +196,24 @@ pub fn add_gitsigners<'a>(
 
 /// Read a `.gitsigners` file. Returns SSH keys.
 pub fn read_gitsigners(path: &Path) -> Result<HashSet<String>, io::Error> {
+    Ok(gitsigners(path)?.into_iter().map(|(_, key)| key).collect())
+}
+
+/// Read a `.gitsigners` file. Returns the signer labels along with their SSH keys.
+pub fn gitsigners(path: &Path) -> Result<Vec<(String, String)>, io::Error> {
     use std::io::BufRead;
 
-    let mut keys = HashSet::new();
+    let mut signers = Vec::new();
     let file = File::open(path.join(".gitsigners"))?;
 
     for line in io::BufReader::new(file).lines() {
         let line = line?;
+        let line = line.trim();
+
+        // Skip comments, which are used to name signers.
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
         if let Some((label, key)) = line.split_once(' ') {
             if let Ok(peer) = PeerId::from_str(label) {
                 let expected = keys::to_ssh_key(&peer)?;
@@ -211,10 +224,119 @@ pub fn read_gitsigners(path: &Path) -> Result<HashSet<String>, io::Error> {
                     ));
                 }
             }
-            keys.insert(key.to_owned());
+            if !signers.iter().any(|(_, k)| k == key) {
+                signers.push((label.to_owned(), key.to_owned()));
+            }
+        }
+    }
+    Ok(signers)
+}
+
+/// Changes made to a `.gitsigners` file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SignersUpdate {
+    pub added: Vec<PeerId>,
+    pub removed: Vec<PeerId>,
+}
+
+impl SignersUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Regenerate the repository's `.gitsigners` file from the given peers. Peer names,
+/// if any, are written as comments above their keys. Duplicate peers are skipped,
+/// and entries that are not labeled with a peer id are kept as-is.
+///
+/// If `dry_run` is set, the file is not written.
+pub fn sync_gitsigners<'a>(
+    repo: &Path,
+    signers: impl IntoIterator<Item = (&'a PeerId, Option<&'a str>)>,
+    dry_run: bool,
+) -> Result<SignersUpdate, io::Error> {
+    let existing = match gitsigners(repo) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => return Err(err),
+    };
+    let (existing, others): (Vec<_>, Vec<_>) = existing
+        .into_iter()
+        .partition(|(label, _)| PeerId::from_str(label).is_ok());
+    let existing = existing
+        .iter()
+        .filter_map(|(label, _)| PeerId::from_str(label).ok())
+        .collect::<Vec<_>>();
+
+    let mut contents = Vec::new();
+    let mut peers = Vec::new();
+
+    for (peer, name) in signers {
+        if peers.contains(peer) {
+            continue;
         }
+        if let Some(name) = name {
+            writeln!(contents, "# {}", name)?;
+        }
+        write_gitsigner(&mut contents, peer)?;
+        peers.push(*peer);
+    }
+    for (label, key) in others {
+        writeln!(contents, "{} {}", label, key)?;
+    }
+
+    let update = SignersUpdate {
+        added: peers
+            .iter()
+            .filter(|p| !existing.contains(p))
+            .copied()
+            .collect(),
+        removed: existing
+            .iter()
+            .filter(|p| !peers.contains(p))
+            .copied()
+            .collect(),
+    };
+    let path = repo.join(".gitsigners");
+    if !dry_run && fs::read(&path).ok().as_ref() != Some(&contents) {
+        fs::write(path, contents)?;
     }
-    Ok(keys)
+    Ok(update)
+}
+
+/// Replace a signer's key in the repository's `.gitsigners` file, eg. after a key
+/// rotation. Entries labeled with the old peer id are relabeled with the new one.
+/// Returns whether the file was changed.
+pub fn rotate_gitsigner(repo: &Path, old: &PeerId, new: &PeerId) -> Result<bool, io::Error> {
+    let path = repo.join(".gitsigners");
+    let contents = fs::read_to_string(&path)?;
+    let old_key = keys::to_ssh_key(old)?;
+    let new_key = keys::to_ssh_key(new)?;
+    let old_label = old.default_encoding();
+
+    let mut rotated = String::new();
+    let mut changed = false;
+    for line in contents.lines() {
+        match line.trim().split_once(' ') {
+            Some((label, key)) if !label.starts_with('#') && key.trim() == old_key => {
+                let label = if label == old_label {
+                    new.default_encoding()
+                } else {
+                    label.to_owned()
+                };
+                rotated.push_str(&format!("{} {}\n", label, new_key));
+                changed = true;
+            }
+            _ => {
+                rotated.push_str(line);
+                rotated.push('\n');
+            }
+        }
+    }
+    if changed {
+        fs::write(path, rotated)?;
+    }
+    Ok(changed)
 }
 
 /// Add a path to the repository's git ignore file. Creates the
@@ -269,9 +391,20 @@ pub fn rad_remote(repo: &Repository) -> anyhow::Result<Remote<LocalUrl>> {
 /// > peers/scooby/master...peers/scooby/rad/heads/master
 ///
 pub fn set_tracking(repo: &Path, remote: &str, branch: &str) -> anyhow::Result<String> {
+    set_tracking_branch(repo, &format!("{}/{}", remote, branch), remote, branch)
+}
+
+/// Like [`set_tracking`], but with the given tracking branch name.
+///
+/// > remotes/scooby/master...peers/scooby/rad/heads/master
+///
+pub fn set_tracking_branch(
+    repo: &Path,
+    name: &str,
+    remote: &str,
+    branch: &str,
+) -> anyhow::Result<String> {
     let repository = git2::Repository::open(repo)?;
-    // The tracking branch name, eg. 'peers/scooby/master'
-    let branch_name = format!("{}/{}", remote, branch);
     // The remote branch being tracked, eg. 'peers/scooby/rad/heads/master'
     let remote_branch_name = format!("{}/rad/heads/{}", remote, branch);
     // The target reference this branch should be set to.
@@ -280,10 +413,10 @@ pub fn set_tracking(repo: &Path, remote: &str, branch: &str) -> anyhow::Result<S
     let commit = reference.peel_to_commit()?;
 
     repository
-        .branch(&branch_name, &commit, true)?
+        .branch(name, &commit, true)?
         .set_upstream(Some(&remote_branch_name))?;
 
-    Ok(branch_name)
+    Ok(name.to_owned())
 }
 
 /// Get the name of the remote of the given branch, if any.
@@ -385,8 +518,8 @@ pub fn push_tag(tag_name: &str) -> anyhow::Result<String> {
     git(Path::new("."), vec!["push", "rad", "tag", tag_name])
 }
 
-pub fn push_branch(name: &str) -> anyhow::Result<String> {
-    git(Path::new("."), vec!["push", "rad", name])
+pub fn push_branch(repo: &Path, name: &str) -> anyhow::Result<String> {
+    git(repo, vec!["push", "rad", name])
 }
 
 fn write_gitsigner(mut w: impl io::Write, signer: &PeerId) -> io::Result<()> {
@@ -428,6 +561,7 @@ pub fn commit_ssh_fingerprint(path: &Path, sha1: &str) -> Result<Option<String>,
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::tempdir::TempDir;
     use std::str::FromStr;
 
     #[test]
@@ -487,4 +621,63 @@ mod test {
 
         assert!(Version::from_str("2.34").is_err());
     }
+
+    #[test]
+    fn test_sync_gitsigners() {
+        let dir = TempDir::new("rad-gitsigners").unwrap();
+        let tmp = dir.path();
+        let alice =
+            PeerId::from_str("hyb5to4rshftx4apgmu9s6wnsp4ddmp1mz6ijh4qqey7fb8wrpawxa").unwrap();
+        let bob =
+            PeerId::from_str("hyd1xifd959krgipmwcgtudc6z8us7ch6tyjwut9bghkdiaftt1kda").unwrap();
+
+        write_gitsigners(tmp, [&alice]).unwrap();
+        fs::write(
+            tmp.join(".gitsigners"),
+            format!(
+                "{}\nalice@example.com ssh-ed25519 AAAA\n",
+                fs::read_to_string(tmp.join(".gitsigners")).unwrap().trim()
+            ),
+        )
+        .unwrap();
+
+        let update = sync_gitsigners(tmp, [(&bob, Some("bob")), (&bob, None)], false).unwrap();
+        assert_eq!(update.added, vec![bob]);
+        assert_eq!(update.removed, vec![alice]);
+
+        let contents = fs::read_to_string(tmp.join(".gitsigners")).unwrap();
+        assert!(contents.starts_with("# bob\n"));
+        assert!(contents.contains("alice@example.com ssh-ed25519 AAAA"));
+
+        let signers = gitsigners(tmp).unwrap();
+        assert_eq!(signers.len(), 2);
+        assert_eq!(signers[0].0, bob.default_encoding());
+
+        let update = sync_gitsigners(tmp, [(&bob, Some("bob"))], false).unwrap();
+        assert!(update.is_empty());
+    }
+
+    #[test]
+    fn test_rotate_gitsigner() {
+        let dir = TempDir::new("rad-rotate").unwrap();
+        let tmp = dir.path();
+        let alice =
+            PeerId::from_str("hyb5to4rshftx4apgmu9s6wnsp4ddmp1mz6ijh4qqey7fb8wrpawxa").unwrap();
+        let bob =
+            PeerId::from_str("hyd1xifd959krgipmwcgtudc6z8us7ch6tyjwut9bghkdiaftt1kda").unwrap();
+
+        sync_gitsigners(tmp, [(&alice, Some("alice"))], false).unwrap();
+
+        assert!(rotate_gitsigner(tmp, &alice, &bob).unwrap());
+        assert!(!rotate_gitsigner(tmp, &alice, &bob).unwrap());
+
+        let contents = fs::read_to_string(tmp.join(".gitsigners")).unwrap();
+        assert!(contents.starts_with("# alice\n"));
+
+        let signers = gitsigners(tmp).unwrap();
+        assert_eq!(
+            signers,
+            vec![(bob.default_encoding(), keys::to_ssh_key(&bob).unwrap())]
+        );
+    }
 }
@@ -0,0 +1,228 @@
+//! Commit signature verification.
+//!
+//! Commits are verified against the keys of the repository's `.gitsigners` file and
+//! the project delegates.
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use librad::PeerId;
+
+use super::Oid;
+use crate::keys;
+use crate::project;
+use crate::tempdir::TempDir;
+
+/// A known signer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signer {
+    /// Signer label, eg. a peer id.
+    pub label: String,
+    /// SSH public key.
+    pub key: String,
+    /// Whether the signer is a project delegate.
+    pub delegate: bool,
+}
+
+/// Set of known signers, keyed by SSH key fingerprint.
+#[derive(Debug, Clone, Default)]
+pub struct Signers {
+    signers: HashMap<String, Signer>,
+}
+
+impl Signers {
+    /// Get the signers of a project: its delegates and the keys of the repository's
+    /// `.gitsigners` file, if any.
+    pub fn load(repo: &Path, project: &project::Metadata) -> Result<Self, io::Error> {
+        let mut signers = Self::default();
+
+        for delegate in &project.delegates {
+            for id in delegate.peers() {
+                signers.insert_peer(&id, true)?;
+            }
+        }
+
+        match super::gitsigners(repo) {
+            Ok(entries) => {
+                for (label, key) in entries {
+                    signers.insert(label, key, false);
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+        Ok(signers)
+    }
+
+    /// Add a signer. If the key is already known, only its delegate flag is updated.
+    pub fn insert(&mut self, label: String, key: String, delegate: bool) {
+        if let Some(fingerprint) = keys::ssh_key_fingerprint(&key) {
+            let signer = Signer {
+                label,
+                key,
+                delegate,
+            };
+            match self.signers.get_mut(&fingerprint) {
+                Some(existing) => existing.delegate |= delegate,
+                None => {
+                    self.signers.insert(fingerprint, signer);
+                }
+            }
+        }
+    }
+
+    pub fn insert_peer(&mut self, peer: &PeerId, delegate: bool) -> Result<(), io::Error> {
+        let key = keys::to_ssh_key(peer)?;
+        self.insert(peer.default_encoding(), key, delegate);
+
+        Ok(())
+    }
+
+    /// Get a signer by SSH key fingerprint.
+    pub fn get(&self, fingerprint: &str) -> Option<&Signer> {
+        self.signers.get(fingerprint)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signers.is_empty()
+    }
+
+    /// Output the signers in the format of an SSH "allowed signers" file.
+    pub fn to_allowed_signers(&self) -> String {
+        let mut out = String::new();
+        for signer in self.signers.values() {
+            out.push_str(&format!("{} {}\n", signer.label, signer.key));
+        }
+        out
+    }
+}
+
+/// Signature status of a commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    /// Signed by a known signer.
+    Verified { signer: Signer },
+    /// Signed with a key that isn't known.
+    Unknown { fingerprint: String },
+    /// Not signed.
+    Unsigned,
+    /// The signature is invalid, or could not be checked.
+    Bad,
+}
+
+/// A commit and its signature status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    pub oid: Oid,
+    pub summary: String,
+    pub status: Status,
+}
+
+impl Commit {
+    pub fn is_verified(&self) -> bool {
+        matches!(self.status, Status::Verified { .. })
+    }
+
+    /// Parse a line of `git log` output, as formatted by [`LOG_FORMAT`].
+    fn parse(line: &str, signers: &Signers) -> Option<Self> {
+        let mut fields = line.splitn(4, '\0');
+        let oid = fields.next()?.parse().ok()?;
+        let result = fields.next()?;
+        let fingerprint = fields.next()?;
+        let summary = fields.next().unwrap_or_default().to_owned();
+
+        let status = match result {
+            "N" => Status::Unsigned,
+            // Good signature, or good signature from a key we don't have validity
+            // information for.
+            "G" | "U" | "E" if !fingerprint.is_empty() => match signers.get(fingerprint) {
+                Some(signer) if result != "E" => Status::Verified {
+                    signer: signer.clone(),
+                },
+                _ => Status::Unknown {
+                    fingerprint: fingerprint.to_owned(),
+                },
+            },
+            _ => Status::Bad,
+        };
+
+        Some(Self {
+            oid,
+            summary,
+            status,
+        })
+    }
+}
+
+/// `git log` format used for verification: hash, signature status, key fingerprint
+/// and summary, separated by NUL bytes.
+const LOG_FORMAT: &str = "--format=%H%x00%G?%x00%GF%x00%s";
+
+/// Verify the signatures of the commits in the given range, eg. `master..HEAD`.
+pub fn verify(repo: &Path, range: &str, signers: &Signers) -> anyhow::Result<Vec<Commit>> {
+    // Git needs an allowed signers file to verify SSH signatures; we generate one from
+    // the known signers.
+    let tmp = TempDir::new("rad-verify")?;
+    let allowed = tmp.path().join("allowed-signers");
+    keys::write_secret(&allowed, signers.to_allowed_signers().as_bytes())?;
+
+    let output = Command::new("git")
+        .current_dir(repo)
+        .arg("-c")
+        .arg(format!("gpg.ssh.allowedSignersFile={}", allowed.display()))
+        .args(["log", LOG_FORMAT, range, "--"])
+        .output()?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "`git log` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| Commit::parse(line, signers))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const KEY: &str =
+        "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIHY0bJqKb9rLqcT9w2Z5wdqiu2TNtUfy9lANRt1Pp9wQ";
+
+    #[test]
+    fn test_parse() {
+        let mut signers = Signers::default();
+        signers.insert(String::from("alice"), KEY.to_owned(), true);
+
+        let fingerprint = keys::ssh_key_fingerprint(KEY).unwrap();
+        let oid = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+        let commit =
+            Commit::parse(&format!("{}\0G\0{}\0Fix bug", oid, fingerprint), &signers).unwrap();
+        assert_eq!(commit.summary, "Fix bug");
+        assert!(matches!(
+            commit.status,
+            Status::Verified { ref signer } if signer.label == "alice" && signer.delegate
+        ));
+
+        let commit = Commit::parse(&format!("{}\0N\0\0Unsigned", oid), &signers).unwrap();
+        assert_eq!(commit.status, Status::Unsigned);
+
+        let commit =
+            Commit::parse(&format!("{}\0U\0SHA256:other\0Unknown", oid), &signers).unwrap();
+        assert_eq!(
+            commit.status,
+            Status::Unknown {
+                fingerprint: String::from("SHA256:other")
+            }
+        );
+
+        let commit = Commit::parse(&format!("{}\0B\0{}\0Bad", oid, fingerprint), &signers).unwrap();
+        assert_eq!(commit.status, Status::Bad);
+    }
+}
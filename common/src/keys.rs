@@ -1,4 +1,6 @@
 //! SSH and key-related functions.
+use std::path::Path;
+
 use anyhow::{Context as _, Error, Result};
 
 use librad::crypto::keystore::crypto;
@@ -7,7 +9,7 @@ use librad::crypto::keystore::pinentry::{Pinentry, SecUtf8};
 use librad::crypto::keystore::{FileStorage, Keystore};
 use librad::git::storage::Storage;
 use librad::profile::Profile;
-use librad::{PeerId, PublicKey};
+use librad::{PeerId, PublicKey, SecretKey};
 
 use lnk_clib::keys;
 use lnk_clib::keys::ssh::SshAuthSock;
@@ -113,19 +115,53 @@ pub fn to_ssh_fingerprint(peer_id: &PeerId) -> Result<String, std::io::Error> {
     Ok(format!("SHA256:{}", encoded.trim_end_matches('=')))
 }
 
+/// Get the SSH key fingerprint of an SSH public key, eg. `ssh-ed25519 AAAA...`.
+/// Returns `None` if the key can't be decoded.
+pub fn ssh_key_fingerprint(key: &str) -> Option<String> {
+    use sha2::Digest;
+
+    let blob = key.split_whitespace().nth(1)?;
+    let blob = base64::decode(blob).ok()?;
+    let sha = sha2::Sha256::digest(&blob).to_vec();
+    let encoded = base64::encode(sha);
+
+    Some(format!("SHA256:{}", encoded.trim_end_matches('=')))
+}
+
 /// Get a profile's secret key by providing a passphrase.
 pub fn load_secret_key(
     profile: &Profile,
     passphrase: SecUtf8,
+) -> Result<ZeroizingSecretKey, anyhow::Error> {
+    load_secret_key_from(&profile.paths().keys_dir().join(KEY_FILE), passphrase)
+}
+
+/// Get the secret key stored in the given key file by providing a passphrase.
+pub fn load_secret_key_from(
+    path: &Path,
+    passphrase: SecUtf8,
 ) -> Result<ZeroizingSecretKey, anyhow::Error> {
     let pwhash = pwhash(passphrase);
-    let file_storage: FileStorage<_, PublicKey, _, _> =
-        FileStorage::new(&profile.paths().keys_dir().join(KEY_FILE), pwhash);
+    let file_storage: FileStorage<_, PublicKey, _, _> = FileStorage::new(path, pwhash);
     let keypair = file_storage.get_key()?;
 
     Ok(ZeroizingSecretKey::new(keypair.secret_key))
 }
 
+/// Write secret data, eg. a key file, to a new file only readable by the current user.
+pub fn write_secret(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write as _;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?;
+
+    file.write_all(contents)
+}
+
 #[cfg(not(debug_assertions))]
 pub fn pwhash(secret: SecUtf8) -> crypto::Pwhash<SecUtf8> {
     crypto::Pwhash::new(secret, crypto::KdfParams::recommended())
@@ -135,3 +171,116 @@ pub fn pwhash(secret: SecUtf8) -> crypto::Pwhash<SecUtf8> {
 pub fn pwhash(secret: SecUtf8) -> crypto::Pwhash<SecUtf8> {
     crypto::Pwhash::new(secret, *crypto::KDF_PARAMS_TEST)
 }
+
+/// Number of key bytes per line of a paper backup.
+const PAPER_LINE_BYTES: usize = 8;
+/// Length of an ed25519 secret key, in bytes.
+const SECRET_KEY_BYTES: usize = 32;
+/// Number of lines of a paper backup of a secret key.
+pub const PAPER_LINES: usize = SECRET_KEY_BYTES / PAPER_LINE_BYTES;
+
+/// Encode secret key bytes as a paper backup: lines of hexadecimal groups, each followed
+/// by a checksum group, so that transcription errors can be located when restoring.
+///
+/// > 4f2a 9c1e 77b0 12ad  e0c1
+///
+pub fn to_paper(secret: &[u8]) -> String {
+    use sha2::Digest;
+
+    let mut paper = String::new();
+    for line in secret.chunks(PAPER_LINE_BYTES) {
+        let checksum = sha2::Sha256::digest(line);
+        let groups = line
+            .chunks(2)
+            .map(|group| group.iter().map(|b| format!("{:02x}", b)).collect())
+            .collect::<Vec<String>>();
+
+        paper.push_str(&format!(
+            "{}  {:02x}{:02x}\n",
+            groups.join(" "),
+            checksum[0],
+            checksum[1]
+        ));
+    }
+    paper
+}
+
+/// Get a secret key from a paper backup.
+pub fn secret_key_from_paper(paper: &str) -> Result<SecretKey, anyhow::Error> {
+    use librad::crypto::keystore::pinentry::SecStr;
+    use librad::crypto::keystore::SecretKeyExt as _;
+
+    let bytes = from_paper(paper)?;
+    let key = SecretKey::from_bytes_and_meta(SecStr::from(bytes.to_vec()), &())
+        .map_err(|e| anyhow::anyhow!("invalid secret key: {}", e))?;
+
+    Ok(key)
+}
+
+/// Decode a paper backup produced by [`to_paper`].
+pub fn from_paper(paper: &str) -> Result<zeroize::Zeroizing<Vec<u8>>, anyhow::Error> {
+    use sha2::Digest;
+
+    let mut secret = zeroize::Zeroizing::new(Vec::new());
+    let lines = paper.lines().map(str::trim).filter(|l| !l.is_empty());
+
+    for (i, line) in lines.enumerate() {
+        let digits = zeroize::Zeroizing::new(
+            line.chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>(),
+        );
+        if digits.len() != PAPER_LINE_BYTES * 2 + 4 {
+            anyhow::bail!(
+                "line {}: expected {} groups of 4 digits",
+                i + 1,
+                PAPER_LINE_BYTES / 2 + 1
+            );
+        }
+        let bytes = (0..digits.len())
+            .step_by(2)
+            .map(|j| u8::from_str_radix(&digits[j..j + 2], 16))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| anyhow::anyhow!("line {}: invalid hexadecimal digits", i + 1))?;
+        let bytes = zeroize::Zeroizing::new(bytes);
+        let (line, checksum) = bytes.split_at(PAPER_LINE_BYTES);
+
+        if sha2::Sha256::digest(line)[..2] != *checksum {
+            anyhow::bail!("line {}: checksum mismatch, please check for typos", i + 1);
+        }
+        secret.extend_from_slice(line);
+    }
+    Ok(secret)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_paper_roundtrip() {
+        let secret = (0..32).map(|i| i * 7).collect::<Vec<u8>>();
+        let paper = to_paper(&secret);
+
+        assert_eq!(paper.lines().count(), PAPER_LINES);
+        assert_eq!(from_paper(&paper).unwrap().as_slice(), secret.as_slice());
+
+        // Whitespace and case don't matter.
+        let sloppy = paper.replace(' ', "").to_uppercase();
+        assert_eq!(from_paper(&sloppy).unwrap().as_slice(), secret.as_slice());
+    }
+
+    #[test]
+    fn test_paper_typo() {
+        let secret = [0xab; 32];
+        let mut lines = to_paper(&secret)
+            .lines()
+            .map(|l| l.to_owned())
+            .collect::<Vec<_>>();
+        lines[1] = lines[1].replacen("abab", "abaa", 1);
+
+        let err = from_paper(&lines.join("\n")).unwrap_err();
+
+        assert!(err.to_string().starts_with("line 2:"), "{}", err);
+    }
+}
@@ -1,20 +1,28 @@
 //! Common radicle utilities.
 #![allow(clippy::or_fun_call)]
 pub mod args;
+pub mod checkouts;
 pub mod cobs;
 pub mod config;
+pub mod diff;
 pub mod git;
 pub mod identity;
 pub mod keys;
 pub mod logger;
+pub mod mirror;
+pub mod outbox;
 pub mod patch;
+pub mod payload;
 pub mod person;
 pub mod profile;
 pub mod project;
 pub mod seed;
 pub mod signer;
 pub mod sync;
+pub mod tempdir;
+pub mod template;
 pub mod test;
+pub mod trash;
 
 #[cfg(feature = "ethereum")]
 pub mod ethereum;
@@ -0,0 +1,156 @@
+//! Mirrors of projects hosted on other git servers.
+//!
+//! A mirror is a working copy cloned from an upstream git remote. Its default branch and
+//! tags are fetched from upstream, and pushed to the project in storage.
+use std::path::Path;
+
+use anyhow::Context as _;
+
+use crate::git;
+
+/// Git configuration key that sets the upstream remote of a mirror.
+pub const CONFIG_MIRROR_KEY: &str = "rad.mirror";
+/// Name of the upstream remote of a mirror.
+pub const UPSTREAM: &str = "upstream";
+
+/// Clone an upstream git repository as a mirror.
+pub fn clone(url: &str, destination: &Path) -> Result<(), anyhow::Error> {
+    let path = destination.to_string_lossy();
+
+    git::git(
+        Path::new("."),
+        ["clone", "--origin", UPSTREAM, url, path.as_ref()],
+    )
+    .with_context(|| format!("failed to clone {}", url))?;
+    git::git(
+        destination,
+        ["config", "--local", CONFIG_MIRROR_KEY, UPSTREAM],
+    )
+    .context("failed to save mirror configuration")?;
+
+    Ok(())
+}
+
+/// Get the upstream remote of a repository, if it is a mirror.
+pub fn upstream(repo: &Path) -> Option<String> {
+    let output = git::git(repo, ["config", "--local", CONFIG_MIRROR_KEY]).ok()?;
+    let remote = output.trim();
+
+    if remote.is_empty() {
+        None
+    } else {
+        Some(remote.to_owned())
+    }
+}
+
+/// Get the URL of a repository's remote.
+pub fn url(repo: &Path, remote: &str) -> Result<String, anyhow::Error> {
+    let output = git::git(repo, ["remote", "get-url", remote])?;
+
+    Ok(output.trim().to_owned())
+}
+
+/// Fetch branches and tags from upstream, and fast-forward the local default branch.
+pub fn fetch(repo: &Path, remote: &str, branch: &str) -> Result<(), anyhow::Error> {
+    let upstream = format!("refs/remotes/{}/{}", remote, branch);
+
+    git::git(repo, ["fetch", "--prune", remote])
+        .with_context(|| format!("failed to fetch from `{}`", remote))?;
+    git::git(repo, ["fetch", "--tags", remote])
+        .with_context(|| format!("failed to fetch tags from `{}`", remote))?;
+
+    let head = git::git(repo, ["symbolic-ref", "--quiet", "--short", "HEAD"]).unwrap_or_default();
+    if head.trim() == branch {
+        git::git(repo, ["merge", "--ff-only", upstream.as_str()])
+    } else {
+        let refspec = format!("{}:refs/heads/{}", upstream, branch);
+        git::git(repo, ["fetch", ".", refspec.as_str()])
+    }
+    .with_context(|| format!("`{}` can't be fast-forwarded to `{}`", branch, upstream))?;
+
+    Ok(())
+}
+
+/// Push the default branch and tags to the project in storage.
+pub fn push(repo: &Path, branch: &str) -> Result<(), anyhow::Error> {
+    git::push_branch(repo, branch).with_context(|| format!("failed to push `{}`", branch))?;
+    git::git(repo, ["push", "rad", "--tags"]).context("failed to push tags")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tempdir::TempDir;
+
+    /// Commit to a repository, without depending on the user's git configuration.
+    fn commit(repo: &Path, message: &str) -> String {
+        git::git(
+            repo,
+            [
+                "-c",
+                "user.name=cloudhead",
+                "-c",
+                "user.email=cloudhead@radicle.xyz",
+                "-c",
+                "commit.gpgsign=false",
+                "commit",
+                "--allow-empty",
+                "--quiet",
+                "-m",
+                message,
+            ],
+        )
+        .unwrap();
+
+        rev_parse(repo, "HEAD")
+    }
+
+    fn rev_parse(repo: &Path, rev: &str) -> String {
+        git::git(repo, ["rev-parse", rev])
+            .unwrap()
+            .trim()
+            .to_owned()
+    }
+
+    #[test]
+    fn test_fetch_push() {
+        let tmp = TempDir::new("rad-mirror").unwrap();
+        let upstream = tmp.path().join("upstream");
+        let mirror = tmp.path().join("mirror");
+        let storage = tmp.path().join("storage.git");
+
+        git::git(tmp.path(), ["init", "--quiet", "upstream"]).unwrap();
+        git::git(&upstream, ["symbolic-ref", "HEAD", "refs/heads/master"]).unwrap();
+        commit(&upstream, "Initial commit");
+
+        clone(&upstream.to_string_lossy(), &mirror).unwrap();
+        assert_eq!(self::upstream(&mirror), Some(UPSTREAM.to_owned()));
+        assert_eq!(url(&mirror, UPSTREAM).unwrap(), upstream.to_string_lossy());
+
+        // Stand-in for the project in storage.
+        git::git(tmp.path(), ["init", "--quiet", "--bare", "storage.git"]).unwrap();
+        git::git(
+            &mirror,
+            ["remote", "add", "rad", &storage.to_string_lossy()],
+        )
+        .unwrap();
+
+        let head = commit(&upstream, "Second commit");
+        git::git(&upstream, ["tag", "v1.0.0"]).unwrap();
+
+        fetch(&mirror, UPSTREAM, "master").unwrap();
+        assert_eq!(rev_parse(&mirror, "master"), head);
+        assert_eq!(rev_parse(&mirror, "v1.0.0"), head);
+
+        push(&mirror, "master").unwrap();
+        assert_eq!(rev_parse(&storage, "master"), head);
+        assert_eq!(rev_parse(&storage, "v1.0.0"), head);
+
+        // Diverging local changes can't be fast-forwarded.
+        commit(&mirror, "Local commit");
+        commit(&upstream, "Third commit");
+        assert!(fetch(&mirror, UPSTREAM, "master").is_err());
+    }
+}
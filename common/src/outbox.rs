@@ -0,0 +1,113 @@
+//! Outbox of pending pushes, persisted in the profile.
+//!
+//! When no seed can be reached, commands that push changes queue the project here,
+//! so that it can be synced later with `rad sync --pending`.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use librad::git::Urn;
+use librad::profile::Profile;
+use serde::{Deserialize, Serialize};
+
+use crate::project::deserialize_urn;
+
+/// Outbox file name, in the profile directory.
+pub const FILE_NAME: &str = "outbox.toml";
+
+/// A pending push.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Project or identity to push.
+    #[serde(deserialize_with = "deserialize_urn")]
+    pub urn: Urn,
+    /// When the push was first queued, in seconds since the epoch.
+    pub queued: u64,
+    /// Number of failed attempts at pushing.
+    pub attempts: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Outbox {
+    #[serde(default)]
+    pending: Vec<Entry>,
+}
+
+impl Outbox {
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        let content = fs::read_to_string(path)?;
+        let outbox = toml::from_str(&content)?;
+
+        Ok(outbox)
+    }
+
+    /// Load the profile's outbox. Returns an empty outbox if there is none.
+    pub fn load(profile: &Profile) -> Result<Self, io::Error> {
+        match Self::read(Self::path(profile)) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            result => result,
+        }
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), anyhow::Error> {
+        let contents = toml::to_string_pretty(self)?;
+        fs::write(path, contents)?;
+
+        Ok(())
+    }
+
+    /// Write the outbox to the profile.
+    pub fn save(&self, profile: &Profile) -> Result<(), anyhow::Error> {
+        self.write(Self::path(profile))
+    }
+
+    pub fn path(profile: &Profile) -> PathBuf {
+        profile.paths().seeds_file().with_file_name(FILE_NAME)
+    }
+
+    /// Queue a push for the given URN. If the URN is already queued, the number of attempts
+    /// is incremented instead.
+    pub fn enqueue(&mut self, urn: Urn) -> &Entry {
+        if let Some(ix) = self.pending.iter().position(|e| e.urn == urn) {
+            let entry = &mut self.pending[ix];
+            entry.attempts += 1;
+
+            return entry;
+        }
+        let queued = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        self.pending.push(Entry {
+            urn,
+            queued,
+            attempts: 1,
+        });
+        self.pending.last().unwrap()
+    }
+
+    /// Remove a URN from the outbox, returning its entry if it was queued.
+    pub fn remove(&mut self, urn: &Urn) -> Option<Entry> {
+        let ix = self.pending.iter().position(|e| &e.urn == urn)?;
+
+        Some(self.pending.remove(ix))
+    }
+
+    pub fn contains(&self, urn: &Urn) -> bool {
+        self.pending.iter().any(|e| &e.urn == urn)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Entry> {
+        self.pending.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
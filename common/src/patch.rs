@@ -1,4 +1,6 @@
 //! Patch-related functions and types.
+pub mod mbox;
+
 use std::convert::TryInto;
 use std::fmt;
 
@@ -11,12 +13,14 @@ use librad::PeerId;
 
 use git_trailers as trailers;
 use radicle_git_ext as git;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::cobs::patch as cob;
 use crate::project;
 
 pub const TAG_PREFIX: &str = "patches/";
+/// Prefix of the branches patches are imported into, eg. `patch/fix-the-thing`.
+pub const BRANCH_PREFIX: &str = "patch/";
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -27,9 +31,11 @@ pub enum Error {
 }
 
 /// A patch merge style.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum MergeStyle {
     /// A merge commit is created.
+    #[serde(rename = "merge-commit")]
     Commit,
     /// The branch is fast-forwarded to the patch's commit.
     FastForward,
@@ -219,6 +225,14 @@ pub struct MergeTargets {
     pub not_merged: Vec<(project::PeerInfo, git::Oid)>,
 }
 
+impl MergeTargets {
+    /// Only keep the unmerged targets of project delegates, eg. when proposing a patch
+    /// to the parent of a fork.
+    pub fn retain_delegates(&mut self) {
+        self.not_merged.retain(|(peer, _)| peer.delegate);
+    }
+}
+
 pub fn find_merge_targets<S>(
     head: &git2::Oid,
     storage: &S,
@@ -284,3 +298,72 @@ pub fn patch_commits<'a>(
 
     Ok(commits)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::convert::TryFrom;
+
+    use librad::SecretKey;
+
+    use crate::test;
+
+    #[test]
+    fn test_parent_targets() {
+        let (storage, _, _, project) = test::setup::profile();
+        let parent = project::Metadata::try_from(project).unwrap();
+        let repo = git2::Repository::open_bare(storage.as_ref().path()).unwrap();
+        let sig = git2::Signature::now("cloudhead", "cloudhead@radicle.xyz").unwrap();
+        let tree = repo.treebuilder(None).unwrap().write().unwrap();
+        let tree = repo.find_tree(tree).unwrap();
+        let head = repo
+            .commit(None, &sig, &sig, "Initial commit", &tree, &[])
+            .unwrap();
+        repo.reference(
+            &format!(
+                "refs/namespaces/{}/refs/heads/master",
+                parent.urn.encode_id()
+            ),
+            head,
+            false,
+            "test",
+        )
+        .unwrap();
+
+        let payload = project::payload(
+            "nakamoto-fork".to_owned(),
+            "Fork of nakamoto".to_owned(),
+            "master".to_owned(),
+        );
+        let fork = project::fork(&storage, &parent, payload).unwrap();
+        assert_eq!(
+            project::parent(&storage, &fork.urn()).unwrap(),
+            Some(parent.urn.clone())
+        );
+
+        let delegate = project::PeerInfo {
+            id: PeerId::from(SecretKey::new()),
+            person: None,
+            delegate: true,
+        };
+        let peer = project::PeerInfo {
+            id: PeerId::from(SecretKey::new()),
+            person: None,
+            delegate: false,
+        };
+        let mut targets = MergeTargets {
+            merged: vec![],
+            not_merged: vec![(peer, head.into()), (delegate.clone(), head.into())],
+        };
+        targets.retain_delegates();
+
+        assert_eq!(
+            targets
+                .not_merged
+                .iter()
+                .map(|(p, _)| p.id)
+                .collect::<Vec<_>>(),
+            vec![delegate.id]
+        );
+    }
+}
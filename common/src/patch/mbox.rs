@@ -0,0 +1,457 @@
+//! Mailbox (`git format-patch`) import and export of patches.
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, Context as _};
+
+use crate::git;
+
+/// Placeholder subject in cover letters generated by `git format-patch`.
+const COVER_SUBJECT_PLACEHOLDER: &str = "*** SUBJECT HERE ***";
+/// Placeholder body in cover letters generated by `git format-patch`.
+const COVER_BLURB_PLACEHOLDER: &str = "*** BLURB HERE ***";
+/// Fixed date of the message separators generated by `git format-patch`.
+const SEPARATOR_DATE: &str = "Mon Sep 17 00:00:00 2001";
+
+/// A single message of a mailbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    /// The message subject, with the `[PATCH ...]` prefix removed.
+    pub subject: String,
+    /// The prefix of the subject, eg. `PATCH v2 0/3`.
+    pub prefix: Option<String>,
+    /// The message body, up to the diff or signature.
+    pub body: String,
+    /// The raw message, including the `From ` line.
+    raw: String,
+}
+
+impl Message {
+    /// Whether this message is a cover letter, ie. `[PATCH 0/N]`.
+    pub fn is_cover_letter(&self) -> bool {
+        self.prefix
+            .as_deref()
+            .and_then(|p| p.split_whitespace().last())
+            .map_or(false, |n| n.starts_with("0/"))
+    }
+
+    fn parse(raw: &str) -> Self {
+        let mut lines = raw.lines().skip_while(|l| is_separator(l));
+        let mut subject = String::new();
+        let mut in_subject = false;
+
+        // Headers, until the first empty line.
+        for line in lines.by_ref() {
+            if line.is_empty() {
+                break;
+            }
+            if let Some(s) = line.strip_prefix("Subject:") {
+                subject = s.trim().to_owned();
+                in_subject = true;
+            } else if in_subject && line.starts_with(char::is_whitespace) {
+                // Folded header.
+                subject.push(' ');
+                subject.push_str(line.trim());
+            } else {
+                in_subject = false;
+            }
+        }
+
+        let (prefix, subject) = match subject.strip_prefix('[') {
+            Some(rest) => match rest.split_once(']') {
+                Some((prefix, subject)) => (Some(prefix.to_owned()), subject.trim().to_owned()),
+                None => (None, subject),
+            },
+            None => (None, subject),
+        };
+        let mut message = Self {
+            subject,
+            prefix,
+            body: String::new(),
+            raw: raw.to_owned(),
+        };
+        let cover_letter = message.is_cover_letter();
+        let mut body = Vec::new();
+
+        for line in lines {
+            // Start of the diffstat, or signature.
+            if line == "---" || line == "-- " || line.starts_with("diff --git ") {
+                break;
+            }
+            // Cover letters end with the commits, as a shortlog, eg. `Alice (2):`.
+            if cover_letter && is_shortlog_header(line) {
+                break;
+            }
+            body.push(line);
+        }
+        message.body = body.join("\n").trim().to_owned();
+        message
+    }
+}
+
+/// Whether a line is a message separator of a `git format-patch` mailbox, ie.
+/// `From <commit> Mon Sep 17 00:00:00 2001`.
+fn is_separator(line: &str) -> bool {
+    match line
+        .trim_end()
+        .strip_prefix("From ")
+        .and_then(|l| l.split_once(' '))
+    {
+        Some((oid, date)) => {
+            oid.len() == 40 && oid.chars().all(|c| c.is_ascii_hexdigit()) && date == SEPARATOR_DATE
+        }
+        None => false,
+    }
+}
+
+/// Whether a line starts a shortlog author section, eg. `Alice Liddell (2):`.
+fn is_shortlog_header(line: &str) -> bool {
+    if line.starts_with(char::is_whitespace) {
+        return false;
+    }
+    match line.strip_suffix("):").and_then(|l| l.rsplit_once(" (")) {
+        Some((name, count)) => {
+            !name.trim().is_empty()
+                && !count.is_empty()
+                && count.chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+/// A mailbox, as output by `git format-patch --stdout`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Mbox {
+    pub messages: Vec<Message>,
+}
+
+impl Mbox {
+    /// Parse a mailbox. Messages are delimited by `git format-patch` separator lines,
+    /// eg. `From <commit> Mon Sep 17 00:00:00 2001`.
+    pub fn parse(mbox: &str) -> Self {
+        let mut messages = Vec::new();
+        let mut current = String::new();
+
+        for line in mbox.split_inclusive('\n') {
+            if is_separator(line) && !current.trim().is_empty() {
+                messages.push(Message::parse(&current));
+                current.clear();
+            }
+            current.push_str(line);
+        }
+        if !current.trim().is_empty() {
+            messages.push(Message::parse(&current));
+        }
+
+        Self { messages }
+    }
+
+    /// The cover letter, if any.
+    pub fn cover_letter(&self) -> Option<&Message> {
+        self.messages.iter().find(|m| m.is_cover_letter())
+    }
+
+    /// The messages containing actual patches.
+    pub fn patches(&self) -> impl Iterator<Item = &Message> {
+        self.messages.iter().filter(|m| !m.is_cover_letter())
+    }
+
+    /// Get the patch title and description. Uses the cover letter if there is one,
+    /// and otherwise the first patch.
+    pub fn title(&self) -> Option<(String, String)> {
+        self.cover_letter()
+            .filter(|m| !m.subject.is_empty() && m.subject != COVER_SUBJECT_PLACEHOLDER)
+            .or_else(|| self.patches().next())
+            .map(|m| {
+                let body = if m.body == COVER_BLURB_PLACEHOLDER {
+                    String::new()
+                } else {
+                    m.body.clone()
+                };
+                (m.subject.clone(), body)
+            })
+    }
+
+    /// Return the mailbox without its cover letter, suitable for `git am`.
+    pub fn to_patches(&self) -> String {
+        self.patches()
+            .map(|m| m.raw.as_str())
+            .collect::<Vec<_>>()
+            .join("")
+    }
+}
+
+/// Apply the patches of a mailbox on top of the current branch, using `git am`.
+pub fn apply(repo: &Path, mbox: &Mbox) -> anyhow::Result<()> {
+    let mut child = Command::new("git")
+        .current_dir(repo)
+        .args(["am", "--3way", "--keep-cr"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("failed to open stdin of `git am`"))?
+        .write_all(mbox.to_patches().as_bytes())?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "`git am` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Apply the patches of a mailbox on a new branch created from `HEAD`, and check it out.
+///
+/// If the patches can't be applied, the previous `HEAD` is checked out again, and the
+/// new branch is deleted.
+pub fn apply_on_branch(repo: &Path, branch: &str, mbox: &Mbox) -> anyhow::Result<()> {
+    let previous = match git::git(repo, ["symbolic-ref", "--quiet", "--short", "HEAD"]) {
+        Ok(name) => name.trim().to_owned(),
+        // Detached `HEAD`.
+        Err(_) => git::git(repo, ["rev-parse", "HEAD"])?.trim().to_owned(),
+    };
+    git::git(repo, ["checkout", "-b", branch])?;
+
+    if let Err(err) = apply(repo, mbox) {
+        git::git(repo, ["am", "--abort"]).ok();
+        git::git(repo, ["checkout", &previous])
+            .and_then(|_| git::git(repo, ["branch", "-D", branch]))
+            .with_context(|| format!("failed to remove branch '{}'", branch))?;
+
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// Format the commits between `base` and `head` as a mailbox, with a cover letter
+/// containing the given title and description.
+pub fn format(
+    repo: &Path,
+    base: &git2::Oid,
+    head: &git2::Oid,
+    title: &str,
+    description: &str,
+) -> anyhow::Result<String> {
+    let range = format!("{}..{}", base, head);
+    let output = Command::new("git")
+        .current_dir(repo)
+        .args(["format-patch", "--stdout", "--cover-letter", &range])
+        .output()?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "`git format-patch` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let mbox = String::from_utf8_lossy(&output.stdout)
+        .replacen(COVER_SUBJECT_PLACEHOLDER, title, 1)
+        .replacen(COVER_BLURB_PLACEHOLDER, description, 1);
+
+    Ok(mbox)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MBOX: &str = "\
+From 0000000000000000000000000000000000000000 Mon Sep 17 00:00:00 2001
+From: Alice <alice@example.com>
+Date: Thu, 1 Sep 2022 10:00:00 +0200
+Subject: [PATCH v2 0/2] Add a fancy
+ new feature
+
+This series adds a new feature.
+
+It has two parts.
+
+Alice (2):
+  Add first part
+  Add second part
+
+ README | 2 ++
+ 1 file changed, 2 insertions(+)
+
+--
+2.37.0
+
+From 1111111111111111111111111111111111111111 Mon Sep 17 00:00:00 2001
+From: Alice <alice@example.com>
+Date: Thu, 1 Sep 2022 10:00:00 +0200
+Subject: [PATCH v2 1/2] Add first part
+
+Details of the first part.
+---
+ README | 1 +
+ 1 file changed, 1 insertion(+)
+
+diff --git a/README b/README
+--- a/README
++++ b/README
+@@ -0,0 +1 @@
++first
+--
+2.37.0
+
+From 2222222222222222222222222222222222222222 Mon Sep 17 00:00:00 2001
+From: Alice <alice@example.com>
+Date: Thu, 1 Sep 2022 10:00:00 +0200
+Subject: [PATCH v2 2/2] Add second part
+
+---
+diff --git a/README b/README
+--
+2.37.0
+";
+
+    #[test]
+    fn test_parse() {
+        let mbox = Mbox::parse(MBOX);
+
+        assert_eq!(mbox.messages.len(), 3);
+        assert_eq!(mbox.patches().count(), 2);
+
+        let cover = mbox.cover_letter().unwrap();
+        assert_eq!(cover.prefix.as_deref(), Some("PATCH v2 0/2"));
+        assert_eq!(cover.subject, "Add a fancy new feature");
+        assert_eq!(
+            cover.body,
+            "This series adds a new feature.\n\nIt has two parts."
+        );
+
+        let first = mbox.patches().next().unwrap();
+        assert_eq!(first.subject, "Add first part");
+        assert_eq!(first.body, "Details of the first part.");
+    }
+
+    #[test]
+    fn test_title() {
+        let mbox = Mbox::parse(MBOX);
+        let (title, description) = mbox.title().unwrap();
+
+        assert_eq!(title, "Add a fancy new feature");
+        assert_eq!(
+            description,
+            "This series adds a new feature.\n\nIt has two parts."
+        );
+
+        // Without a cover letter, the first patch is used.
+        let mbox = Mbox::parse(&mbox.to_patches());
+        let (title, description) = mbox.title().unwrap();
+
+        assert_eq!(title, "Add first part");
+        assert_eq!(description, "Details of the first part.");
+    }
+
+    #[test]
+    fn test_parse_body() {
+        let mbox = Mbox::parse(
+            "\
+From 1111111111111111111111111111111111111111 Mon Sep 17 00:00:00 2001
+From: Alice <alice@example.com>
+Subject: [PATCH] Fix the parser
+
+This fixes the following (see below):
+
+From the start, the parser was broken.
+Alice (2):
+---
+diff --git a/README b/README
+",
+        );
+        assert_eq!(mbox.messages.len(), 1);
+        assert_eq!(
+            mbox.messages[0].body,
+            "This fixes the following (see below):\n\n\
+             From the start, the parser was broken.\nAlice (2):"
+        );
+
+        assert!(is_separator(
+            "From 1111111111111111111111111111111111111111 Mon Sep 17 00:00:00 2001\n"
+        ));
+        assert!(!is_separator("From the start, the parser was broken."));
+        assert!(is_shortlog_header("Alice Liddell (12):"));
+        assert!(!is_shortlog_header("This fixes the following (see below):"));
+        assert!(!is_shortlog_header("  Add first part (2):"));
+    }
+
+    #[test]
+    fn test_apply_on_branch_failure() {
+        let tmp = crate::tempdir::TempDir::new("rad-mbox").unwrap();
+        let repo = tmp.path();
+
+        git::git(repo, ["init", "--quiet"]).unwrap();
+        std::fs::write(repo.join("README"), "hello\n").unwrap();
+        git::git(repo, ["add", "README"]).unwrap();
+        git::git(
+            repo,
+            [
+                "-c",
+                "user.name=Alice",
+                "-c",
+                "user.email=alice@example.com",
+                "commit",
+                "--quiet",
+                "-m",
+                "Initial commit",
+            ],
+        )
+        .unwrap();
+        let head = git::git(repo, ["symbolic-ref", "--short", "HEAD"]).unwrap();
+
+        // The patch doesn't apply, since `README` has different contents.
+        let mbox = Mbox::parse(
+            "\
+From 1111111111111111111111111111111111111111 Mon Sep 17 00:00:00 2001
+From: Alice <alice@example.com>
+Date: Thu, 1 Sep 2022 10:00:00 +0200
+Subject: [PATCH] Change README
+
+---
+ README | 2 +-
+ 1 file changed, 1 insertion(+), 1 deletion(-)
+
+diff --git a/README b/README
+index 0000000..1111111 100644
+--- a/README
++++ b/README
+@@ -1 +1 @@
+-goodbye
++hello world
+--
+2.37.0
+",
+        );
+        assert!(apply_on_branch(repo, "patch/change-readme", &mbox).is_err());
+
+        // The previous branch is checked out, and the new branch is gone.
+        assert_eq!(
+            git::git(repo, ["symbolic-ref", "--short", "HEAD"]).unwrap(),
+            head
+        );
+        assert!(git::git(repo, ["rev-parse", "--verify", "patch/change-readme"]).is_err());
+        assert!(!repo.join(".git").join("rebase-apply").exists());
+
+        // Importing again fails the same way, instead of finding an existing branch.
+        let err = apply_on_branch(repo, "patch/change-readme", &mbox).unwrap_err();
+        assert!(err.to_string().contains("git am"));
+    }
+
+    #[test]
+    fn test_to_patches() {
+        let mbox = Mbox::parse(MBOX);
+        let patches = mbox.to_patches();
+
+        assert!(patches.starts_with("From 1111111111111111111111111111111111111111"));
+        assert!(!patches.contains("This series adds a new feature."));
+    }
+}
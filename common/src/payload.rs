@@ -0,0 +1,278 @@
+//! Identity payload extensions.
+//!
+//! Extensions are stored in an identity payload as JSON objects, keyed by a namespace
+//! URL. Extensions set from the command line use short namespaces, eg. `matrix`, which
+//! map to URLs of the form `https://radicle.xyz/ext/matrix/v1`.
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use url::Url;
+
+use crate::person::Ens;
+use librad::identities::payload::HasNamespace;
+
+/// URL prefix of short extension namespaces.
+pub const NAMESPACE_PREFIX: &str = "https://radicle.xyz/ext/";
+
+/// URL prefix of the namespaces of the identity subjects, eg. the project name.
+const SUBJECT_PREFIX: &str = "https://radicle.xyz/link/identities/";
+
+/// Extensions with a known meaning, and how to label them.
+pub const KNOWN: &[(&str, &str)] = &[
+    ("website", "Website"),
+    ("matrix", "Matrix"),
+    ("team", "Team"),
+    ("security", "Security contact"),
+    ("fork", "Forked from"),
+    ("labels", "Labels"),
+    ("merge", "Merge policy"),
+];
+
+/// Get the namespace URL of a short extension namespace.
+pub fn namespace(name: &str) -> Url {
+    Url::parse(&format!("{}{}/v1", NAMESPACE_PREFIX, name)).expect("valid namespace URL")
+}
+
+/// An extension field assignment, eg. `matrix.handle=@alice:matrix.org`.
+/// An empty value removes the field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assignment {
+    /// Short namespace, eg. `matrix`.
+    pub namespace: String,
+    /// Field of the extension object, eg. `handle`.
+    pub key: String,
+    /// Field value.
+    pub value: String,
+}
+
+impl FromStr for Assignment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, value) = s.split_once('=').ok_or_else(|| {
+            format!(
+                "invalid assignment '{}', expected <namespace>.<key>=<value>",
+                s
+            )
+        })?;
+        let (namespace, key) = path
+            .split_once('.')
+            .ok_or_else(|| format!("invalid field '{}', expected <namespace>.<key>", path))?;
+
+        let valid = |s: &str| {
+            !s.is_empty()
+                && s.chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        };
+        if !valid(namespace) {
+            return Err(format!("invalid namespace '{}'", namespace));
+        }
+        if !valid(key) {
+            return Err(format!("invalid key '{}'", key));
+        }
+
+        Ok(Self {
+            namespace: namespace.to_owned(),
+            key: key.to_owned(),
+            value: value.to_owned(),
+        })
+    }
+}
+
+/// Apply assignments to a payload, in its JSON representation.
+pub fn assign(payload: &mut Value, assignments: &[Assignment]) -> Result<(), String> {
+    let exts = payload
+        .as_object_mut()
+        .ok_or_else(|| String::from("payload is not an object"))?;
+
+    for a in assignments {
+        let namespace = namespace(&a.namespace).to_string();
+        let ext = exts
+            .entry(namespace.clone())
+            .or_insert_with(|| Value::Object(Default::default()))
+            .as_object_mut()
+            .ok_or_else(|| format!("extension '{}' is not an object", a.namespace))?;
+
+        if a.value.is_empty() {
+            ext.remove(&a.key);
+        } else {
+            ext.insert(a.key.clone(), Value::String(a.value.clone()));
+        }
+        if ext.is_empty() {
+            exts.remove(&namespace);
+        }
+    }
+    Ok(())
+}
+
+/// A payload extension, for display.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Extension {
+    /// Extension label, eg. `Matrix`. Unknown extensions are labeled with their namespace.
+    pub label: String,
+    /// Extension value.
+    pub value: String,
+}
+
+impl fmt::Display for Extension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.label, self.value)
+    }
+}
+
+/// Get the extensions of a payload, in its JSON representation. Known extensions
+/// come first.
+pub fn extensions(payload: &Value) -> Vec<Extension> {
+    let exts = match payload.as_object() {
+        Some(exts) => exts,
+        None => return vec![],
+    };
+    let mut known = Vec::new();
+    let mut unknown = Vec::new();
+
+    for (namespace, val) in exts {
+        if namespace.starts_with(SUBJECT_PREFIX) {
+            continue;
+        }
+        if namespace == Ens::namespace().as_str() {
+            if let Ok(ens) = serde_json::from_value::<Ens>(val.clone()) {
+                known.push((
+                    0,
+                    Extension {
+                        label: String::from("ENS"),
+                        value: ens.name,
+                    },
+                ));
+                continue;
+            }
+        }
+        let name = namespace
+            .strip_prefix(NAMESPACE_PREFIX)
+            .and_then(|s| s.strip_suffix("/v1"));
+        let position = name.and_then(|n| KNOWN.iter().position(|(k, _)| *k == n));
+        let value = render(val);
+
+        match (name, position) {
+            (_, Some(i)) => known.push((
+                i + 1,
+                Extension {
+                    label: KNOWN[i].1.to_owned(),
+                    value,
+                },
+            )),
+            (Some(name), None) => unknown.push(Extension {
+                label: name.to_owned(),
+                value,
+            }),
+            (None, None) => unknown.push(Extension {
+                label: namespace.clone(),
+                value,
+            }),
+        }
+    }
+    known.sort_by_key(|(i, _)| *i);
+
+    known.into_iter().map(|(_, e)| e).chain(unknown).collect()
+}
+
+/// Render an extension value. Objects with a single field are rendered as that field's
+/// value.
+fn render(val: &Value) -> String {
+    match val {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(render).collect::<Vec<_>>().join(", "),
+        Value::Object(obj) => match obj.values().collect::<Vec<_>>().as_slice() {
+            [val] => render(val),
+            _ => obj
+                .iter()
+                .map(|(k, v)| format!("{}={}", k, render(v)))
+                .collect::<Vec<_>>()
+                .join(", "),
+        },
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_assignment() {
+        let a = Assignment::from_str("matrix.handle=@alice:matrix.org").unwrap();
+        assert_eq!(a.namespace, "matrix");
+        assert_eq!(a.key, "handle");
+        assert_eq!(a.value, "@alice:matrix.org");
+
+        let a = Assignment::from_str("team.name=").unwrap();
+        assert_eq!(a.value, "");
+
+        assert!(Assignment::from_str("matrix=alice").is_err());
+        assert!(Assignment::from_str("matrix.handle").is_err());
+        assert!(Assignment::from_str("Matrix.handle=alice").is_err());
+        assert!(Assignment::from_str(".handle=alice").is_err());
+    }
+
+    #[test]
+    fn test_assign() {
+        let mut payload = json!({
+            "https://radicle.xyz/link/identities/person/v1": { "name": "alice" }
+        });
+        let assignments = [
+            "matrix.handle=@alice:matrix.org",
+            "website.url=https://alice.xyz",
+        ]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect::<Vec<Assignment>>();
+
+        assign(&mut payload, &assignments).unwrap();
+        assert_eq!(
+            payload["https://radicle.xyz/ext/matrix/v1"],
+            json!({ "handle": "@alice:matrix.org" })
+        );
+        assert_eq!(
+            extensions(&payload),
+            vec![
+                Extension {
+                    label: String::from("Website"),
+                    value: String::from("https://alice.xyz"),
+                },
+                Extension {
+                    label: String::from("Matrix"),
+                    value: String::from("@alice:matrix.org"),
+                },
+            ]
+        );
+
+        assign(&mut payload, &["matrix.handle=".parse().unwrap()]).unwrap();
+        assert!(payload.get("https://radicle.xyz/ext/matrix/v1").is_none());
+    }
+
+    #[test]
+    fn test_extensions() {
+        let payload = json!({
+            "https://radicle.xyz/link/identities/person/v1": { "name": "alice" },
+            "https://radicle.xyz/ethereum/ens/v1": { "name": "alice.eth" },
+            "https://radicle.xyz/ext/pgp/v1": { "fingerprint": "ABCD", "server": "keys.openpgp.org" },
+            "https://radicle.xyz/ext/labels/v1": { "labels": ["bug", "good first issue"] },
+            "https://example.com/ext/v2": "value"
+        });
+
+        assert_eq!(
+            extensions(&payload)
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "ENS: alice.eth",
+                "Labels: bug, good first issue",
+                "https://example.com/ext/v2: value",
+                "pgp: fingerprint=ABCD, server=keys.openpgp.org",
+            ]
+        );
+    }
+}
@@ -10,11 +10,12 @@ use librad::crypto::BoxedSigner;
 use librad::identities::payload;
 use librad::identities::payload::HasNamespace;
 use librad::profile::Profile;
+use librad::PublicKey;
 
 use lnk_identities::{self, local, person};
 
 pub use librad::git::identities::person::verify;
-pub use person::get;
+pub use person::{get, merge};
 
 lazy_static::lazy_static! {
     static ref ENS_NAMESPACE: url::Url = "https://radicle.xyz/ethereum/ens/v1"
@@ -76,6 +77,39 @@ pub fn local(storage: &Storage) -> Result<LocalIdentity, local::Error> {
     local::default(storage)
 }
 
+/// Set the delegations of the local identity, ie. the device keys that can sign on its
+/// behalf. The update is signed with the storage's key, which must be a delegation.
+/// Returns the updated person.
+pub fn set_delegations(
+    storage: &Storage,
+    delegations: impl IntoIterator<Item = PublicKey>,
+) -> Result<Person> {
+    let id = local::default(storage)?;
+    let exts = id
+        .payload()
+        .exts()
+        .map(|(namespace, val)| payload::Ext {
+            namespace: namespace.clone(),
+            val: val.clone(),
+        })
+        .collect::<Vec<_>>();
+    let delegations = delegations.into_iter().collect::<Vec<_>>();
+
+    if delegations.is_empty() {
+        anyhow::bail!("an identity must have at least one delegation");
+    }
+    let new = person::update(
+        storage,
+        &id.urn(),
+        Some(id.urn()),
+        None,
+        exts,
+        Some(delegations.into_iter()),
+    )?;
+
+    Ok(new)
+}
+
 /// Set an ENS payload for the local identity.
 /// Returns the updated person.
 pub fn set_ens_payload(ens: Ens, storage: &Storage) -> Result<Person> {
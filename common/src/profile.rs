@@ -1,4 +1,7 @@
 //! User profile related functions.
+pub mod archive;
+
+use std::process::Command;
 use std::{env, fmt, path};
 
 use anyhow::{anyhow, Error, Result};
@@ -10,6 +13,7 @@ use librad::crypto::{
     keystore::{FileStorage, Keystore as _},
     PublicKey, SecretKey,
 };
+use librad::git::Urn;
 use librad::PeerId;
 use librad::{git::storage::ReadOnly, git::Storage, keystore::crypto::Crypto};
 
@@ -18,19 +22,47 @@ use crate::keys;
 
 /// Environment var that sets the radicle home directory.
 pub const RAD_HOME: &str = "RAD_HOME";
+/// Git configuration key that sets the profile to use in a repository.
+pub const CONFIG_PROFILE_KEY: &str = "rad.profile";
 
 /// Create a new profile.
 pub fn create<C: Crypto>(home: impl Into<LnkHome>, crypto: C) -> Result<(Profile, PeerId)>
+where
+    C::Error: fmt::Debug + fmt::Display + Send + Sync + 'static,
+    C::SecretBox: Serialize + DeserializeOwned,
+{
+    create_with_key(home, crypto, SecretKey::new())
+}
+
+/// Create a new profile with the given secret key, eg. when restoring a key backup.
+pub fn create_with_key<C: Crypto>(
+    home: impl Into<LnkHome>,
+    crypto: C,
+    key: SecretKey,
+) -> Result<(Profile, PeerId)>
 where
     C::Error: fmt::Debug + fmt::Display + Send + Sync + 'static,
     C::SecretBox: Serialize + DeserializeOwned,
 {
     let home = home.into();
-    let profile = Profile::new(&home)?;
+    let (profile, peer) = create_inactive(&home, crypto, key)?;
 
     Profile::set(&home, profile.id().clone())?;
 
-    let key = SecretKey::new();
+    Ok((profile, peer))
+}
+
+/// Create a new profile with the given secret key, without making it the active profile.
+pub fn create_inactive<C: Crypto>(
+    home: &LnkHome,
+    crypto: C,
+    key: SecretKey,
+) -> Result<(Profile, PeerId)>
+where
+    C::Error: fmt::Debug + fmt::Display + Send + Sync + 'static,
+    C::SecretBox: Serialize + DeserializeOwned,
+{
+    let profile = Profile::new(home)?;
     let mut store: FileStorage<C, PublicKey, SecretKey, _> =
         FileStorage::new(&profile.paths().keys_dir().join(keys::KEY_FILE), crypto);
 
@@ -68,6 +100,14 @@ pub fn default() -> Result<Profile, Error> {
     }
 }
 
+/// Get a profile by id. Fails if there is no such profile.
+pub fn get(id: &ProfileId) -> Result<Profile, Error> {
+    list()?
+        .into_iter()
+        .find(|p| p.id() == id)
+        .ok_or_else(|| anyhow!("profile '{}' not found", id))
+}
+
 /// Get a profile's name. If none is given, get the default profile's name.
 pub fn name(profile: Option<&Profile>) -> Result<String, Error> {
     let default = default()?;
@@ -96,9 +136,124 @@ pub fn set(id: &ProfileId) -> Result<(), Error> {
     Ok(())
 }
 
+/// Find the profile using the given key, if any.
+pub fn find(home: &LnkHome, peer: &PeerId) -> Result<Option<Profile>, Error> {
+    for profile in Profile::list(home)? {
+        if read_only(&profile)?.peer_id() == peer {
+            return Ok(Some(profile));
+        }
+    }
+    Ok(None)
+}
+
+/// Remove a profile, including its key and storage.
+pub fn remove(profile: &Profile) -> Result<(), Error> {
+    let root = profile
+        .paths()
+        .git_dir()
+        .parent()
+        .ok_or_else(|| anyhow!("profile {} has no root directory", profile.id()))?;
+    std::fs::remove_dir_all(root)?;
+
+    Ok(())
+}
+
 /// Open read-only storage.
 pub fn read_only(profile: &Profile) -> Result<ReadOnly, Error> {
     let storage = ReadOnly::open(profile.paths())?;
 
     Ok(storage)
 }
+
+/// Copy the given namespaces of a profile's storage into another profile's storage,
+/// eg. after a key rotation.
+///
+/// The source profile's own refs are copied as the refs of a remote peer, along with
+/// the refs of the other remotes. Only the identity refs are adopted as the destination
+/// profile's own refs: branches and signed refs are left to the destination's key.
+pub fn copy_storage(from: &Profile, to: &Profile, urns: &[Urn]) -> Result<(), Error> {
+    let to_peer = *read_only(to)?.peer_id();
+
+    for urn in urns {
+        copy_refs(from, to, urn)?;
+
+        let namespace = format!("refs/namespaces/{}/refs/", urn.encode_id());
+        let refs = git(
+            from.paths().git_dir(),
+            &["for-each-ref", "--format=%(refname)", &namespace],
+        )?;
+        let own = format!("{}remotes/{}/", namespace, to_peer);
+        let refspecs = refs
+            .lines()
+            .filter(|r| !r.starts_with(&own))
+            .filter(|r| {
+                let name = r.trim_start_matches(&namespace);
+
+                name.starts_with("remotes/")
+                    || name == "rad/id"
+                    || name == "rad/self"
+                    || name.starts_with("rad/ids/")
+            })
+            .map(|r| format!("+{}:{}", r, r))
+            .collect::<Vec<_>>();
+
+        fetch(from, to, &refspecs)?;
+    }
+    Ok(())
+}
+
+/// Copy a profile's own refs of a namespace into another profile's storage, as the refs
+/// of a remote peer. This is how the other profile would see them after fetching them.
+pub fn copy_refs(from: &Profile, to: &Profile, urn: &Urn) -> Result<(), Error> {
+    let peer = *read_only(from)?.peer_id();
+    let namespace = format!("refs/namespaces/{}/refs/", urn.encode_id());
+    let refs = git(
+        from.paths().git_dir(),
+        &["for-each-ref", "--format=%(refname)", &namespace],
+    )?;
+    let refspecs = refs
+        .lines()
+        .filter_map(|r| {
+            let name = r.trim_start_matches(&namespace);
+            if name.starts_with("remotes/") {
+                None
+            } else {
+                Some(format!("+{}:{}remotes/{}/{}", r, namespace, peer, name))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    fetch(from, to, &refspecs)
+}
+
+/// Fetch refs from a profile's storage into another profile's storage.
+fn fetch(from: &Profile, to: &Profile, refspecs: &[String]) -> Result<(), Error> {
+    if refspecs.is_empty() {
+        return Ok(());
+    }
+    let source = from.paths().git_dir().to_string_lossy().into_owned();
+    let mut args = vec!["fetch", "--quiet", "--no-tags", source.as_str()];
+    args.extend(refspecs.iter().map(|r| r.as_str()));
+
+    git(to.paths().git_dir(), &args)?;
+
+    Ok(())
+}
+
+/// Run a git command on a profile's storage.
+pub(crate) fn git(git_dir: &path::Path, args: &[&str]) -> Result<String, Error> {
+    let output = Command::new("git")
+        .arg("--git-dir")
+        .arg(git_dir)
+        .args(args)
+        .output()?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "`git {}` failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
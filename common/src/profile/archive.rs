@@ -0,0 +1,230 @@
+//! Encrypted profile archives, to move an identity to another device.
+//!
+//! An archive contains the profile's key file, a git bundle of the local identity's
+//! refs, and the profile configuration. Its contents are sealed with the key passphrase.
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context as _};
+use serde::{Deserialize, Serialize};
+
+use librad::crypto::keystore::crypto::{Crypto, Pwhash};
+use librad::crypto::keystore::pinentry::SecUtf8;
+use librad::git::Urn;
+use librad::profile::{LnkHome, Profile};
+use librad::PeerId;
+
+use crate::config::{Config, SignerConfig};
+use crate::signer::ZeroizingSecretKey;
+use crate::tempdir::TempDir;
+use crate::{keys, person};
+
+/// Current archive format version.
+pub const VERSION: u32 = 1;
+
+type SecretBox = <Pwhash<SecUtf8> as Crypto>::SecretBox;
+
+/// A sealed profile archive.
+#[derive(Serialize, Deserialize)]
+pub struct Archive {
+    /// Archive format version.
+    pub version: u32,
+    /// Peer id of the archived profile.
+    pub peer: PeerId,
+    /// Sealed [`Contents`].
+    sealed: SecretBox,
+}
+
+/// Unsealed archive contents.
+#[derive(Serialize, Deserialize)]
+struct Contents {
+    /// Base64-encoded key file, sealed with the same passphrase as the archive.
+    key: String,
+    /// The local identity, if any.
+    #[serde(default)]
+    person: Option<String>,
+    /// Base64-encoded git bundle of the local identity's refs.
+    #[serde(default)]
+    bundle: Option<String>,
+    /// Profile configuration, ie. `config.toml`.
+    #[serde(default)]
+    config: Option<String>,
+}
+
+impl Archive {
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        let archive: Self =
+            serde_json::from_str(&json).context("file is not a radicle profile archive")?;
+
+        if archive.version != VERSION {
+            anyhow::bail!("unsupported archive version {}", archive.version);
+        }
+        Ok(archive)
+    }
+
+    /// Write the archive to a new file, only readable by the current user.
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        keys::write_secret(path, json.as_bytes())
+            .with_context(|| format!("couldn't write archive to {:?}", path))?;
+
+        Ok(())
+    }
+}
+
+/// Export a profile. The passphrase must be the profile's key passphrase.
+pub fn export(profile: &Profile, passphrase: SecUtf8) -> anyhow::Result<Archive> {
+    let key = keys::load_secret_key(profile, passphrase.clone()).context("invalid passphrase")?;
+    let peer = PeerId::from(key.secret_key().clone());
+    let key_file = fs::read(profile.paths().keys_dir().join(keys::KEY_FILE))?;
+
+    let storage = super::read_only(profile)?;
+    let person = storage.config()?.user()?;
+    let bundle = match &person {
+        Some(urn) => Some(bundle(profile.paths().git_dir(), urn, |_| true)?),
+        None => None,
+    };
+    let config = fs::read_to_string(Config::path(profile)).ok();
+
+    let contents = Contents {
+        key: base64::encode(key_file),
+        person: person.map(|urn| urn.to_string()),
+        bundle: bundle.map(base64::encode),
+        config,
+    };
+    let json = zeroize::Zeroizing::new(serde_json::to_vec(&contents)?);
+    let sealed = keys::pwhash(passphrase)
+        .seal(&*json)
+        .map_err(|e| anyhow!("couldn't seal archive: {}", e))?;
+
+    Ok(Archive {
+        version: VERSION,
+        peer,
+        sealed,
+    })
+}
+
+/// Import an archive as a new profile, and make it the active profile.
+///
+/// Fails if the passphrase is invalid, if the archived key doesn't match the archive's
+/// peer id, or if a profile with the same key already exists.
+pub fn import(
+    home: &LnkHome,
+    archive: Archive,
+    passphrase: SecUtf8,
+) -> anyhow::Result<(Profile, Option<Urn>)> {
+    let json = keys::pwhash(passphrase.clone())
+        .unseal(archive.sealed)
+        .map_err(|_| anyhow!("invalid passphrase"))?;
+    let contents: Contents = serde_json::from_slice(json.unsecure())?;
+    let key_file = zeroize::Zeroizing::new(base64::decode(&contents.key)?);
+
+    // The key store only reads keys from files.
+    let tmp = TempDir::new("rad-import")?;
+    let tmp_key = tmp.path().join(keys::KEY_FILE);
+    keys::write_secret(&tmp_key, &key_file)?;
+    let key = keys::load_secret_key_from(&tmp_key, passphrase.clone())
+        .context("couldn't unseal archived key")?;
+    drop(tmp);
+
+    let peer = PeerId::from(key.secret_key().clone());
+
+    if peer != archive.peer {
+        anyhow::bail!(
+            "archived key {} doesn't match archive peer id {}",
+            peer,
+            archive.peer
+        );
+    }
+    if let Some(existing) = super::find(home, &peer)? {
+        anyhow::bail!("profile {} already uses key {}", existing.id(), peer);
+    }
+
+    // The profile is only made active once it is fully restored, and removed otherwise.
+    let (profile, _) =
+        super::create_inactive(home, keys::pwhash(passphrase), key.secret_key().clone())?;
+    let person = match restore(&profile, key, contents) {
+        Ok(person) => person,
+        Err(err) => {
+            super::remove(&profile).ok();
+            return Err(err);
+        }
+    };
+    Profile::set(home, profile.id().clone())?;
+
+    Ok((profile, person))
+}
+
+/// Restore the local identity and configuration of an archive into a new profile.
+fn restore(
+    profile: &Profile,
+    key: ZeroizingSecretKey,
+    contents: Contents,
+) -> anyhow::Result<Option<Urn>> {
+    let person = contents.person.map(|urn| urn.parse::<Urn>()).transpose()?;
+    if let (Some(urn), Some(bundle)) = (&person, &contents.bundle) {
+        unbundle(profile.paths().git_dir(), urn, &base64::decode(bundle)?)?;
+
+        let storage = keys::storage(profile, key)?;
+        let identity = person::get(&storage, urn)?
+            .ok_or_else(|| anyhow!("identity {} not found in archive", urn))?;
+        person::set_local(&storage, &identity)?;
+    }
+
+    // Restore the configuration. Key file paths refer to the old device, and are dropped.
+    let mut config = match contents.config {
+        Some(config) => toml::from_str(&config).context("invalid archived configuration")?,
+        None => Config::default(),
+    };
+    if let Some(SignerConfig::KeyFile { path, .. }) = &mut config.signer {
+        *path = None;
+    }
+    config.write(Config::path(profile))?;
+
+    Ok(person)
+}
+
+/// Create a git bundle of an identity's refs, keeping only the refs matching `filter`.
+pub(crate) fn bundle(
+    git_dir: &Path,
+    urn: &Urn,
+    filter: impl Fn(&str) -> bool,
+) -> anyhow::Result<Vec<u8>> {
+    let namespace = format!("refs/namespaces/{}/", urn.encode_id());
+    let refs = super::git(
+        git_dir,
+        &["for-each-ref", "--format=%(refname)", &namespace],
+    )?;
+    let refs = refs.lines().filter(|r| filter(r)).collect::<Vec<_>>();
+
+    if refs.is_empty() {
+        anyhow::bail!("identity {} not found in storage", urn);
+    }
+    let tmp = TempDir::new("rad-export")?;
+    let path = tmp.path().join("identity.bundle");
+    let path_str = path.to_string_lossy();
+    let mut args = vec!["bundle", "create", path_str.as_ref()];
+    args.extend(refs);
+
+    super::git(git_dir, &args)?;
+    let bundle = fs::read(&path)?;
+
+    Ok(bundle)
+}
+
+/// Fetch an identity's refs from a git bundle.
+pub(crate) fn unbundle(git_dir: &Path, urn: &Urn, bundle: &[u8]) -> anyhow::Result<()> {
+    let tmp = TempDir::new("rad-import")?;
+    let path = tmp.path().join("identity.bundle");
+    keys::write_secret(&path, bundle)?;
+
+    let namespace = format!("refs/namespaces/{}", urn.encode_id());
+    let refspec = format!("{}/*:{}/*", namespace, namespace);
+    super::git(
+        git_dir,
+        &["fetch", "--quiet", &path.to_string_lossy(), &refspec],
+    )?;
+
+    Ok(())
+}
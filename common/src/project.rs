@@ -7,26 +7,28 @@ use std::path::PathBuf;
 
 use anyhow::{anyhow, Result};
 use either::Either;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use url::Url;
 
 use librad::canonical::Cstring;
 use librad::crypto::BoxedSigner;
+use librad::git::identities::project::heads::DefaultBranchHead;
 use librad::git::identities::{self, project, Project};
 use librad::git::local::transport;
 use librad::git::local::url::LocalUrl;
+use librad::git::refs::Refs;
 use librad::git::storage::{ReadOnly, Storage};
 use librad::git::tracking;
 use librad::git::types::remote::Remote;
 use librad::git::types::{Namespace, Reference};
 use librad::git::Urn;
 use librad::git_ext::{OneLevel, RefLike};
-use librad::identities::payload::{self, ProjectPayload};
+use librad::identities::payload::{self, HasNamespace, ProjectPayload};
 use librad::identities::SomeIdentity;
-use librad::identities::{Person, VerifiedProject};
+use librad::identities::{IndirectDelegation, Person, VerifiedProject};
 use librad::paths::Paths;
 use librad::profile::Profile;
-use librad::PeerId;
+use librad::{PeerId, PublicKey};
 
 use lnk_identities;
 use lnk_identities::working_copy_dir::WorkingCopyDir;
@@ -41,6 +43,53 @@ pub const URL_SCHEME: &str = "rad";
 /// Prefix for remote tracking branches from peers.
 pub const PEER_PREFIX: &str = "peers";
 
+lazy_static::lazy_static! {
+    static ref FORK_NAMESPACE: Url = common::payload::namespace("fork");
+    static ref LABELS_NAMESPACE: Url = common::payload::namespace("labels");
+    static ref MERGE_NAMESPACE: Url = common::payload::namespace("merge");
+}
+
+/// Fork payload extension, recording the project a fork was created from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Fork {
+    pub parent: Urn,
+}
+
+impl payload::HasNamespace for Fork {
+    fn namespace() -> &'static Url {
+        &FORK_NAMESPACE
+    }
+}
+
+/// Labels payload extension, listing the issue labels a project uses.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Labels {
+    pub labels: Vec<String>,
+}
+
+impl payload::HasNamespace for Labels {
+    fn namespace() -> &'static Url {
+        &LABELS_NAMESPACE
+    }
+}
+
+/// Merge policy payload extension.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MergePolicy {
+    /// How patches must be merged. Any style is allowed if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<common::patch::MergeStyle>,
+    /// Whether patch commits must be verified before merging.
+    #[serde(default)]
+    pub verify: bool,
+}
+
+impl payload::HasNamespace for MergePolicy {
+    fn namespace() -> &'static Url {
+        &MERGE_NAMESPACE
+    }
+}
+
 /// Project indirect contributor identity.
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct PeerIdentity {
@@ -48,6 +97,9 @@ pub struct PeerIdentity {
     pub urn: Urn,
     pub name: String,
     pub ens: Option<Ens>,
+    /// Payload extensions, eg. a Matrix handle.
+    #[serde(default)]
+    pub extensions: Vec<common::payload::Extension>,
 }
 
 impl PeerIdentity {
@@ -62,11 +114,21 @@ impl PeerIdentity {
                 Ok(e) => e,
                 _ => None,
             };
+            // The ENS name is already in `ens`.
+            let extensions = serde_json::to_value(person.payload())
+                .map(|mut p| {
+                    if let Some(exts) = p.as_object_mut() {
+                        exts.remove(Ens::namespace().as_str());
+                    }
+                    common::payload::extensions(&p)
+                })
+                .unwrap_or_default();
 
             return Ok(Some(PeerIdentity {
                 urn: person.urn(),
                 name: person.subject().name.to_string(),
                 ens,
+                extensions,
             }));
         }
         Ok(None)
@@ -93,6 +155,14 @@ impl PeerInfo {
         }
     }
 
+    /// Payload extensions of the peer identity, if known.
+    pub fn extensions(&self) -> &[common::payload::Extension] {
+        match &self.person {
+            Some(person) => &person.extensions,
+            None => &[],
+        }
+    }
+
     pub fn get<S: AsRef<ReadOnly>>(peer_id: &PeerId, project: &Metadata, storage: &S) -> PeerInfo {
         let delegate = project.delegates.iter().any(|d| d.contains(peer_id));
         let reference = project.peer_self(peer_id, storage);
@@ -115,7 +185,7 @@ impl PeerInfo {
 }
 
 /// Project delegate.
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "camelCase", tag = "type")]
 pub enum Delegate {
     /// Direct delegation, ie. public key.
@@ -140,6 +210,14 @@ impl Delegate {
             Self::Indirect { ids, .. } => ids.contains(other),
         }
     }
+
+    /// Get the keys of the delegate. A personal identity can have more than one.
+    pub fn peers(&self) -> Vec<PeerId> {
+        match self {
+            Self::Direct { id } => vec![*id],
+            Self::Indirect { ids, .. } => ids.iter().copied().collect(),
+        }
+    }
 }
 
 /// Project metadata.
@@ -273,8 +351,13 @@ pub fn payload(name: String, description: String, default_branch: String) -> pay
 
 /// Create a new project identity.
 pub fn create(payload: payload::Project, storage: &Storage) -> anyhow::Result<Project> {
+    create_with_payload(ProjectPayload::new(payload), storage)
+}
+
+/// Create a new project identity from a payload with extensions, delegated to the
+/// local identity.
+pub fn create_with_payload(payload: ProjectPayload, storage: &Storage) -> anyhow::Result<Project> {
     let whoami = person::local(storage)?;
-    let payload = ProjectPayload::new(payload);
     let delegations = identities::IndirectDelegation::try_from_iter(iter::once(Either::Right(
         whoami.clone().into_inner().into_inner(),
     )))?;
@@ -283,6 +366,66 @@ pub fn create(payload: payload::Project, storage: &Storage) -> anyhow::Result<Pr
     Ok(project)
 }
 
+/// Create a fork of a project: a new project identity that records its parent, and whose
+/// default branch starts at the parent's default branch head.
+pub fn fork(
+    storage: &Storage,
+    parent: &Metadata,
+    payload: payload::Project,
+) -> anyhow::Result<Project> {
+    let head = match project::heads::default_branch_head(storage, parent.verified(storage)?) {
+        Ok(DefaultBranchHead::Head { target, .. }) => target,
+        _ => get_local_head(storage, &parent.urn, &parent.default_branch.to_string())?.ok_or_else(
+            || {
+                anyhow!(
+                    "couldn't determine the default branch head of project {}",
+                    parent.urn
+                )
+            },
+        )?,
+    };
+    let mut payload = ProjectPayload::new(payload);
+    payload.set_ext(Fork {
+        parent: parent.urn.clone(),
+    })?;
+
+    let project = create_with_payload(payload, storage)?;
+    let repo = git::Repository::open_bare(storage.as_ref().path())?;
+    repo.reference(
+        &format!(
+            "refs/namespaces/{}/refs/heads/{}",
+            project.urn().encode_id(),
+            parent.default_branch
+        ),
+        head,
+        false,
+        &format!("fork: {}", parent.urn),
+    )?;
+    Refs::update(storage, &project.urn())?;
+
+    Ok(project)
+}
+
+/// Get the parent of a project, if it is a fork.
+pub fn parent<S>(storage: &S, urn: &Urn) -> anyhow::Result<Option<Urn>>
+where
+    S: AsRef<ReadOnly>,
+{
+    Ok(ext::<_, Fork>(storage, urn)?.map(|f| f.parent))
+}
+
+/// Get a payload extension of a project.
+pub fn ext<S, T>(storage: &S, urn: &Urn) -> anyhow::Result<Option<T>>
+where
+    S: AsRef<ReadOnly>,
+    T: payload::HasNamespace + DeserializeOwned,
+{
+    let project = lnk_identities::project::get(storage, urn)?
+        .ok_or_else(|| anyhow!("project {} not found", urn))?;
+
+    Ok(project.payload().get_ext::<T>()?)
+}
+
 /// Initialize a repo as a project.
 pub fn init(
     project: &Project,
@@ -434,6 +577,116 @@ where
     Ok(meta)
 }
 
+/// A project identity update that isn't signed by a quorum of delegates yet.
+///
+/// An update must be signed by a majority of the proposed delegates, as well as by a
+/// majority of the current ones. Signatures are counted against the proposed delegates,
+/// unless they already have a majority, in which case the current delegates' majority
+/// is the one missing.
+#[derive(Debug)]
+pub struct PendingUpdate {
+    /// The latest, unverified revision of the project identity.
+    pub project: Project,
+    /// Delegates whose majority is missing.
+    pub delegates: Vec<Delegate>,
+    /// Of these delegates, the ones that signed the update.
+    pub signed: Vec<Delegate>,
+    /// Number of delegate signatures required to verify the update.
+    pub quorum: usize,
+}
+
+/// Number of delegates that have to sign a project identity update, ie. a majority.
+pub fn quorum(delegates: usize) -> usize {
+    delegates / 2 + 1
+}
+
+/// Get the pending identity update of a project, if any.
+pub fn pending(storage: &Storage, urn: &Urn) -> anyhow::Result<Option<PendingUpdate>> {
+    let latest = identities::project::get(storage, urn)?
+        .ok_or_else(|| anyhow!("project {} not found", urn))?;
+    let verified = identities::project::verify(storage, urn)?
+        .ok_or_else(|| anyhow!("project {} not found", urn))?;
+
+    if latest.revision == verified.revision {
+        return Ok(None);
+    }
+    let signed = |delegates: &[Delegate]| {
+        delegates
+            .iter()
+            .filter(|d| {
+                latest
+                    .signatures
+                    .keys()
+                    .any(|k| d.contains(&PeerId::from(*k)))
+            })
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    let proposed = Metadata::try_from(latest.clone())?.delegates;
+    let current = Metadata::try_from(verified.into_inner())?.delegates;
+
+    let (delegates, signed) = match signed(&proposed) {
+        signed if signed.len() < quorum(proposed.len()) => (proposed, signed),
+        _ => {
+            let signed = signed(&current);
+            (current, signed)
+        }
+    };
+
+    Ok(Some(PendingUpdate {
+        quorum: quorum(delegates.len()),
+        project: latest,
+        delegates,
+        signed,
+    }))
+}
+
+/// Get a project identity as seen by one of its peers, ie. the peer's latest revision.
+pub fn peer_revision(
+    storage: &Storage,
+    urn: &Urn,
+    peer: &PeerId,
+) -> anyhow::Result<Option<Project>> {
+    if peer == storage.peer_id() {
+        return Ok(identities::project::get(storage, urn)?);
+    }
+    let mut reference = Reference::rad_id(Namespace::from(urn.clone()));
+    reference.remote = Some(*peer);
+
+    let urn = Urn::try_from(reference).map_err(|e| anyhow!(e))?;
+    let project = identities::project::get(storage, &urn)?;
+
+    Ok(project)
+}
+
+/// Adopt a peer's revision of a project identity, signing it with the storage's key if
+/// we are a delegate.
+pub fn merge(storage: &Storage, urn: &Urn, peer: PeerId) -> anyhow::Result<Project> {
+    let project = identities::project::merge(storage, urn, peer)?;
+
+    Ok(project)
+}
+
+/// Update the delegations of a project. The update is signed with the storage's key,
+/// and only takes effect once signed by a [`quorum`] of the current delegates.
+pub fn set_delegations(
+    storage: &Storage,
+    urn: &Urn,
+    delegations: impl IntoIterator<Item = Either<PublicKey, Person>>,
+) -> anyhow::Result<Project> {
+    let delegations = IndirectDelegation::try_from_iter(delegations)
+        .map_err(|e| anyhow!("invalid delegations: {}", e))?;
+    let project = identities::project::update(
+        storage,
+        urn,
+        None,
+        None::<ProjectPayload>,
+        Some(delegations),
+    )?;
+
+    Ok(project)
+}
+
 /// Get the personal identity associated with a project's peer.
 pub fn person<S>(storage: &S, project: Urn, peer: &PeerId) -> anyhow::Result<Option<Person>>
 where
@@ -535,10 +788,13 @@ pub struct SetupRemote<'a> {
     pub fetch: bool,
     /// Whether or not to setup an upstream tracking branch.
     pub upstream: bool,
+    /// Prefix of the upstream tracking branch, eg. `remotes`. Defaults to [`PEER_PREFIX`].
+    pub upstream_prefix: Option<String>,
 }
 
 impl<'a> SetupRemote<'a> {
-    /// Run the setup for the given peer.
+    /// Run the setup for the given peer. If the remote name is already used by another
+    /// peer, a suffix is added to it; see [`SetupRemote::remote_name`].
     pub fn run(
         &self,
         peer: &PeerId,
@@ -548,7 +804,7 @@ impl<'a> SetupRemote<'a> {
         let repo = self.repo;
         let urn = &self.project.urn;
 
-        // TODO: Handle conflicts in remote name.
+        let name = self.remote_name(peer, name)?;
         let peer_prefix = format!("{}/{}", PEER_PREFIX, name);
         let remote_name = format!("{}/rad", peer_prefix);
         let mut remote = self::remote(urn, peer, &remote_name)?;
@@ -561,15 +817,228 @@ impl<'a> SetupRemote<'a> {
         }
         // Setup remote-tracking branch.
         if self.upstream {
-            // TODO: If this fails because the branch already exists, suggest how to specify a
-            // different branch name or prefix.
-            let branch =
-                git::set_tracking(repo.path(), &peer_prefix, &self.project.default_branch)?;
+            let branch = git::set_tracking_branch(
+                repo.path(),
+                &self.branch_name(&name),
+                &peer_prefix,
+                &self.project.default_branch,
+            )?;
 
             return Ok(Some((remote, branch)));
         }
         Ok(None)
     }
+
+    /// Get a remote name for the given peer that doesn't conflict with the remotes and
+    /// tracking branches of other peers. If the name is taken, a numeric suffix is
+    /// added, eg. `alice-2`.
+    pub fn remote_name(&self, peer: &PeerId, name: &str) -> anyhow::Result<String> {
+        let remotes = git::remotes(self.repo)?;
+        let name = (1..)
+            .map(|n| {
+                if n == 1 {
+                    name.to_owned()
+                } else {
+                    format!("{}-{}", name, n)
+                }
+            })
+            .find(|candidate| self.is_available(peer, candidate, &remotes))
+            .expect("SetupRemote::remote_name: there is always an available name");
+
+        Ok(name)
+    }
+
+    /// Name of the upstream tracking branch for the given remote name.
+    fn branch_name(&self, name: &str) -> String {
+        let prefix = self.upstream_prefix.as_deref().unwrap_or(PEER_PREFIX);
+        let branch: &str = &self.project.default_branch;
+
+        format!("{}/{}/{}", prefix.trim_end_matches('/'), name, branch)
+    }
+
+    fn is_available(&self, peer: &PeerId, name: &str, remotes: &[(String, PeerId)]) -> bool {
+        let remote = format!("{}/{}/rad", PEER_PREFIX, name);
+
+        // An existing remote with this name must be for the same peer.
+        if self.repo.find_remote(&remote).is_ok()
+            && !remotes.iter().any(|(r, p)| r == &remote && p == peer)
+        {
+            return false;
+        }
+        if !self.upstream {
+            return true;
+        }
+
+        // An existing tracking branch with this name must track the same remote.
+        let branch = self.branch_name(name);
+        let default_branch: &str = &self.project.default_branch;
+        let expected = format!("refs/remotes/{}/heads/{}", remote, default_branch);
+
+        match self
+            .repo
+            .branch_upstream_name(&format!("refs/heads/{}", branch))
+        {
+            Ok(upstream) => upstream.as_str() == Some(expected.as_str()),
+            Err(err) if err.code() == git::ErrorCode::NotFound => self
+                .repo
+                .find_branch(&branch, git::BranchType::Local)
+                .is_err(),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Result of renaming a remote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteRename {
+    /// The new remote name.
+    pub remote: String,
+    /// Local branches that were renamed, as `(old, new)` pairs.
+    pub branches: Vec<(String, String)>,
+}
+
+/// Rename a peer remote, along with its remote-tracking refs and the local branches
+/// tracking it.
+///
+/// Remotes setup by [`SetupRemote`], eg. `peers/alice/rad`, are renamed to
+/// `peers/<name>/rad`, and their tracking branches, eg. `peers/alice/master`, are moved
+/// under the new prefix. Other remotes are renamed to `<name>`.
+///
+/// A remote of the new name for the same peer, eg. after tracking the peer under
+/// another name, is reused. Conflicts are checked before any change is made, and
+/// changes are rolled back if the rename fails.
+pub fn rename_remote(
+    repo: &git::Repository,
+    urn: &Urn,
+    from: &str,
+    name: &str,
+) -> anyhow::Result<RemoteRename> {
+    let peer = git::remotes(repo)?
+        .into_iter()
+        .find(|(r, _)| r == from)
+        .map(|(_, peer)| peer)
+        .ok_or_else(|| anyhow!("remote '{}' is not a radicle peer remote", from))?;
+
+    let old_prefix = from
+        .strip_prefix(&format!("{}/", PEER_PREFIX))
+        .and_then(|s| s.strip_suffix("/rad"))
+        .map(|n| format!("{}/{}/", PEER_PREFIX, n));
+    let (to, new_prefix) = match old_prefix {
+        Some(_) => (
+            format!("{}/{}/rad", PEER_PREFIX, name),
+            Some(format!("{}/{}/", PEER_PREFIX, name)),
+        ),
+        None => (name.to_owned(), None),
+    };
+
+    // Whether the new remote is created by this rename, as opposed to reused.
+    let created = match git::remotes(repo)?.into_iter().find(|(r, _)| *r == to) {
+        Some((_, p)) if p == peer && to != from => false,
+        Some(_) => anyhow::bail!("remote '{}' already exists", to),
+        None if repo.find_remote(&to).is_ok() => {
+            anyhow::bail!("remote '{}' already exists", to)
+        }
+        None => true,
+    };
+
+    // Remote-tracking refs to move, eg. `refs/remotes/peers/alice/rad/heads/master`.
+    let old_refs = format!("refs/remotes/{}/", from);
+    let mut refs = Vec::new();
+    for r in repo.references_glob(&format!("{}*", old_refs))? {
+        let r = r?;
+        if let (Some(name), Some(oid)) = (r.name(), r.target()) {
+            let suffix = name.strip_prefix(&old_refs).unwrap_or(name).to_owned();
+            refs.push((suffix, oid));
+        }
+    }
+
+    // Local branches tracking the remote, with their new name and upstream.
+    let mut branches = Vec::new();
+    for branch in repo.branches(Some(git::BranchType::Local))? {
+        let (branch, _) = branch?;
+        let branch_name = match branch.name()? {
+            Some(name) => name.to_owned(),
+            None => continue,
+        };
+        let upstream = match repo.branch_upstream_name(&format!("refs/heads/{}", branch_name)) {
+            Ok(upstream) => upstream.as_str().unwrap_or_default().to_owned(),
+            Err(_) => continue,
+        };
+        if let Some(suffix) = upstream.strip_prefix(&old_refs) {
+            let new_name = match (&old_prefix, &new_prefix) {
+                (Some(old), Some(new)) => match branch_name.strip_prefix(old.as_str()) {
+                    Some(rest) => format!("{}{}", new, rest),
+                    None => branch_name.clone(),
+                },
+                _ => branch_name.clone(),
+            };
+            if new_name != branch_name
+                && repo.find_branch(&new_name, git::BranchType::Local).is_ok()
+            {
+                anyhow::bail!("branch '{}' already exists", new_name);
+            }
+            branches.push((branch_name, new_name, suffix.to_owned()));
+        }
+    }
+
+    // Apply the changes.
+    if created {
+        let mut remote = self::remote(urn, &peer, &to)?;
+        remote.save(repo)?;
+    }
+
+    let apply = || -> anyhow::Result<()> {
+        for (suffix, oid) in &refs {
+            repo.reference(
+                &format!("refs/remotes/{}/{}", to, suffix),
+                *oid,
+                false,
+                "rad: rename remote",
+            )?;
+        }
+        for (old, new, upstream) in &branches {
+            let mut branch = repo.find_branch(old, git::BranchType::Local)?;
+            if old != new {
+                branch = branch.rename(new, false)?;
+            }
+            branch.set_upstream(Some(&format!("{}/{}", to, upstream)))?;
+        }
+        Ok(())
+    };
+
+    if let Err(err) = apply() {
+        // Roll back: restore the branches, and remove the new remote along with its refs,
+        // unless it was reused.
+        for (old, new, upstream) in &branches {
+            if let Ok(mut branch) = repo
+                .find_branch(new, git::BranchType::Local)
+                .or_else(|_| repo.find_branch(old, git::BranchType::Local))
+            {
+                if old != new {
+                    if let Ok(b) = branch.rename(old, false) {
+                        branch = b;
+                    }
+                }
+                branch
+                    .set_upstream(Some(&format!("{}/{}", from, upstream)))
+                    .ok();
+            }
+        }
+        if created {
+            repo.remote_delete(&to).ok();
+        }
+        return Err(err);
+    }
+    repo.remote_delete(from)?;
+
+    Ok(RemoteRename {
+        remote: to,
+        branches: branches
+            .into_iter()
+            .filter(|(old, new, _)| old != new)
+            .map(|(old, new, _)| (old, new))
+            .collect(),
+    })
 }
 
 pub fn deserialize_urn<'de, D>(deserializer: D) -> Result<Urn, D::Error>
@@ -579,3 +1048,225 @@ where
     let s: String = serde::Deserialize::deserialize(deserializer)?;
     s.parse().map_err(serde::de::Error::custom)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    use librad::crypto::keystore::crypto::{Pwhash, KDF_PARAMS_TEST};
+    use librad::crypto::keystore::pinentry::SecUtf8;
+
+    use crate::tempdir::TempDir;
+    use crate::test;
+
+    /// Create a repository with a single commit, and return the commit.
+    fn repository(path: &std::path::Path) -> (git::Repository, git::Oid) {
+        let repo = git::Repository::init(path).unwrap();
+        let sig = git::Signature::now("cloudhead", "cloudhead@radicle.xyz").unwrap();
+        let tree = repo.treebuilder(None).unwrap().write().unwrap();
+        let tree = repo.find_tree(tree).unwrap();
+        let oid = repo
+            .commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])
+            .unwrap();
+        drop(tree);
+
+        (repo, oid)
+    }
+
+    /// Setup a peer remote, as `peers/<name>/rad`, with a tracking branch for `master`.
+    fn setup_peer(repo: &git::Repository, urn: &Urn, peer: &PeerId, name: &str, oid: git::Oid) {
+        let remote = format!("{}/{}/rad", PEER_PREFIX, name);
+        self::remote(urn, peer, &remote)
+            .unwrap()
+            .save(repo)
+            .unwrap();
+
+        repo.reference(
+            &format!("refs/remotes/{}/heads/master", remote),
+            oid,
+            false,
+            "test",
+        )
+        .unwrap();
+        let commit = repo.find_commit(oid).unwrap();
+        let mut branch = repo
+            .branch(&format!("{}/{}/master", PEER_PREFIX, name), &commit, false)
+            .unwrap();
+        branch
+            .set_upstream(Some(&format!("{}/heads/master", remote)))
+            .unwrap();
+    }
+
+    fn upstream(repo: &git::Repository, branch: &str) -> Option<String> {
+        repo.branch_upstream_name(&format!("refs/heads/{}", branch))
+            .ok()
+            .and_then(|b| b.as_str().map(ToOwned::to_owned))
+    }
+
+    #[test]
+    fn test_fork() {
+        let (storage, _, _, project) = test::setup::profile();
+        let parent = Metadata::try_from(project).unwrap();
+        let repo = git::Repository::open_bare(storage.as_ref().path()).unwrap();
+        let sig = git::Signature::now("cloudhead", "cloudhead@radicle.xyz").unwrap();
+        let tree = repo.treebuilder(None).unwrap().write().unwrap();
+        let tree = repo.find_tree(tree).unwrap();
+        let head = repo
+            .commit(None, &sig, &sig, "Initial commit", &tree, &[])
+            .unwrap();
+        repo.reference(
+            &format!(
+                "refs/namespaces/{}/refs/heads/master",
+                parent.urn.encode_id()
+            ),
+            head,
+            false,
+            "test",
+        )
+        .unwrap();
+
+        let payload = super::payload(
+            "nakamoto-fork".to_owned(),
+            "Fork of nakamoto".to_owned(),
+            "master".to_owned(),
+        );
+        let fork = fork(&storage, &parent, payload).unwrap();
+
+        assert_eq!(
+            super::parent(&storage, &fork.urn()).unwrap(),
+            Some(parent.urn.clone())
+        );
+        assert_eq!(super::parent(&storage, &parent.urn).unwrap(), None);
+        assert_eq!(
+            get_local_head(&storage, &fork.urn(), "master").unwrap(),
+            Some(head)
+        );
+    }
+
+    #[test]
+    fn test_remote_name() {
+        let (_, profile, _, project) = test::setup::profile();
+        let pass = Pwhash::new(SecUtf8::from(test::USER_PASS), *KDF_PARAMS_TEST);
+        let signer = test::signer(&profile, pass).unwrap();
+        let metadata = Metadata::try_from(project).unwrap();
+        let alice =
+            PeerId::from_str("hyb5to4rshftx4apgmu9s6wnsp4ddmp1mz6ijh4qqey7fb8wrpawxa").unwrap();
+        let bob =
+            PeerId::from_str("hyd1xifd959krgipmwcgtudc6z8us7ch6tyjwut9bghkdiaftt1kda").unwrap();
+        let tmp = TempDir::new("rad-remote-name").unwrap();
+        let (repo, oid) = repository(tmp.path());
+        let setup = SetupRemote {
+            project: &metadata,
+            repo: &repo,
+            signer,
+            fetch: false,
+            upstream: true,
+            upstream_prefix: None,
+        };
+
+        assert_eq!(setup.remote_name(&alice, "alice").unwrap(), "alice");
+
+        setup_peer(&repo, &metadata.urn, &alice, "alice", oid);
+        // The remote of the same peer can be reused.
+        assert_eq!(setup.remote_name(&alice, "alice").unwrap(), "alice");
+        assert!(setup.is_available(&alice, "alice", &git::remotes(&repo).unwrap()));
+        // Another peer gets a suffix.
+        assert_eq!(setup.remote_name(&bob, "alice").unwrap(), "alice-2");
+        assert!(!setup.is_available(&bob, "alice", &git::remotes(&repo).unwrap()));
+
+        // A local branch with the same name as the tracking branch also conflicts.
+        let commit = repo.find_commit(oid).unwrap();
+        repo.branch("peers/alice-2/master", &commit, false).unwrap();
+        assert_eq!(setup.remote_name(&bob, "alice").unwrap(), "alice-3");
+
+        // Unless the upstream branch isn't setup.
+        let setup = SetupRemote {
+            upstream: false,
+            ..setup
+        };
+        assert_eq!(setup.remote_name(&bob, "alice").unwrap(), "alice-2");
+    }
+
+    #[test]
+    fn test_rename_remote() {
+        let (_, _, _, project) = test::setup::profile();
+        let urn = project.urn();
+        let alice =
+            PeerId::from_str("hyb5to4rshftx4apgmu9s6wnsp4ddmp1mz6ijh4qqey7fb8wrpawxa").unwrap();
+        let bob =
+            PeerId::from_str("hyd1xifd959krgipmwcgtudc6z8us7ch6tyjwut9bghkdiaftt1kda").unwrap();
+        let tmp = TempDir::new("rad-rename-remote").unwrap();
+        let (repo, oid) = repository(tmp.path());
+
+        setup_peer(&repo, &urn, &alice, "alice", oid);
+        setup_peer(&repo, &urn, &bob, "bob", oid);
+
+        // Renaming to an existing remote fails before any change is made.
+        assert!(rename_remote(&repo, &urn, "peers/alice/rad", "bob").is_err());
+
+        // A stale remote-tracking ref makes the rename fail after the new remote is
+        // created, which is rolled back.
+        repo.reference(
+            "refs/remotes/peers/carol/rad/heads/master",
+            oid,
+            false,
+            "test",
+        )
+        .unwrap();
+        assert!(rename_remote(&repo, &urn, "peers/alice/rad", "carol").is_err());
+        assert!(repo.find_remote("peers/alice/rad").is_ok());
+        assert!(repo.find_remote("peers/carol/rad").is_err());
+        assert!(repo
+            .find_branch("peers/carol/master", git::BranchType::Local)
+            .is_err());
+        assert_eq!(
+            upstream(&repo, "peers/alice/master").as_deref(),
+            Some("refs/remotes/peers/alice/rad/heads/master")
+        );
+
+        // A remote for the same peer is reused, and isn't removed on rollback.
+        self::remote(&urn, &alice, "peers/erin/rad")
+            .unwrap()
+            .save(&repo)
+            .unwrap();
+        repo.reference(
+            "refs/remotes/peers/erin/rad/heads/master",
+            oid,
+            false,
+            "test",
+        )
+        .unwrap();
+        assert!(rename_remote(&repo, &urn, "peers/alice/rad", "erin").is_err());
+        assert!(repo.find_remote("peers/alice/rad").is_ok());
+        assert!(repo.find_remote("peers/erin/rad").is_ok());
+        assert!(repo
+            .find_reference("refs/remotes/peers/erin/rad/heads/master")
+            .is_ok());
+        repo.remote_delete("peers/erin/rad").unwrap();
+
+        let renamed = rename_remote(&repo, &urn, "peers/alice/rad", "dave").unwrap();
+        assert_eq!(
+            renamed,
+            RemoteRename {
+                remote: String::from("peers/dave/rad"),
+                branches: vec![(
+                    String::from("peers/alice/master"),
+                    String::from("peers/dave/master")
+                )],
+            }
+        );
+        assert!(repo.find_remote("peers/alice/rad").is_err());
+        assert_eq!(
+            upstream(&repo, "peers/dave/master").as_deref(),
+            Some("refs/remotes/peers/dave/rad/heads/master")
+        );
+        assert_eq!(
+            git::remotes(&repo).unwrap(),
+            vec![
+                (String::from("peers/bob/rad"), bob),
+                (String::from("peers/dave/rad"), alice)
+            ]
+        );
+    }
+}
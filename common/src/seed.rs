@@ -1,4 +1,7 @@
 //! Seed-related functionality.
+pub mod api;
+pub mod health;
+
 use std::convert::TryFrom;
 use std::net;
 use std::path::Path;
@@ -10,8 +13,8 @@ use librad::git::Urn;
 use url::{Host, Url};
 
 use crate::args::Error;
+use crate::git;
 use crate::sync::Seed;
-use crate::{git, project};
 
 pub const CONFIG_SEED_KEY: &str = "rad.seed";
 pub const CONFIG_PEER_KEY: &str = "rad.peer";
@@ -20,16 +23,6 @@ pub const DEFAULT_SEED_API_PORT: u16 = 8777;
 pub const DEFAULT_SEED_P2P_PORT: u16 = 8776;
 pub const DEFAULT_SEED_GIT_PORT: u16 = 443;
 
-#[derive(serde::Deserialize)]
-pub struct CommitHeader {
-    pub summary: String,
-}
-
-#[derive(serde::Deserialize)]
-pub struct Commit {
-    pub header: CommitHeader,
-}
-
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum Protocol {
     Link { peer: Option<PeerId> },
@@ -226,6 +219,47 @@ pub fn parse_value(parser: &mut lexopt::Parser) -> anyhow::Result<Seed<String>>
     Ok(seed)
 }
 
+/// Set the configured seed within the local repository.
+pub fn set_seed(seed: &Url) -> Result<(), anyhow::Error> {
+    let seed = seed.to_string();
+    let path = Path::new(".");
+    let args = ["config", "--local", CONFIG_SEED_KEY, seed.as_str()];
+
+    git::git(path, args)
+        .map(|_| ())
+        .context("failed to save seed configuration")
+}
+
+/// Unset the configured seed within the local repository.
+pub fn unset_seed() -> Result<(), anyhow::Error> {
+    let path = Path::new(".");
+    let args = ["config", "--local", "--unset", CONFIG_SEED_KEY];
+
+    git::git(path, args)
+        .map(|_| ())
+        .context("failed to unset seed configuration")
+}
+
+/// Get the seed configured within the given repository, if any.
+///
+/// Only the repository's local configuration is considered.
+pub fn get_seed(repo: &Path) -> Result<Option<Url>, anyhow::Error> {
+    let args = ["config", "--local", CONFIG_SEED_KEY];
+
+    // Fails if the key isn't set, or if the path isn't a repository.
+    let output = match git::git(repo, args) {
+        Ok(output) => output,
+        Err(_) => return Ok(None),
+    };
+    let url = Url::parse(output.trim()).context(format!(
+        "`{}` is not set to a valid URL in {}",
+        CONFIG_SEED_KEY,
+        repo.display()
+    ))?;
+
+    Ok(Some(url))
+}
+
 /// Set the configured "peer" seed within the local repository.
 pub fn set_peer_seed(seed: &Seed<String>, peer_id: &PeerId) -> Result<(), anyhow::Error> {
     let seed = seed.to_string();
@@ -250,52 +284,6 @@ pub fn get_peer_seed(peer_id: &PeerId) -> Result<Url, anyhow::Error> {
     Ok(url)
 }
 
-/// Query a seed node for its [`PeerId`].
-pub fn get_seed_id(mut seed: Url) -> Result<PeerId, anyhow::Error> {
-    seed.set_port(Some(DEFAULT_SEED_API_PORT)).unwrap();
-    seed = seed.join("/v1/peer")?;
-
-    let agent = ureq::Agent::new();
-    let obj: serde_json::Value = agent.get(seed.as_str()).call()?.into_json()?;
-
-    let id = obj
-        .get("id")
-        .ok_or(anyhow!("missing 'id' in seed API response"))?
-        .as_str()
-        .ok_or(anyhow!("'id' is not a string"))?;
-    let id = PeerId::from_default_encoding(id)?;
-
-    Ok(id)
-}
-
-/// Query a seed node for a project commit.
-pub fn get_commit(
-    mut seed: Url,
-    project: &Urn,
-    commit: &git::Oid,
-) -> Result<Commit, anyhow::Error> {
-    seed.set_port(Some(DEFAULT_SEED_API_PORT)).unwrap();
-    seed = seed.join(&format!("/v1/projects/{}/commits/{}", project, commit))?;
-
-    let agent = ureq::Agent::new();
-    let val: serde_json::Value = agent.get(seed.as_str()).call()?.into_json()?;
-    let commit = serde_json::from_value(val)?;
-
-    Ok(commit)
-}
-
-/// Query a seed node for a project's remotes.
-pub fn get_remotes(mut seed: Url, project: &Urn) -> Result<Vec<project::PeerInfo>, anyhow::Error> {
-    seed.set_port(Some(DEFAULT_SEED_API_PORT)).unwrap();
-    seed = seed.join(&format!("/v1/projects/{}/remotes", project))?;
-
-    let agent = ureq::Agent::new();
-    let val: serde_json::Value = agent.get(seed.as_str()).call()?.into_json()?;
-    let response = serde_json::from_value(val)?;
-
-    Ok(response)
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
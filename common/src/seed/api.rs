@@ -0,0 +1,422 @@
+//! Seed HTTP API client.
+//!
+//! Covers the `/v1` endpoints exposed by seed nodes.
+use std::collections::BTreeMap;
+use std::io;
+use std::time::Duration;
+
+use librad::crypto::peer::PeerId;
+use librad::git::Urn;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use url::Url;
+
+use super::{Address, Protocol};
+use crate::config::SeedConfig;
+use crate::git;
+use crate::project::{deserialize_urn, PeerInfo};
+use crate::sync::Seed;
+
+/// Default timeout of API requests.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(9);
+
+/// Seed API error.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("invalid seed API URL: {0}")]
+    Url(#[from] url::ParseError),
+    #[error("seed API request to {url} failed ({status}): {message}")]
+    Status {
+        url: String,
+        status: u16,
+        message: String,
+    },
+    #[error("seed API request failed: {0}")]
+    Transport(Box<ureq::Transport>),
+    #[error("invalid seed API response: {0}")]
+    Json(#[from] io::Error),
+}
+
+impl Error {
+    /// Whether the requested resource doesn't exist on the seed.
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Self::Status { status, .. } if *status == 404)
+    }
+}
+
+impl From<ureq::Error> for Error {
+    fn from(err: ureq::Error) -> Self {
+        match err {
+            ureq::Error::Status(status, response) => Self::Status {
+                url: response.get_url().to_owned(),
+                status,
+                message: response.into_string().unwrap_or_default(),
+            },
+            ureq::Error::Transport(err) => Self::Transport(Box::new(err)),
+        }
+    }
+}
+
+/// Seed peer information.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Peer {
+    pub id: PeerId,
+}
+
+/// Project hosted on a seed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    #[serde(deserialize_with = "deserialize_urn")]
+    pub urn: Urn,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub default_branch: String,
+    #[serde(default, deserialize_with = "deserialize_oid_opt")]
+    pub head: Option<git::Oid>,
+}
+
+/// Commit author or committer.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Person {
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitHeader {
+    #[serde(deserialize_with = "deserialize_oid")]
+    pub sha1: git::Oid,
+    pub summary: String,
+    #[serde(default)]
+    pub description: String,
+    pub author: Option<Person>,
+    pub committer_time: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Commit {
+    pub header: CommitHeader,
+}
+
+/// A project remote, ie. a peer's branches.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Remote {
+    /// Branch heads, keyed by branch name.
+    #[serde(deserialize_with = "deserialize_heads")]
+    pub heads: BTreeMap<String, git::Oid>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    Blob,
+    Tree,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TreeEntry {
+    pub name: String,
+    pub path: String,
+    pub kind: EntryKind,
+}
+
+/// Directory listing of a project at a given revision.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Tree {
+    pub path: String,
+    pub entries: Vec<TreeEntry>,
+}
+
+/// File of a project at a given revision.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Blob {
+    pub path: String,
+    #[serde(default)]
+    pub binary: bool,
+    /// File content, if the file isn't binary.
+    pub content: Option<String>,
+}
+
+/// Collaborative object summary, eg. an issue or patch.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Cob {
+    pub id: String,
+    pub title: String,
+    /// Remaining fields, which depend on the object type.
+    #[serde(flatten)]
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Client for a seed's HTTP API.
+#[derive(Debug, Clone)]
+pub struct SeedApiClient {
+    base: Url,
+    agent: ureq::Agent,
+}
+
+impl SeedApiClient {
+    /// Create a client for the given API URL, eg. `https://seed.example.com:8777`.
+    pub fn new(base: Url) -> Self {
+        Self::with_timeout(base, DEFAULT_TIMEOUT)
+    }
+
+    pub fn with_timeout(mut base: Url, timeout: Duration) -> Self {
+        if !base.path().ends_with('/') {
+            let path = format!("{}/", base.path());
+            base.set_path(&path);
+        }
+        let agent = ureq::AgentBuilder::new().timeout(timeout).build();
+
+        Self { base, agent }
+    }
+
+    /// Create a client for a configured seed.
+    pub fn from_config(seed: &SeedConfig) -> Self {
+        Self::new(seed.api.clone())
+    }
+
+    /// Create a client for the seed at the given address. If the seed's host is
+    /// configured, its API URL is used, otherwise the default API port is assumed.
+    pub fn from_address<'a>(
+        addr: &Address,
+        mut seeds: impl Iterator<Item = &'a SeedConfig>,
+    ) -> Self {
+        let host = addr.host.to_string();
+
+        if let Some(seed) = seeds.find(|s| {
+            [&s.p2p, &s.git, &s.api]
+                .iter()
+                .any(|url| url.host_str() == Some(host.as_str()))
+        }) {
+            return Self::from_config(seed);
+        }
+        let local = matches!(
+            addr.protocol,
+            Protocol::Git { local: true } | Protocol::Api { local: true }
+        );
+        let mut api = Address::new(addr.host.clone(), Protocol::Api { local });
+        if let Protocol::Api { .. } = addr.protocol {
+            api.port = addr.port;
+        }
+        let mut url = api.url();
+        url.set_port(Some(api.port())).ok();
+
+        Self::new(url)
+    }
+
+    /// Create a client for a seed to sync with. If the seed's host is configured, its
+    /// API URL is used, otherwise the default API port is assumed.
+    pub fn from_seed<'a>(
+        seed: &Seed<String>,
+        seeds: impl Iterator<Item = &'a SeedConfig>,
+    ) -> Result<Self, Error> {
+        let url = Url::parse(&format!("rad://{}", seed.addrs))?;
+        let host = url.host().ok_or(url::ParseError::EmptyHost)?.to_owned();
+        let addr = Address::new(
+            host,
+            Protocol::Link {
+                peer: Some(seed.peer),
+            },
+        );
+
+        Ok(Self::from_address(&addr, seeds))
+    }
+
+    /// The API base URL.
+    pub fn url(&self) -> &Url {
+        &self.base
+    }
+
+    /// Get the seed's peer information.
+    pub fn peer(&self) -> Result<Peer, Error> {
+        self.get("v1/peer")
+    }
+
+    /// Get the projects hosted on the seed.
+    pub fn projects(&self) -> Result<Vec<Project>, Error> {
+        self.get("v1/projects")
+    }
+
+    pub fn project(&self, urn: &Urn) -> Result<Project, Error> {
+        self.get(&format!("v1/projects/{}", urn))
+    }
+
+    pub fn commit(&self, urn: &Urn, commit: &git::Oid) -> Result<Commit, Error> {
+        self.get(&format!("v1/projects/{}/commits/{}", urn, commit))
+    }
+
+    /// Get the peers the seed has a copy of the project from.
+    pub fn remotes(&self, urn: &Urn) -> Result<Vec<PeerInfo>, Error> {
+        self.get(&format!("v1/projects/{}/remotes", urn))
+    }
+
+    pub fn remote(&self, urn: &Urn, peer: &PeerId) -> Result<Remote, Error> {
+        self.get(&format!(
+            "v1/projects/{}/remotes/{}",
+            urn,
+            peer.default_encoding()
+        ))
+    }
+
+    pub fn tree(&self, urn: &Urn, commit: &git::Oid, path: &str) -> Result<Tree, Error> {
+        self.get(&format!(
+            "v1/projects/{}/tree/{}/{}",
+            urn,
+            commit,
+            path.trim_start_matches('/')
+        ))
+    }
+
+    pub fn blob(&self, urn: &Urn, commit: &git::Oid, path: &str) -> Result<Blob, Error> {
+        self.get(&format!(
+            "v1/projects/{}/blob/{}/{}",
+            urn,
+            commit,
+            path.trim_start_matches('/')
+        ))
+    }
+
+    pub fn issues(&self, urn: &Urn) -> Result<Vec<Cob>, Error> {
+        self.get(&format!("v1/projects/{}/issues", urn))
+    }
+
+    pub fn patches(&self, urn: &Urn) -> Result<Vec<Cob>, Error> {
+        self.get(&format!("v1/projects/{}/patches", urn))
+    }
+
+    fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, Error> {
+        let url = self.base.join(path)?;
+        let response = self.agent.get(url.as_str()).call()?;
+
+        Ok(response.into_json()?)
+    }
+}
+
+fn deserialize_oid<'de, D>(deserializer: D) -> Result<git::Oid, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s: String = Deserialize::deserialize(deserializer)?;
+    s.parse().map_err(serde::de::Error::custom)
+}
+
+fn deserialize_oid_opt<'de, D>(deserializer: D) -> Result<Option<git::Oid>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s: Option<String> = Deserialize::deserialize(deserializer)?;
+    s.map(|s| s.parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+fn deserialize_heads<'de, D>(deserializer: D) -> Result<BTreeMap<String, git::Oid>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let heads: BTreeMap<String, String> = Deserialize::deserialize(deserializer)?;
+    heads
+        .into_iter()
+        .map(|(name, oid)| Ok((name, oid.parse().map_err(serde::de::Error::custom)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::seed::FakeSeed;
+    use std::str::FromStr;
+
+    const PEER: &str = "hyb5to4rshftx4apgmu9s6wnsp4ddmp1mz6ijh4qqey7fb8wrpawxa";
+    const URN: &str = "rad:git:hnrkfbrd7y9674d8ow8uioki16fniwcyoz67y";
+    const OID: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+    #[test]
+    fn test_peer() {
+        let seed = FakeSeed::spawn().unwrap();
+        seed.route("/v1/peer", serde_json::json!({ "id": PEER }));
+
+        let client = SeedApiClient::new(seed.url());
+        let peer = client.peer().unwrap();
+
+        assert_eq!(peer.id, PeerId::from_str(PEER).unwrap());
+    }
+
+    #[test]
+    fn test_project_endpoints() {
+        let urn = Urn::from_str(URN).unwrap();
+        let oid = git::Oid::from_str(OID).unwrap();
+        let seed = FakeSeed::spawn().unwrap();
+
+        seed.route(
+            &format!("/v1/projects/{}/remotes", URN),
+            serde_json::json!([{ "id": PEER, "delegate": true }]),
+        );
+        seed.route(
+            &format!("/v1/projects/{}/commits/{}", URN, OID),
+            serde_json::json!({ "header": { "sha1": OID, "summary": "Initial commit" } }),
+        );
+        seed.route(
+            &format!("/v1/projects/{}/remotes/{}", URN, PEER),
+            serde_json::json!({ "heads": { "master": OID } }),
+        );
+
+        let client = SeedApiClient::new(seed.url());
+
+        let remotes = client.remotes(&urn).unwrap();
+        assert_eq!(remotes.len(), 1);
+        assert!(remotes[0].delegate);
+        assert!(remotes[0].person.is_none());
+
+        let commit = client.commit(&urn, &oid).unwrap();
+        assert_eq!(commit.header.sha1, oid);
+        assert_eq!(commit.header.summary, "Initial commit");
+
+        let remote = client.remote(&urn, &remotes[0].id).unwrap();
+        assert_eq!(remote.heads.get("master"), Some(&oid));
+    }
+
+    #[test]
+    fn test_not_found() {
+        let urn = Urn::from_str(URN).unwrap();
+        let seed = FakeSeed::spawn().unwrap();
+        let client = SeedApiClient::new(seed.url());
+        let err = client.project(&urn).unwrap_err();
+
+        assert!(err.is_not_found(), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_from_address() {
+        let addr = Address::from_str("https://seed.example.com").unwrap();
+        let client = SeedApiClient::from_address(&addr, std::iter::empty());
+        assert_eq!(client.url().as_str(), "https://seed.example.com:8777/");
+
+        let mut config = SeedConfig::new(addr.host.clone(), None);
+        config.api.set_port(Some(9000)).unwrap();
+
+        let client = SeedApiClient::from_address(&addr, [config].iter());
+        assert_eq!(client.url().as_str(), "https://seed.example.com:9000/");
+    }
+
+    #[test]
+    fn test_from_seed() {
+        let seed = Seed {
+            peer: PeerId::from_str(PEER).unwrap(),
+            addrs: String::from("seed.example.com:8776"),
+            label: None,
+        };
+        let client = SeedApiClient::from_seed(&seed, std::iter::empty()).unwrap();
+        assert_eq!(client.url().as_str(), "https://seed.example.com:8777/");
+
+        let fake = FakeSeed::spawn().unwrap();
+        let seed = Seed {
+            addrs: String::from("127.0.0.1:8776"),
+            ..seed
+        };
+        let client = SeedApiClient::from_seed(&seed, [fake.config(None)].iter()).unwrap();
+        assert_eq!(client.url(), &fake.url());
+    }
+}
@@ -0,0 +1,230 @@
+//! Seed health checks.
+//!
+//! Results are cached in the profile, and used to rank seeds when syncing.
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fs;
+use std::io;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use librad::profile::Profile;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::api::SeedApiClient;
+use super::Address;
+use crate::config::SeedConfig;
+
+/// Health cache file name, in the profile directory.
+pub const FILE_NAME: &str = "seeds.health.toml";
+/// Timeout used for each probe.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+/// Cached results older than this are ignored, in seconds.
+pub const MAX_AGE: u64 = 60 * 60 * 24;
+
+/// Result of probing a single seed endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Probe {
+    /// Whether the endpoint is reachable.
+    pub ok: bool,
+    /// Latency of the probe, in milliseconds.
+    pub latency: Option<u64>,
+    /// Error encountered, if any.
+    pub error: Option<String>,
+}
+
+impl Probe {
+    fn success(started: Instant) -> Self {
+        Self {
+            ok: true,
+            latency: Some(started.elapsed().as_millis() as u64),
+            error: None,
+        }
+    }
+
+    fn failure(err: impl ToString) -> Self {
+        Self {
+            ok: false,
+            latency: None,
+            error: Some(err.to_string()),
+        }
+    }
+}
+
+/// Health of a seed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Health {
+    // Nb. Plain values must come before tables for TOML serialization to work.
+    /// Whether the peer id reported by the API matches the configured one.
+    /// `None` if it could not be checked.
+    pub verified: Option<bool>,
+    /// When the check was done, in seconds since the epoch.
+    pub checked: u64,
+    /// The P2P endpoint. Since the protocol runs over QUIC, this only checks that the
+    /// address resolves.
+    pub p2p: Probe,
+    /// The Git (HTTPS) endpoint.
+    pub git: Probe,
+    /// The HTTP API endpoint.
+    pub api: Probe,
+}
+
+impl Health {
+    /// Whether the seed is usable for syncing.
+    pub fn is_healthy(&self) -> bool {
+        self.p2p.ok && self.verified != Some(false)
+    }
+
+    /// Best known latency to the seed, in milliseconds.
+    pub fn latency(&self) -> Option<u64> {
+        self.api.latency.or(self.git.latency)
+    }
+
+    /// Whether the result is too old to be used.
+    pub fn is_stale(&self) -> bool {
+        now().saturating_sub(self.checked) > MAX_AGE
+    }
+}
+
+/// Probe a seed's endpoints.
+pub fn check(seed: &SeedConfig, timeout: Duration) -> Health {
+    let p2p = probe_resolve(&seed.p2p);
+    let git = probe_connect(&seed.git, timeout);
+    let mut api = probe_connect(&seed.api, timeout);
+    let mut verified = None;
+
+    if api.ok {
+        let expected = Address::try_from(seed.p2p.clone())
+            .ok()
+            .and_then(|a| a.peer());
+        let started = Instant::now();
+
+        match SeedApiClient::with_timeout(seed.api.clone(), timeout).peer() {
+            Ok(peer) => {
+                api = Probe::success(started);
+                verified = expected.map(|expected| expected == peer.id);
+            }
+            Err(err) => {
+                api = Probe::failure(err);
+            }
+        }
+    }
+
+    Health {
+        verified,
+        checked: now(),
+        p2p,
+        git,
+        api,
+    }
+}
+
+/// Check that the URL's host resolves.
+fn probe_resolve(url: &Url) -> Probe {
+    let started = Instant::now();
+
+    match resolve(url) {
+        Ok(_) => Probe::success(started),
+        Err(err) => Probe::failure(err),
+    }
+}
+
+/// Check that a TCP connection can be established to the URL's host.
+fn probe_connect(url: &Url, timeout: Duration) -> Probe {
+    let addrs = match resolve(url) {
+        Ok(addrs) => addrs,
+        Err(err) => return Probe::failure(err),
+    };
+    let started = Instant::now();
+    let mut error = None;
+
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(_) => return Probe::success(started),
+            Err(err) => error = Some(err),
+        }
+    }
+    Probe::failure(error.map_or(String::from("no address found"), |e| e.to_string()))
+}
+
+fn resolve(url: &Url) -> io::Result<Vec<std::net::SocketAddr>> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "missing host"))?;
+    let port = url
+        .port_or_known_default()
+        .or_else(|| Address::try_from(url.clone()).ok().map(|a| a.port()))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "missing port"))?;
+
+    Ok((host, port).to_socket_addrs()?.collect())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Cached seed health results, keyed by the seed's P2P URL.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Cache {
+    #[serde(default)]
+    seed: BTreeMap<String, Health>,
+}
+
+impl Cache {
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        let content = fs::read_to_string(path)?;
+        let cache = toml::from_str(&content)?;
+
+        Ok(cache)
+    }
+
+    /// Load the profile's health cache. Returns an empty cache if there is none.
+    pub fn load(profile: &Profile) -> Result<Self, io::Error> {
+        match Self::read(Self::path(profile)) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            result => result,
+        }
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), anyhow::Error> {
+        let contents = toml::to_string_pretty(self)?;
+        fs::write(path, contents)?;
+
+        Ok(())
+    }
+
+    /// Write the cache to the profile.
+    pub fn save(&self, profile: &Profile) -> Result<(), anyhow::Error> {
+        self.write(Self::path(profile))
+    }
+
+    pub fn path(profile: &Profile) -> PathBuf {
+        profile.paths().seeds_file().with_file_name(FILE_NAME)
+    }
+
+    /// Get the cached health of a seed, unless it's stale.
+    pub fn get(&self, seed: &SeedConfig) -> Option<&Health> {
+        self.seed
+            .get(seed.p2p.as_str())
+            .filter(|health| !health.is_stale())
+    }
+
+    pub fn insert(&mut self, seed: &SeedConfig, health: Health) {
+        self.seed.insert(seed.p2p.to_string(), health);
+    }
+
+    /// Order seeds by health: healthy seeds first, fastest first, then seeds that
+    /// haven't been checked, and finally unhealthy seeds.
+    pub fn rank(&self, seeds: &mut [SeedConfig]) {
+        seeds.sort_by_key(|seed| match self.get(seed) {
+            Some(health) if health.is_healthy() => (0, health.latency().unwrap_or(u64::MAX)),
+            Some(_) => (2, u64::MAX),
+            None => (1, u64::MAX),
+        });
+    }
+}
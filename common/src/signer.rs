@@ -1,14 +1,28 @@
+use std::convert::TryInto;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::{env, process};
+
+use anyhow::{anyhow, Context as _};
 use zeroize::Zeroizing;
 
+use librad::crypto::keystore::pinentry::SecUtf8;
 use librad::crypto::keystore::sign::ed25519;
 use librad::crypto::BoxedSignError;
 use librad::crypto::BoxedSigner;
 use librad::profile::Profile;
-use librad::SecretKey;
+use librad::{PeerId, SecretKey};
 
 use lnk_clib::keys;
 use lnk_clib::keys::ssh::SshAuthSock;
 
+use crate::config::{Config, SignerConfig};
+use crate::tempdir::TempDir;
+
+/// Env var holding a base64-encoded, encrypted key file, for [`SignerConfig::Env`].
+pub const RAD_SECRET_KEY: &str = "RAD_SECRET_KEY";
+
 /// A trait for types that can be converted to signers.
 pub trait ToSigner {
     /// Convert to a signer.
@@ -52,6 +66,10 @@ impl ZeroizingSecretKey {
             key: Zeroizing::new(key),
         }
     }
+
+    pub fn secret_key(&self) -> &SecretKey {
+        &self.key
+    }
 }
 
 #[async_trait::async_trait]
@@ -79,3 +97,223 @@ impl librad::Signer for ZeroizingSecretKey {
             .map_err(BoxedSignError::from_std_error)
     }
 }
+
+/// Get the signer configured in the profile's `config.toml`, if any.
+///
+/// Nb. Only the profile configuration is considered, since the working copy
+/// configuration could otherwise be used to run arbitrary signing commands.
+pub fn configured(profile: &Profile) -> Result<Option<BoxedSigner>, anyhow::Error> {
+    let config = match Config::profile(profile) {
+        Ok(config) => config,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err).context("couldn't read profile configuration"),
+    };
+    config
+        .signer
+        .map(|cfg| from_config(profile, &cfg))
+        .transpose()
+}
+
+/// Load a signer from its configuration.
+pub fn from_config(profile: &Profile, config: &SignerConfig) -> Result<BoxedSigner, anyhow::Error> {
+    match config {
+        SignerConfig::KeyFile {
+            path,
+            passphrase_fd,
+        } => {
+            let passphrase = passphrase(*passphrase_fd)?;
+            let key = match path {
+                Some(path) => crate::keys::load_secret_key_from(path, passphrase),
+                None => crate::keys::load_secret_key(profile, passphrase),
+            }
+            .context("couldn't unseal key file")?;
+
+            Ok(BoxedSigner::new(key))
+        }
+        SignerConfig::Env { var, passphrase_fd } => {
+            let encoded = Zeroizing::new(
+                env::var(var).map_err(|_| anyhow!("signer key variable '{}' is not set", var))?,
+            );
+            let contents =
+                Zeroizing::new(base64::decode(encoded.trim()).with_context(|| {
+                    format!("signer key variable '{}' is not valid base64", var)
+                })?);
+            let passphrase = passphrase(*passphrase_fd)?;
+
+            // The key store only reads keys from files, so we write the key to a private
+            // temporary directory for the time it takes to unseal it.
+            let tmp = TempDir::new("rad-signer")?;
+            let path = tmp.path().join("key");
+            crate::keys::write_secret(&path, &contents)?;
+
+            let key = crate::keys::load_secret_key_from(&path, passphrase);
+            drop(tmp);
+
+            Ok(BoxedSigner::new(key.with_context(|| {
+                format!("couldn't unseal key from '{}'", var)
+            })?))
+        }
+        SignerConfig::Command { command, args } => {
+            let signer = CommandSigner::new(command, args.clone())?;
+
+            Ok(BoxedSigner::new(signer))
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    /// Passphrase read from a file descriptor. The file descriptor can only be read once,
+    /// so the passphrase is kept for the lifetime of the process.
+    static ref FD_PASSPHRASE: std::sync::Mutex<Option<SecUtf8>> = std::sync::Mutex::new(None);
+}
+
+/// Get a key passphrase, from the `RAD_PASSPHRASE` environment variable or from the
+/// given file descriptor.
+///
+/// Nb. A passphrase read from a file descriptor isn't passed down to child processes,
+/// eg. the git remote helper, which get their signer from ssh-agent or `RAD_PASSPHRASE`.
+fn passphrase(fd: Option<i32>) -> Result<SecUtf8, anyhow::Error> {
+    use std::mem::ManuallyDrop;
+    use std::os::unix::io::FromRawFd;
+
+    if let Ok(passphrase) = env::var(crate::keys::RAD_PASSPHRASE) {
+        return Ok(SecUtf8::from(passphrase));
+    }
+    let fd = fd.ok_or_else(|| {
+        anyhow!(
+            "no passphrase found: set `passphrase_fd` in the signer configuration, \
+             or the `{}` environment variable",
+            crate::keys::RAD_PASSPHRASE
+        )
+    })?;
+
+    let mut cached = FD_PASSPHRASE
+        .lock()
+        .map_err(|_| anyhow!("passphrase lock poisoned"))?;
+    if let Some(passphrase) = &*cached {
+        return Ok(passphrase.clone());
+    }
+
+    // SAFETY: The file descriptor is handed to us by the parent process for this
+    // purpose. It isn't owned by us, so it mustn't be closed when the file is dropped.
+    let mut file = ManuallyDrop::new(unsafe { fs::File::from_raw_fd(fd) });
+    let mut input = Zeroizing::new(String::new());
+    file.read_to_string(&mut input)
+        .with_context(|| format!("couldn't read passphrase from file descriptor {}", fd))?;
+
+    let passphrase = SecUtf8::from(input.trim_end_matches(&['\r', '\n'][..]).to_owned());
+    *cached = Some(passphrase.clone());
+
+    Ok(passphrase)
+}
+
+/// Error returned by [`CommandSigner`].
+#[derive(thiserror::Error, Debug)]
+pub enum CommandError {
+    #[error("failed to run signing command: {0}")]
+    Io(#[from] io::Error),
+    #[error("signing command failed ({status}): {stderr}")]
+    Failed {
+        status: process::ExitStatus,
+        stderr: String,
+    },
+    #[error("signing command returned an invalid {0}")]
+    Invalid(&'static str),
+}
+
+/// Signer that delegates signing to an external command, eg. a hardware security
+/// module or a secrets manager client.
+///
+/// The command is run with its configured arguments, followed by an operation:
+///
+/// * `public-key`: print the signer's peer id on standard output.
+/// * `sign`: sign the data read from standard input, and print the base64-encoded
+///   Ed25519 signature on standard output.
+///
+/// A non-zero exit status signals failure, with the reason on standard error.
+#[derive(Debug, Clone)]
+pub struct CommandSigner {
+    program: String,
+    args: Vec<String>,
+    public_key: ed25519::PublicKey,
+}
+
+impl CommandSigner {
+    /// Create a new command signer. Runs the command to get the signer's public key.
+    pub fn new(program: &str, args: Vec<String>) -> Result<Self, CommandError> {
+        let output = call(program, &args, "public-key", &[])?;
+        let peer: PeerId = String::from_utf8_lossy(&output)
+            .trim()
+            .parse()
+            .map_err(|_| CommandError::Invalid("peer id"))?;
+        let key: [u8; 32] = peer
+            .as_public_key()
+            .as_ref()
+            .try_into()
+            .map_err(|_| CommandError::Invalid("public key"))?;
+
+        Ok(Self {
+            program: program.to_owned(),
+            args,
+            public_key: ed25519::PublicKey(key),
+        })
+    }
+
+    fn sign_data(&self, data: &[u8]) -> Result<ed25519::Signature, CommandError> {
+        let output = call(&self.program, &self.args, "sign", data)?;
+        let signature = base64::decode(String::from_utf8_lossy(&output).trim())
+            .map_err(|_| CommandError::Invalid("signature"))?;
+        let signature: [u8; 64] = signature
+            .as_slice()
+            .try_into()
+            .map_err(|_| CommandError::Invalid("signature"))?;
+
+        Ok(ed25519::Signature(signature))
+    }
+}
+
+#[async_trait::async_trait]
+impl ed25519::Signer for CommandSigner {
+    type Error = BoxedSignError;
+
+    fn public_key(&self) -> ed25519::PublicKey {
+        self.public_key
+    }
+
+    async fn sign(&self, data: &[u8]) -> Result<ed25519::Signature, Self::Error> {
+        self.sign_data(data).map_err(BoxedSignError::from_std_error)
+    }
+}
+
+impl librad::Signer for CommandSigner {
+    fn sign_blocking(
+        &self,
+        data: &[u8],
+    ) -> Result<librad::keystore::sign::Signature, <Self as ed25519::Signer>::Error> {
+        self.sign_data(data).map_err(BoxedSignError::from_std_error)
+    }
+}
+
+/// Run a signing command operation, passing it the given input.
+fn call(program: &str, args: &[String], op: &str, input: &[u8]) -> Result<Vec<u8>, CommandError> {
+    let mut child = Command::new(program)
+        .args(args)
+        .arg(op)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input)?;
+    }
+    let output = child.wait_with_output()?;
+
+    if !output.status.success() {
+        return Err(CommandError::Failed {
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        });
+    }
+    Ok(output.stdout)
+}
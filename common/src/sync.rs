@@ -1,11 +1,14 @@
+pub mod history;
 mod push;
 
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time;
 
 use anyhow::anyhow;
+use futures::stream::{self, StreamExt};
 
 use librad::crypto::BoxedSigner;
 use librad::git::Urn;
@@ -18,17 +21,25 @@ use librad::net::{
     replication, Network,
 };
 use librad::profile::Profile;
-use librad::Signer;
+use librad::{PeerId, Signer};
 use link_async::Spawner;
 use lnk_clib::seed::store::FileStore;
 
 pub use lnk_clib::seed::{Seed, Seeds};
 pub use lnk_sync::Mode;
 
+use crate::checkouts;
 use crate::config;
 use crate::nonempty::NonEmpty;
+use crate::seed;
+use crate::seed::{health, Address};
 
 /// Sync result of a seed.
+/// None of the seeds to sync with could be resolved, eg. when offline.
+#[derive(thiserror::Error, Debug)]
+#[error("No seeds attempted: all seeds failed to resolve")]
+pub struct UnresolvedSeeds;
+
 #[derive(Debug)]
 pub struct SyncResult {
     pub seed: Seed<Vec<SocketAddr>>,
@@ -36,44 +47,98 @@ pub struct SyncResult {
     pub push: Option<Result<push::Success, push::Error>>,
 }
 
+impl SyncResult {
+    /// Whether the seed could not be reached.
+    pub fn is_unreachable(&self) -> bool {
+        matches!(
+            self.fetch,
+            Some(Err(client::error::Replicate::NoConnection(_)))
+        ) || matches!(
+            self.push,
+            Some(Err(push::Error::NoResponse))
+                | Some(Err(push::Error::Client(
+                    client::error::RequestPull::NoConnection(_)
+                )))
+        )
+    }
+}
+
+/// Maximum number of seeds synced concurrently.
+pub const MAX_CONCURRENT_SEEDS: usize = 4;
+
+/// An event emitted while syncing.
+#[derive(Debug)]
+pub enum Event<'a> {
+    /// A progress message was received from a seed.
+    Progress { seed: &'a PeerId, message: String },
+    /// A seed finished syncing.
+    Synced(&'a SyncResult),
+}
+
 /// Sync the given URN with the provided list of seeds.
-pub async fn sync<S, E>(
+///
+/// Seeds are synced concurrently, up to [`MAX_CONCURRENT_SEEDS`] at a time. Events are
+/// passed to `on_event` as they happen, and results are returned in order of completion.
+pub async fn sync<S, E, F>(
     client: &Client<S, E>,
     urn: Urn,
     seeds: Seeds,
     mode: Mode,
     timeout: time::Duration,
+    on_event: F,
 ) -> Vec<SyncResult>
 where
     S: Signer + Clone,
     E: ConnectPeer + Clone + Send + Sync + 'static,
+    F: Fn(Event),
 {
     let mut syncs = Vec::with_capacity(seeds.len());
     let is_push = mode.is_push();
     let is_fetch = mode.is_fetch();
+    let on_event = &on_event;
     let Seeds(seeds) = seeds;
 
-    for seed in seeds {
-        let fetch = if is_fetch {
-            match tokio::time::timeout(timeout, client.replicate(seed.clone(), urn.clone(), None))
+    let mut results = stream::iter(seeds.into_iter().map(|seed| {
+        let urn = urn.clone();
+
+        async move {
+            let fetch = if is_fetch {
+                match tokio::time::timeout(
+                    timeout,
+                    client.replicate(seed.clone(), urn.clone(), None),
+                )
                 .await
-            {
-                Ok(result) => Some(result),
-                Err(_) => Some(Err(client::error::Replicate::NoConnection(
-                    client::error::NoConnection(seed.peer),
-                ))),
-            }
-        } else {
-            None
-        };
+                {
+                    Ok(result) => Some(result),
+                    Err(_) => Some(Err(client::error::Replicate::NoConnection(
+                        client::error::NoConnection(seed.peer),
+                    ))),
+                }
+            } else {
+                None
+            };
 
-        let push = if is_push {
-            Some(push::push(client, urn.clone(), seed.clone(), timeout).await)
-        } else {
-            None
-        };
+            let push = if is_push {
+                let peer = seed.peer;
+                let progress = |message| {
+                    on_event(Event::Progress {
+                        seed: &peer,
+                        message,
+                    })
+                };
+                Some(push::push(client, urn, seed.clone(), timeout, progress).await)
+            } else {
+                None
+            };
 
-        syncs.push(SyncResult { seed, fetch, push })
+            SyncResult { seed, fetch, push }
+        }
+    }))
+    .buffer_unordered(MAX_CONCURRENT_SEEDS);
+
+    while let Some(result) = results.next().await {
+        on_event(Event::Synced(&result));
+        syncs.push(result);
     }
     syncs
 }
@@ -98,13 +163,52 @@ pub async fn client(
     Ok(client)
 }
 
+/// Get the seeds to sync a project with.
+///
+/// If one of the project's working copies sets a seed via its local `rad.seed` key, that
+/// seed is used, otherwise the seeds configured for the profile. Invalid `rad.seed`
+/// values are skipped, and returned along with the seeds.
+pub fn project_seeds(
+    profile: &Profile,
+    urn: &Urn,
+) -> anyhow::Result<(NonEmpty<Seed<String>>, Vec<anyhow::Error>)> {
+    let mut errors = Vec::new();
+    let mut repos = Vec::new();
+
+    // The current working copy takes precedence over other working copies.
+    if matches!(crate::project::cwd(), Ok((cwd, _)) if cwd == *urn) {
+        repos.push(PathBuf::from("."));
+    }
+    if let Ok(checkouts) = checkouts::Checkouts::load(profile) {
+        repos.extend(checkouts.get(urn).map(|p| p.to_path_buf()));
+    }
+
+    for repo in repos {
+        let seed = seed::get_seed(&repo).and_then(|url| {
+            url.map(|url| Address::try_from(url).and_then(Seed::try_from))
+                .transpose()
+        });
+        match seed {
+            Ok(Some(seed)) => return Ok((NonEmpty::new(seed), errors)),
+            Ok(None) => {}
+            Err(err) => errors.push(err),
+        }
+    }
+    Ok((seeds(profile)?, errors))
+}
+
 /// Get the seeds configured for the profile.
-/// First checks local (working copy) config, then global.
+/// First checks local (working copy) config, then the profile config, then global.
 pub fn seeds(profile: &Profile) -> anyhow::Result<NonEmpty<Seed<String>>> {
     let config = config::Config::load(profile)?;
-    let seeds = config
-        .seeds()
-        .cloned()
+    let mut seeds = config.seeds().cloned().collect::<Vec<_>>();
+
+    // Prefer seeds that are known to be healthy.
+    if let Ok(cache) = health::Cache::load(profile) {
+        cache.rank(&mut seeds);
+    }
+    let seeds = seeds
+        .into_iter()
         .map(|s| s.try_into())
         .collect::<Result<Vec<_>, _>>()?;
     if let Ok(seeds) = seeds.try_into() {
@@ -122,3 +226,69 @@ pub fn seeds(profile: &Profile) -> anyhow::Result<NonEmpty<Seed<String>>> {
         .try_into()
         .map_err(|_| anyhow!("No seeds configured for profile {}", profile.id()))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+    use std::net::{Ipv4Addr, SocketAddrV4};
+
+    use librad::crypto::keystore::crypto::{Pwhash, KDF_PARAMS_TEST};
+    use librad::crypto::keystore::pinentry::SecUtf8;
+    use librad::SecretKey;
+
+    use crate::test;
+
+    #[test]
+    fn test_sync_concurrently() {
+        let (_, profile, _, project) = test::setup::profile();
+        let pass = Pwhash::new(SecUtf8::from(test::USER_PASS), *KDF_PARAMS_TEST);
+        let signer = test::signer(&profile, pass).unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let timeout = time::Duration::from_secs(1);
+
+        // Seeds that can't be reached, which each fail after about the same time.
+        let seeds = (0..MAX_CONCURRENT_SEEDS + 1)
+            .map(|i| Seed {
+                peer: PeerId::from(SecretKey::new()),
+                addrs: vec![SocketAddr::V4(SocketAddrV4::new(
+                    Ipv4Addr::LOCALHOST,
+                    19000 + i as u16,
+                ))],
+                label: None,
+            })
+            .collect::<Vec<_>>();
+        let synced = Cell::new(0);
+        let run = |seeds: Vec<Seed<Vec<SocketAddr>>>| {
+            let started = time::Instant::now();
+            let results = rt.block_on(async {
+                let client = client(signer.clone(), &profile).await.unwrap();
+
+                sync(
+                    &client,
+                    project.urn(),
+                    Seeds(seeds),
+                    Mode::Push,
+                    timeout,
+                    |event| {
+                        if let Event::Synced(_) = event {
+                            synced.set(synced.get() + 1);
+                        }
+                    },
+                )
+                .await
+            });
+            (results, started.elapsed())
+        };
+
+        let (_, single) = run(seeds[..1].to_vec());
+        synced.set(0);
+        let (results, all) = run(seeds.clone());
+
+        assert_eq!(results.len(), seeds.len());
+        assert_eq!(synced.get(), seeds.len());
+        assert!(results.iter().all(|r| matches!(r.push, Some(Err(_)))));
+        // Synced one at a time, this would take as long as syncing each seed in turn.
+        assert!(all < single * seeds.len() as u32);
+    }
+}
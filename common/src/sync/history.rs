@@ -0,0 +1,208 @@
+//! Sync history, persisted in the profile.
+//!
+//! Records when each project was last synced with a seed, and which of its refs were
+//! last pushed.
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use librad::git::Urn;
+use librad::profile::Profile;
+use serde::{Deserialize, Serialize};
+
+use crate::cobs::shared::Timestamp;
+use crate::project::deserialize_urn;
+
+/// Sync history file name, in the profile directory.
+pub const FILE_NAME: &str = "synced.toml";
+
+/// Last sync of a project or identity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    #[serde(deserialize_with = "deserialize_urn")]
+    pub urn: Urn,
+    /// When at least one seed was last reached.
+    pub synced: Timestamp,
+    /// Own refs of the project, eg. `refs/heads/master`, and their targets, as of the
+    /// last push to a seed.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pushed: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    #[serde(default)]
+    entries: Vec<Entry>,
+}
+
+impl History {
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        let content = fs::read_to_string(path)?;
+        let history = toml::from_str(&content)?;
+
+        Ok(history)
+    }
+
+    /// Load the profile's sync history. Returns an empty history if there is none.
+    pub fn load(profile: &Profile) -> Result<Self, io::Error> {
+        match Self::read(Self::path(profile)) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            result => result,
+        }
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), anyhow::Error> {
+        let contents = toml::to_string_pretty(self)?;
+        fs::write(path, contents)?;
+
+        Ok(())
+    }
+
+    /// Write the sync history to the profile.
+    pub fn save(&self, profile: &Profile) -> Result<(), anyhow::Error> {
+        self.write(Self::path(profile))
+    }
+
+    pub fn path(profile: &Profile) -> PathBuf {
+        profile.paths().seeds_file().with_file_name(FILE_NAME)
+    }
+
+    /// Record that the given URN was just synced.
+    pub fn record(&mut self, urn: Urn) {
+        self.entry(urn).synced = Timestamp::now();
+    }
+
+    /// Record that the given refs of a URN were just pushed to a seed.
+    pub fn record_push(&mut self, urn: Urn, refs: BTreeMap<String, String>) {
+        let entry = self.entry(urn);
+
+        entry.synced = Timestamp::now();
+        entry.pushed = refs;
+    }
+
+    /// Get the last sync time of a URN.
+    pub fn get(&self, urn: &Urn) -> Option<Timestamp> {
+        self.entries
+            .iter()
+            .find(|e| &e.urn == urn)
+            .map(|e| e.synced)
+    }
+
+    /// Get the refs of a URN that changed since they were last pushed, given the current
+    /// refs. If the URN was never pushed, all refs are returned.
+    pub fn unpushed<'a>(&self, urn: &Urn, refs: &'a BTreeMap<String, String>) -> Vec<&'a str> {
+        let pushed = self
+            .entries
+            .iter()
+            .find(|e| &e.urn == urn)
+            .map(|e| &e.pushed);
+
+        refs.iter()
+            .filter(|(name, oid)| pushed.and_then(|p| p.get(*name)) != Some(*oid))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    fn entry(&mut self, urn: Urn) -> &mut Entry {
+        match self.entries.iter().position(|e| e.urn == urn) {
+            Some(ix) => &mut self.entries[ix],
+            None => {
+                self.entries.push(Entry {
+                    urn,
+                    synced: Timestamp::now(),
+                    pushed: BTreeMap::new(),
+                });
+                self.entries.last_mut().expect("an entry was just added")
+            }
+        }
+    }
+}
+
+/// Get the own refs of a URN in storage, eg. `refs/heads/master`, and their targets.
+/// Refs of remote peers aren't included.
+pub fn refs(git_dir: &Path, urn: &Urn) -> anyhow::Result<BTreeMap<String, String>> {
+    let namespace = format!("refs/namespaces/{}/", urn.encode_id());
+    let output = crate::profile::git(
+        git_dir,
+        &[
+            "for-each-ref",
+            "--format=%(objectname) %(refname)",
+            &namespace,
+        ],
+    )?;
+
+    Ok(output
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(oid, name)| (name.trim_start_matches(&namespace), oid))
+        .filter(|(name, _)| !name.starts_with("refs/remotes/"))
+        .map(|(name, oid)| (name.to_owned(), oid.to_owned()))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_record_get() {
+        let urn = Urn::from_str("rad:git:hnrkbtw9t1of4ykjy6er4qqwxtc54k9943eto").unwrap();
+        let other = Urn::from_str("rad:git:hnrkyghsrokxzxpy9pww69xr11dr9q7edbxfo").unwrap();
+        let mut history = History::default();
+
+        assert_eq!(history.get(&urn), None);
+
+        history.record(urn.clone());
+        let synced = history.get(&urn).unwrap();
+        assert_eq!(history.get(&other), None);
+
+        // Recording again updates the existing entry.
+        history.record(urn.clone());
+        history.record(other.clone());
+        assert_eq!(history.entries.len(), 2);
+        assert!(history.get(&urn).unwrap() >= synced);
+        assert!(history.get(&other).is_some());
+
+        let tmp = crate::tempdir::TempDir::new("rad-history").unwrap();
+        let path = tmp.path().join(FILE_NAME);
+        history.write(&path).unwrap();
+
+        let read = History::read(&path).unwrap();
+        assert_eq!(read.get(&urn), history.get(&urn));
+        assert_eq!(read.get(&other), history.get(&other));
+    }
+
+    #[test]
+    fn test_unpushed() {
+        let urn = Urn::from_str("rad:git:hnrkbtw9t1of4ykjy6er4qqwxtc54k9943eto").unwrap();
+        let refs = [
+            ("refs/heads/master", "a"),
+            ("refs/heads/dev", "b"),
+            ("refs/cobs/xyz.radicle.issue/hnrk", "c"),
+        ]
+        .iter()
+        .map(|(name, oid)| (name.to_string(), oid.to_string()))
+        .collect::<BTreeMap<_, _>>();
+        let mut history = History::default();
+
+        assert_eq!(history.unpushed(&urn, &refs).len(), 3);
+
+        // Fetching doesn't mark refs as pushed.
+        history.record(urn.clone());
+        assert_eq!(history.unpushed(&urn, &refs).len(), 3);
+
+        history.record_push(urn.clone(), refs.clone());
+        assert!(history.unpushed(&urn, &refs).is_empty());
+
+        // A ref moved to an older commit is still unpushed.
+        let mut changed = refs.clone();
+        changed.insert("refs/heads/dev".to_owned(), "z".to_owned());
+        changed.insert("refs/heads/new".to_owned(), "d".to_owned());
+        assert_eq!(
+            history.unpushed(&urn, &changed),
+            vec!["refs/heads/dev", "refs/heads/new"]
+        );
+    }
+}
@@ -33,6 +33,7 @@ pub(super) async fn push<S, E>(
     urn: Urn,
     seed: Seed<Vec<SocketAddr>>,
     timeout: time::Duration,
+    on_progress: impl Fn(String),
 ) -> Result<request_pull::Success, Error>
 where
     S: Signer + Clone,
@@ -47,8 +48,8 @@ where
                 request_pull::Response::Error(err) => {
                     return Err(err.into());
                 }
-                request_pull::Response::Progress(_) => {
-                    continue;
+                request_pull::Response::Progress(progress) => {
+                    on_progress(progress.message);
                 }
             },
             Err(err) => {
@@ -0,0 +1,57 @@
+//! Private temporary directories.
+use std::fs;
+use std::io;
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+
+/// A temporary directory only accessible by the current user, removed when dropped.
+///
+/// The directory name is random, and creation fails if it already exists, so files
+/// created inside it can't be pre-created or swapped by other users.
+#[derive(Debug)]
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// Create a new temporary directory, with a name starting with the given prefix.
+    pub fn new(prefix: &str) -> Result<Self, io::Error> {
+        let path = std::env::temp_dir().join(format!("{}-{}", prefix, uuid::Uuid::new_v4()));
+        fs::DirBuilder::new().mode(0o700).create(&path)?;
+
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.path).ok();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_tempdir() {
+        let tmp = TempDir::new("rad-test").unwrap();
+        let path = tmp.path().to_path_buf();
+        let other = TempDir::new("rad-test").unwrap();
+
+        assert_ne!(path, other.path());
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o700
+        );
+        fs::write(path.join("file"), "").unwrap();
+
+        drop(tmp);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,215 @@
+//! Project templates, persisted in the profile.
+//!
+//! A template is either a `templates/<name>.toml` file, or a `templates/<name>/` directory
+//! containing a `template.toml` file. It sets the defaults of `rad init`:
+//!
+//! ```toml
+//! default_branch = "main"
+//! description = "{name}, maintained by the Acme team"
+//! labels = ["bug", "enhancement"]
+//! seeds = ["rad://hyb5to4rshftx4apgmu9s6wnsp4ddmp1mz6ijh4qqey7fb8wrpawxa@seed.acme.xyz"]
+//! gitsigners = "delegates"
+//!
+//! [merge]
+//! style = "fast-forward"
+//! verify = true
+//! ```
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use librad::identities::payload::ProjectPayload;
+use librad::profile::Profile;
+use serde::{Deserialize, Serialize};
+
+use crate::config::SeedConfig;
+use crate::project::{Labels, MergePolicy};
+use crate::seed::Address;
+
+/// Templates directory name, in the profile directory.
+pub const DIR_NAME: &str = "templates";
+/// Template file name, in a template directory.
+pub const FILE_NAME: &str = "template.toml";
+
+/// Which keys are written to the `.gitsigners` file of a new project.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GitSigners {
+    /// The key of this device.
+    Local,
+    /// The keys of all the project delegates' devices.
+    Delegates,
+    /// No `.gitsigners` file, and commit signing isn't configured.
+    None,
+}
+
+impl Default for GitSigners {
+    fn default() -> Self {
+        Self::Local
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Template {
+    /// Default branch of the project.
+    #[serde(default)]
+    pub default_branch: Option<String>,
+    /// Project description. `{name}` is replaced with the project name.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Issue labels used by the project.
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// How patches are merged.
+    #[serde(default)]
+    pub merge: Option<MergePolicy>,
+    /// Seeds to sync the project with, as `rad://<peer>@<host>` addresses.
+    #[serde(default)]
+    pub seeds: Vec<String>,
+    /// Keys written to the `.gitsigners` file.
+    #[serde(default)]
+    pub gitsigners: GitSigners,
+}
+
+impl Template {
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        let content = fs::read_to_string(path)?;
+        let template = toml::from_str(&content)?;
+
+        Ok(template)
+    }
+
+    /// Load a template from the profile, by name.
+    pub fn load(profile: &Profile, name: &str) -> anyhow::Result<Self> {
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            anyhow::bail!("invalid template name '{}'", name);
+        }
+        let dir = dir(profile);
+        let file = dir.join(format!("{}.toml", name));
+        let path = if file.exists() {
+            file
+        } else {
+            dir.join(name).join(FILE_NAME)
+        };
+
+        match Self::read(&path) {
+            Ok(template) => Ok(template),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                let available = list(profile)?;
+                if available.is_empty() {
+                    anyhow::bail!(
+                        "template '{}' not found; templates are read from {}",
+                        name,
+                        dir.display()
+                    );
+                }
+                anyhow::bail!(
+                    "template '{}' not found; available templates: {}",
+                    name,
+                    available.join(", ")
+                );
+            }
+            Err(err) => Err(anyhow::anyhow!("invalid template '{}': {}", name, err)),
+        }
+    }
+
+    /// Get the project description, for the given project name.
+    pub fn description(&self, name: &str) -> Option<String> {
+        self.description
+            .as_ref()
+            .map(|pattern| pattern.replace("{name}", name))
+    }
+
+    /// Get the seeds of the template.
+    pub fn seeds(&self) -> anyhow::Result<Vec<SeedConfig>> {
+        self.seeds
+            .iter()
+            .map(|s| SeedConfig::from_address(&Address::from_str(s)?))
+            .collect()
+    }
+
+    /// Set the template's payload extensions, ie. the labels and merge policy.
+    pub fn apply(&self, payload: &mut ProjectPayload) -> Result<(), serde_json::Error> {
+        if !self.labels.is_empty() {
+            payload.set_ext(Labels {
+                labels: self.labels.clone(),
+            })?;
+        }
+        if let Some(merge) = &self.merge {
+            payload.set_ext(merge.clone())?;
+        }
+        Ok(())
+    }
+}
+
+/// Get the templates directory of a profile.
+pub fn dir(profile: &Profile) -> PathBuf {
+    profile.paths().seeds_file().with_file_name(DIR_NAME)
+}
+
+/// List the names of the templates in the profile.
+pub fn list(profile: &Profile) -> Result<Vec<String>, io::Error> {
+    let entries = match fs::read_dir(dir(profile)) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+    let mut names = Vec::new();
+
+    for entry in entries {
+        let path = entry?.path();
+        let name = if path.is_dir() && path.join(FILE_NAME).exists() {
+            path.file_name()
+        } else if path.extension().map_or(false, |e| e == "toml") {
+            path.file_stem()
+        } else {
+            None
+        };
+        names.extend(name.map(|n| n.to_string_lossy().into_owned()));
+    }
+    names.sort();
+
+    Ok(names)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_template() {
+        let template: Template = toml::from_str(
+            r#"
+            default_branch = "main"
+            description = "{name}, maintained by the Acme team"
+            labels = ["bug", "enhancement"]
+            gitsigners = "delegates"
+
+            [merge]
+            style = "fast-forward"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(template.default_branch.as_deref(), Some("main"));
+        assert_eq!(
+            template.description("acme").as_deref(),
+            Some("acme, maintained by the Acme team")
+        );
+        assert_eq!(template.gitsigners, GitSigners::Delegates);
+        assert_eq!(
+            template.merge,
+            Some(MergePolicy {
+                style: Some(crate::patch::MergeStyle::FastForward),
+                verify: false,
+            })
+        );
+        assert!(toml::from_str::<Template>("branch = \"main\"").is_err());
+        assert_eq!(
+            toml::from_str::<Template>("").unwrap().gitsigners,
+            GitSigners::Local
+        );
+    }
+}
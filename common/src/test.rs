@@ -20,6 +20,8 @@ use librad::PublicKey;
 
 use super::{keys, person, profile, project, signer, test};
 
+pub mod seed;
+
 pub type BoxedError = Box<dyn error::Error>;
 
 pub const USER_PASS: &str = "password";
@@ -0,0 +1,87 @@
+//! In-process fake seed, serving canned seed API responses.
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use librad::crypto::peer::PeerId;
+use serde::Serialize;
+use url::{Host, Url};
+
+use crate::config::SeedConfig;
+
+type Routes = Arc<Mutex<HashMap<String, String>>>;
+
+/// A fake seed HTTP API, listening on a random local port. Requests to paths without
+/// a route are answered with `404 Not Found`.
+pub struct FakeSeed {
+    addr: SocketAddr,
+    routes: Routes,
+}
+
+impl FakeSeed {
+    /// Start the fake seed in a background thread.
+    pub fn spawn() -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let routes = Routes::default();
+        let handle = routes.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                serve(stream, &handle).ok();
+            }
+        });
+
+        Ok(Self { addr, routes })
+    }
+
+    /// Respond to `GET` requests on the given path with a JSON body.
+    pub fn route(&self, path: &str, body: impl Serialize) -> &Self {
+        let body = serde_json::to_string(&body).expect("FakeSeed::route: invalid body");
+        self.routes.lock().unwrap().insert(path.to_owned(), body);
+        self
+    }
+
+    /// The API URL of the seed.
+    pub fn url(&self) -> Url {
+        Url::parse(&format!("http://{}", self.addr)).unwrap()
+    }
+
+    /// A seed configuration pointing to this seed's API.
+    pub fn config(&self, peer: Option<PeerId>) -> SeedConfig {
+        let mut config = SeedConfig::new(Host::Ipv4(Ipv4Addr::LOCALHOST), peer);
+        config.api = self.url();
+        config
+    }
+}
+
+fn serve(stream: TcpStream, routes: &Routes) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+
+    // Skip the headers.
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    let path = request.split_whitespace().nth(1).unwrap_or_default();
+    let path = path.split('?').next().unwrap_or_default();
+    let (status, body) = match routes.lock().unwrap().get(path) {
+        Some(body) => ("200 OK", body.clone()),
+        None => ("404 Not Found", String::from("{\"message\":\"Not Found\"}")),
+    };
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
@@ -0,0 +1,288 @@
+//! Removed projects, kept in the profile until their grace period expires.
+//!
+//! When a project is removed, its refs are bundled into the trash before being deleted
+//! from storage, so that the removal can be undone with `rad rm --restore`.
+use std::fs;
+use std::io;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use librad::git::Urn;
+use librad::profile::Profile;
+use librad::PeerId;
+use serde::{Deserialize, Serialize};
+
+use crate::cobs::shared::Timestamp;
+use crate::profile;
+use crate::project::deserialize_urn;
+
+/// Trash directory name, in the profile directory.
+pub const DIR_NAME: &str = "trash";
+
+/// How long removed projects are kept before being purged, in seconds.
+pub const GRACE_PERIOD: u64 = 60 * 60 * 24 * 7;
+
+/// A removed project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Item {
+    #[serde(deserialize_with = "deserialize_urn")]
+    pub urn: Urn,
+    /// Project name, for display.
+    pub name: String,
+    /// When the project was removed.
+    pub removed: Timestamp,
+    /// Peers that were tracked, and are tracked again on restore.
+    #[serde(default)]
+    pub tracked: Vec<PeerId>,
+    /// Whether collaborative objects were kept in storage. If so, they are left out of
+    /// the trash, and aren't touched on restore.
+    #[serde(default)]
+    pub keep_cobs: bool,
+}
+
+impl Item {
+    /// Whether the grace period of this item is over.
+    pub fn is_expired(&self) -> bool {
+        Timestamp::now().as_secs() >= self.removed.as_secs() + GRACE_PERIOD
+    }
+
+    /// When this item will be purged.
+    pub fn expires(&self) -> Timestamp {
+        Timestamp::new(self.removed.as_secs() + GRACE_PERIOD)
+    }
+}
+
+/// Get the trash directory of a profile.
+pub fn dir(profile: &Profile) -> PathBuf {
+    profile.paths().seeds_file().with_file_name(DIR_NAME)
+}
+
+/// Move a project's refs to the trash, and delete them from storage. When
+/// [`Item::keep_cobs`] is set, collaborative object refs are kept in storage.
+pub fn remove(profile: &Profile, item: &Item) -> anyhow::Result<()> {
+    let git_dir = profile.paths().git_dir();
+    let bundle = profile::archive::bundle(git_dir, &item.urn, |r| !item.keep_cobs || !is_cob(r))?;
+    let dir = dir(profile);
+    let (meta, data) = paths(&dir, &item.urn);
+
+    fs::create_dir_all(&dir)?;
+    fs::write(&data, bundle)?;
+    fs::write(&meta, toml::to_string_pretty(item)?)?;
+
+    delete_refs(git_dir, &item.urn, item.keep_cobs)?;
+
+    Ok(())
+}
+
+/// Restore a project's refs from the trash. Returns the restored item.
+pub fn restore(profile: &Profile, urn: &Urn) -> anyhow::Result<Item> {
+    let item = get(profile, urn)?
+        .ok_or_else(|| anyhow::anyhow!("project {} was not found in the trash", urn))?;
+    let (meta, data) = paths(&dir(profile), urn);
+    let bundle = fs::read(&data)?;
+
+    profile::archive::unbundle(profile.paths().git_dir(), urn, &bundle)?;
+
+    fs::remove_file(meta)?;
+    fs::remove_file(data)?;
+
+    Ok(item)
+}
+
+/// Get a removed project.
+pub fn get(profile: &Profile, urn: &Urn) -> anyhow::Result<Option<Item>> {
+    let (meta, _) = paths(&dir(profile), urn);
+
+    match fs::read_to_string(meta) {
+        Ok(contents) => Ok(Some(toml::from_str(&contents)?)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// List the removed projects, oldest first.
+pub fn list(profile: &Profile) -> anyhow::Result<Vec<Item>> {
+    let entries = match fs::read_dir(dir(profile)) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err.into()),
+    };
+    let mut items = Vec::new();
+
+    for entry in entries {
+        let path = entry?.path();
+
+        if path.extension().map_or(false, |e| e == "toml") {
+            items.push(toml::from_str::<Item>(&fs::read_to_string(path)?)?);
+        }
+    }
+    items.sort_by_key(|i| i.removed);
+
+    Ok(items)
+}
+
+/// Delete the removed projects whose grace period is over. Returns the purged items.
+pub fn purge(profile: &Profile) -> anyhow::Result<Vec<Item>> {
+    let dir = dir(profile);
+    let mut purged = Vec::new();
+
+    for item in list(profile)? {
+        if item.is_expired() {
+            let (meta, data) = paths(&dir, &item.urn);
+
+            fs::remove_file(data).ok();
+            fs::remove_file(meta)?;
+            purged.push(item);
+        }
+    }
+    Ok(purged)
+}
+
+/// Delete a project's refs from storage, including packed refs. Returns the number of
+/// refs deleted.
+pub fn delete_refs(git_dir: &Path, urn: &Urn, keep_cobs: bool) -> anyhow::Result<usize> {
+    let namespace = format!("refs/namespaces/{}/", urn.encode_id());
+    let refs = profile::git(
+        git_dir,
+        &["for-each-ref", "--format=%(refname)", &namespace],
+    )?;
+    let input = refs
+        .lines()
+        .filter(|r| !keep_cobs || !is_cob(r))
+        .map(|r| format!("delete {}\n", r))
+        .collect::<String>();
+
+    if input.is_empty() {
+        return Ok(0);
+    }
+
+    let mut child = Command::new("git")
+        .arg("--git-dir")
+        .arg(git_dir)
+        .args(&["update-ref", "--stdin"])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(input.as_bytes())?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "`git update-ref` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(input.lines().count())
+}
+
+/// Whether a namespaced ref is a collaborative object ref, eg.
+/// `refs/namespaces/<id>/refs/cobs/..` or `refs/namespaces/<id>/refs/remotes/<peer>/cobs/..`.
+pub fn is_cob(refname: &str) -> bool {
+    matches!(
+        refname.split('/').skip(3).collect::<Vec<_>>().as_slice(),
+        ["refs", "cobs", ..] | ["refs", "remotes", _, "cobs", ..]
+    )
+}
+
+/// Metadata and bundle paths of a removed project.
+fn paths(dir: &Path, urn: &Urn) -> (PathBuf, PathBuf) {
+    let id = urn.encode_id();
+
+    (
+        dir.join(format!("{}.toml", id)),
+        dir.join(format!("{}.bundle", id)),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test;
+
+    #[test]
+    fn test_is_cob() {
+        assert!(is_cob(
+            "refs/namespaces/hnrk/refs/cobs/xyz.radicle.issue/hnrk"
+        ));
+        assert!(is_cob(
+            "refs/namespaces/hnrk/refs/remotes/hyb/cobs/xyz.radicle.patch/hnrk"
+        ));
+        assert!(!is_cob("refs/namespaces/hnrk/refs/heads/master"));
+        assert!(!is_cob("refs/namespaces/hnrk/refs/heads/cobs"));
+    }
+
+    #[test]
+    fn test_remove_and_restore() {
+        let (storage, profile, _, project) = test::setup::profile();
+        let urn = project.urn();
+        let item = Item {
+            urn: urn.clone(),
+            name: String::from("nakamoto"),
+            removed: Timestamp::now(),
+            tracked: vec![],
+            keep_cobs: false,
+        };
+
+        remove(&profile, &item).unwrap();
+        assert!(crate::project::get(&storage, &urn).unwrap().is_none());
+        assert_eq!(get(&profile, &urn).unwrap(), Some(item.clone()));
+        assert_eq!(list(&profile).unwrap(), vec![item.clone()]);
+
+        assert_eq!(restore(&profile, &urn).unwrap(), item);
+        assert!(crate::project::get(&storage, &urn).unwrap().is_some());
+        assert!(get(&profile, &urn).unwrap().is_none());
+        assert!(restore(&profile, &urn).is_err());
+    }
+
+    #[test]
+    fn test_remove_and_restore_keep_cobs() {
+        let (storage, profile, whoami, project) = test::setup::profile();
+        let urn = project.urn();
+        let git_dir = profile.paths().git_dir();
+        let namespace = format!("refs/namespaces/{}", urn.encode_id());
+        let cob = format!(
+            "{}/refs/cobs/xyz.radicle.issue/{}",
+            namespace,
+            urn.encode_id()
+        );
+        let id = profile::git(
+            git_dir,
+            &["rev-parse", &format!("{}/refs/rad/id", namespace)],
+        )
+        .unwrap();
+        let other = profile::git(
+            git_dir,
+            &[
+                "rev-parse",
+                &format!("refs/namespaces/{}/refs/rad/id", whoami.urn().encode_id()),
+            ],
+        )
+        .unwrap();
+        let item = Item {
+            urn: urn.clone(),
+            name: String::from("nakamoto"),
+            removed: Timestamp::now(),
+            tracked: vec![],
+            keep_cobs: true,
+        };
+        profile::git(git_dir, &["update-ref", &cob, id.trim()]).unwrap();
+
+        remove(&profile, &item).unwrap();
+        assert!(crate::project::get(&storage, &urn).unwrap().is_none());
+
+        // The kept COB is updated while the project is in the trash.
+        profile::git(git_dir, &["update-ref", &cob, other.trim()]).unwrap();
+
+        assert_eq!(restore(&profile, &urn).unwrap(), item);
+        assert!(crate::project::get(&storage, &urn).unwrap().is_some());
+        assert_eq!(
+            profile::git(git_dir, &["rev-parse", &cob]).unwrap().trim(),
+            other.trim()
+        );
+    }
+}
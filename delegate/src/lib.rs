@@ -0,0 +1,285 @@
+use std::convert::TryFrom;
+use std::ffi::OsString;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context as _};
+use either::Either;
+
+use librad::git::identities;
+use librad::git::storage::Storage;
+use librad::git::Urn;
+use librad::PeerId;
+
+use radicle_common::args::{Args, Error, Help};
+use radicle_common::project::Delegate;
+use radicle_common::{keys, person, project, sync, tokio};
+use radicle_terminal as term;
+
+pub const HELP: Help = Help {
+    name: "delegate",
+    description: env!("CARGO_PKG_DESCRIPTION"),
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad delegate [ls]
+    rad delegate add <peer-id | urn> [--no-sync]
+    rad delegate rm <peer-id | urn> [--no-sync]
+
+    Manages the delegates of the current project. A delegate is either a key, given
+    by its peer id, or a personal identity, given by its URN. Personal identities must
+    be present in local storage.
+
+    Changes to the delegates must be signed by a majority of the current delegates, and
+    by a majority of the proposed delegates. Until they are, the change is published
+    as pending, with a warning, and is shown by `rad delegate ls`.
+
+Options
+
+    --no-sync   Don't sync the project with the configured seeds
+    --help      Print help
+"#,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Operation {
+    List,
+    Add { delegate: Either<PeerId, Urn> },
+    Remove { delegate: Either<PeerId, Urn> },
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub op: Operation,
+    pub sync: bool,
+}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+        let mut op: Option<String> = None;
+        let mut delegate: Option<Either<PeerId, Urn>> = None;
+        let mut sync = true;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("no-sync") => {
+                    sync = false;
+                }
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                Value(val) if op.is_none() => match val.to_string_lossy().as_ref() {
+                    "l" | "ls" => op = Some(String::from("ls")),
+                    "a" | "add" => op = Some(String::from("add")),
+                    "r" | "rm" => op = Some(String::from("rm")),
+
+                    unknown => anyhow::bail!("unknown operation '{}'", unknown),
+                },
+                Value(val) if delegate.is_none() => {
+                    let val = val.to_string_lossy();
+
+                    delegate = if let Ok(peer) = PeerId::from_str(&val) {
+                        Some(Either::Left(peer))
+                    } else if let Ok(urn) = Urn::from_str(&val) {
+                        Some(Either::Right(urn))
+                    } else {
+                        anyhow::bail!("invalid peer id or URN '{}'", val);
+                    };
+                }
+                _ => return Err(anyhow!(arg.unexpected())),
+            }
+        }
+
+        let op = match (op.as_deref(), delegate) {
+            (None | Some("ls"), None) => Operation::List,
+            (Some("add"), Some(delegate)) => Operation::Add { delegate },
+            (Some("rm"), Some(delegate)) => Operation::Remove { delegate },
+            (Some(op), None) => anyhow::bail!("a peer id or URN must be specified for '{}'", op),
+            (_, Some(_)) => anyhow::bail!("unexpected delegate"),
+        };
+
+        Ok((Options { op, sync }, vec![]))
+    }
+}
+
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let (urn, _) = project::cwd()
+        .map_err(|_| anyhow!("this command must be run in the context of a project"))?;
+    let profile = ctx.profile()?;
+    let signer = term::signer(&profile)?;
+    let storage = keys::storage(&profile, signer.clone())?;
+
+    match options.op {
+        Operation::List => return list(&storage, &urn),
+        Operation::Add { delegate } => update(&storage, &urn, delegate, true)?,
+        Operation::Remove { delegate } => update(&storage, &urn, delegate, false)?,
+    }
+
+    if options.sync {
+        let rt = tokio::runtime::Runtime::new()?;
+        let seeds = term::sync::seeds(&profile, &urn)?;
+
+        term::sync::sync(urn, seeds, sync::Mode::Push, &profile, signer, &rt)?;
+    }
+
+    Ok(())
+}
+
+/// List the delegates of a project, and its pending identity update, if any.
+pub fn list(storage: &Storage, urn: &Urn) -> anyhow::Result<()> {
+    let project = project::get(storage, urn)?
+        .ok_or_else(|| anyhow!("couldn't load project {} from local state", urn))?;
+    let verified = project::Metadata::try_from(project.verified(storage)?.into_inner())?;
+
+    table(storage, &verified.delegates)?;
+
+    if let Some(pending) = project::pending(storage, urn)? {
+        let proposed = project::Metadata::try_from(pending.project.clone())?;
+
+        term::blank();
+        term::info!(
+            "Pending update {} ({} of {} signature(s))",
+            term::format::secondary(pending.project.revision),
+            pending.signed.len(),
+            pending.quorum
+        );
+        table(storage, &proposed.delegates)?;
+
+        let missing = pending
+            .delegates
+            .iter()
+            .filter(|d| !pending.signed.contains(d))
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            term::blank();
+            term::info!("Awaiting signatures from {}", missing.join(", "));
+        }
+    }
+
+    Ok(())
+}
+
+/// Add or remove a project delegate. Signed with the local key, which must be a delegate.
+pub fn update(
+    storage: &Storage,
+    urn: &Urn,
+    delegate: Either<PeerId, Urn>,
+    add: bool,
+) -> anyhow::Result<()> {
+    if let Some(pending) = project::pending(storage, urn)? {
+        anyhow::bail!(
+            "project has a pending identity update {} awaiting signatures from other delegates",
+            pending.project.revision
+        );
+    }
+    let verified = identities::project::verify(storage, urn)?
+        .ok_or_else(|| anyhow!("project {} not found", urn))?
+        .into_inner();
+    let metadata = project::Metadata::try_from(verified.clone())?;
+
+    if !metadata
+        .delegates
+        .iter()
+        .any(|d| d.contains(storage.peer_id()))
+    {
+        anyhow::bail!("only project delegates can change the delegates");
+    }
+
+    let mut delegations = verified
+        .delegations()
+        .iter()
+        .map(|d| d.map_left(|k| *k).map_right(|p| p.clone()))
+        .collect::<Vec<_>>();
+    let position = delegations.iter().position(|d| match (d, &delegate) {
+        (Either::Left(key), Either::Left(peer)) => key == peer.as_public_key(),
+        (Either::Right(person), Either::Right(urn)) => person.urn() == *urn,
+        _ => false,
+    });
+
+    match (position, &delegate) {
+        (Some(_), _) if add => anyhow::bail!("{} is already a delegate", fmt(&delegate)),
+        (None, _) if !add => anyhow::bail!("{} is not a delegate", fmt(&delegate)),
+        (Some(i), _) => {
+            delegations.remove(i);
+
+            if delegations.is_empty() {
+                anyhow::bail!("a project must have at least one delegate");
+            }
+        }
+        (None, Either::Left(peer)) => {
+            delegations.push(Either::Left(*peer.as_public_key()));
+        }
+        (None, Either::Right(urn)) => {
+            let person = person::get(storage, urn)?.ok_or_else(|| {
+                anyhow!(
+                    "personal identity {} not found in local storage, fetch it first",
+                    urn
+                )
+            })?;
+            delegations.push(Either::Right(person));
+        }
+    }
+
+    let project = project::set_delegations(storage, urn, delegations)
+        .context("couldn't update project delegations")?;
+
+    term::success!(
+        "{} {}",
+        if add {
+            "Added delegate"
+        } else {
+            "Removed delegate"
+        },
+        term::format::tertiary(fmt(&delegate))
+    );
+    if let Some(pending) = project::pending(storage, urn)? {
+        term::warning(&format!(
+            "Update {} has {} of {} required delegate signature(s), it is pending until other delegates sign it",
+            project.revision,
+            pending.signed.len(),
+            pending.quorum
+        ));
+    }
+
+    Ok(())
+}
+
+fn table(storage: &Storage, delegates: &[Delegate]) -> anyhow::Result<()> {
+    let mut table = term::Table::default();
+
+    for delegate in delegates {
+        let (name, kind) = match delegate {
+            Delegate::Direct { .. } => (String::new(), "key"),
+            Delegate::Indirect { urn, .. } => (
+                person::get(storage, urn)?
+                    .map(|p| p.subject().name.to_string())
+                    .unwrap_or_default(),
+                "person",
+            ),
+        };
+        table.push([
+            term::format::bold(delegate.to_string()),
+            term::format::tertiary(name),
+            term::format::dim(kind),
+            if delegate.contains(storage.peer_id()) {
+                term::format::badge_primary("you")
+            } else {
+                String::new()
+            },
+        ]);
+    }
+    table.render();
+
+    Ok(())
+}
+
+fn fmt(delegate: &Either<PeerId, Urn>) -> String {
+    match delegate {
+        Either::Left(peer) => peer.default_encoding(),
+        Either::Right(urn) => urn.to_string(),
+    }
+}
@@ -1,16 +1,21 @@
+use std::convert::TryFrom;
 use std::ffi::OsString;
 use std::str::FromStr;
 
 use radicle_common::args::{Args, Error, Help};
 use radicle_common::keys;
+use radicle_common::payload::{self, Assignment};
 use radicle_terminal as term;
 
+use librad::canonical::Cstring;
 use librad::git::identities::{any, person, project, SomeIdentity};
 use librad::git::Urn;
+use librad::git_ext::RefLike;
 
 use link_identities::payload::{PersonPayload, ProjectPayload};
 
 use anyhow::anyhow;
+use serde::{de::DeserializeOwned, Serialize};
 
 pub const HELP: Help = Help {
     name: "edit",
@@ -24,15 +29,43 @@ Usage
     Edits the identity document pointed to by the URN. If it isn't specified,
     the current project is edited.
 
+    Without field options, the payload is opened in an editor. If the edited payload
+    is invalid, the error is shown and the editor can be re-opened with your changes.
+
+    Extensions are namespaced objects attached to the payload. The following namespaces
+    have a known meaning, and are shown by `rad inspect --ext`: `website`, `matrix`,
+    `team` and `security`.
+
 Options
 
-    --help              Print help
+    --name <name>               Set the name of the project or person
+    --description <text>        Set the project description
+    --default-branch <branch>   Set the project default branch
+    --set <ns>.<key>=<value>    Set a field of a payload extension, eg. `matrix.handle=@me:matrix.org`.
+                                An empty value removes the field. Can be specified multiple times
+    --dry-run                   Show the changes, without updating the identity
+    --help                      Print help
 "#,
 };
 
 #[derive(Default, Debug, Eq, PartialEq)]
 pub struct Options {
     pub urn: Option<Urn>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub default_branch: Option<String>,
+    pub set: Vec<Assignment>,
+    pub dry_run: bool,
+}
+
+impl Options {
+    /// Whether any payload field is set from the command line.
+    fn has_fields(&self) -> bool {
+        self.name.is_some()
+            || self.description.is_some()
+            || self.default_branch.is_some()
+            || !self.set.is_empty()
+    }
 }
 
 impl Args for Options {
@@ -40,18 +73,37 @@ impl Args for Options {
         use lexopt::prelude::*;
 
         let mut parser = lexopt::Parser::from_args(args);
-        let mut urn: Option<Urn> = None;
+        let mut options = Options::default();
 
         while let Some(arg) = parser.next()? {
             match arg {
+                Long("name") => {
+                    options.name = Some(parser.value()?.to_string_lossy().into());
+                }
+                Long("description") => {
+                    options.description = Some(parser.value()?.to_string_lossy().into());
+                }
+                Long("default-branch") => {
+                    options.default_branch = Some(parser.value()?.to_string_lossy().into());
+                }
+                Long("set") => {
+                    let val = parser.value()?;
+                    let val = val.to_string_lossy();
+                    let assignment = val.parse().map_err(|e: String| anyhow!(e))?;
+
+                    options.set.push(assignment);
+                }
+                Long("dry-run") => {
+                    options.dry_run = true;
+                }
                 Long("help") => {
                     return Err(Error::Help.into());
                 }
-                Value(val) if urn.is_none() => {
+                Value(val) if options.urn.is_none() => {
                     let val = val.to_string_lossy();
 
                     if let Ok(val) = Urn::from_str(&val) {
-                        urn = Some(val);
+                        options.urn = Some(val);
                     } else {
                         return Err(anyhow!("invalid URN '{}'", val));
                     }
@@ -60,7 +112,7 @@ impl Args for Options {
             }
         }
 
-        Ok((Options { urn }, vec![]))
+        Ok((options, vec![]))
     }
 }
 
@@ -71,6 +123,7 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
 
     let urn = options
         .urn
+        .clone()
         .or_else(|| radicle_common::project::cwd().ok().map(|(urn, _)| urn))
         .ok_or_else(|| anyhow!("Couldn't get URN from either command line or cwd"))?;
 
@@ -79,32 +132,48 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
 
     match identity {
         SomeIdentity::Project(_) => {
-            let payload = serde_json::to_string_pretty(
-                project::verify(&storage, &urn)?
-                    .ok_or_else(|| anyhow!("Couldn't get project's identity doc"))?
-                    .payload(),
-            )?;
-            match term::Editor::new().edit(&payload)? {
-                Some(updated_payload) => {
-                    let payload: ProjectPayload = serde_json::from_str(&updated_payload)?;
-                    project::update(&storage, &urn, None, payload, None)?;
-                }
-                None => return Err(anyhow!("Operation aborted!")),
+            let current = project::verify(&storage, &urn)?
+                .ok_or_else(|| anyhow!("Couldn't get project's identity doc"))?
+                .payload()
+                .clone();
+            let payload = if options.has_fields() {
+                let payload = set_project_fields(current.clone(), &options);
+                let payload = set_extensions(payload, &options.set)?;
+                validate_project(&payload)?;
+                payload
+            } else {
+                edit(&current, validate_project)?
+            };
+
+            if !changed(&current, &payload, options.dry_run)? {
+                return Ok(());
             }
+            project::update(&storage, &urn, None, payload, None)?;
         }
         SomeIdentity::Person(_) => {
-            let payload = serde_json::to_string_pretty(
-                person::verify(&storage, &urn)?
-                    .ok_or_else(|| anyhow!("Couldn't get person's identity doc"))?
-                    .payload(),
-            )?;
-            match term::Editor::new().edit(&payload)? {
-                Some(updated_payload) => {
-                    let payload: PersonPayload = serde_json::from_str(&updated_payload)?;
-                    person::update(&storage, &urn, None, payload, None)?;
+            let current = person::verify(&storage, &urn)?
+                .ok_or_else(|| anyhow!("Couldn't get person's identity doc"))?
+                .payload()
+                .clone();
+            let payload = if options.has_fields() {
+                if options.description.is_some() || options.default_branch.is_some() {
+                    anyhow::bail!("Only `--name` can be set on a person");
                 }
-                None => return Err(anyhow!("Operation aborted!")),
+                let mut payload = current.clone();
+                if let Some(name) = &options.name {
+                    payload.subject.name = Cstring::from(name.as_str());
+                }
+                let payload = set_extensions(payload, &options.set)?;
+                validate_person(&payload)?;
+                payload
+            } else {
+                edit(&current, validate_person)?
+            };
+
+            if !changed(&current, &payload, options.dry_run)? {
+                return Ok(());
             }
+            person::update(&storage, &urn, None, payload, None)?;
         }
         _ => {
             anyhow::bail!("Operation not supported for identity type of {}", urn)
@@ -115,3 +184,119 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Open a payload in the editor until it is valid. When it isn't, the error is shown,
+/// and the editor is re-opened with the user's text.
+fn edit<T>(payload: &T, validate: impl Fn(&T) -> anyhow::Result<()>) -> anyhow::Result<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let mut text = serde_json::to_string_pretty(payload)?;
+
+    loop {
+        text = term::Editor::new()
+            .edit(&text)?
+            .ok_or_else(|| anyhow!("Operation aborted!"))?;
+
+        let result = match serde_json::from_str::<T>(&text) {
+            Ok(payload) => validate(&payload).map(|_| payload),
+            Err(err) => {
+                let line = text.lines().nth(err.line().saturating_sub(1));
+                if let Some(line) = line {
+                    term::blank();
+                    term::indented(&term::format::dim(line));
+                    term::indented(&format!(
+                        "{}{}",
+                        " ".repeat(err.column().saturating_sub(1)),
+                        term::format::negative("^")
+                    ));
+                }
+                Err(anyhow!(
+                    "line {}, column {}: {}",
+                    err.line(),
+                    err.column(),
+                    invalid(&err)
+                ))
+            }
+        };
+
+        match result {
+            Ok(payload) => return Ok(payload),
+            Err(err) => {
+                term::error(format!("Invalid payload: {}", err));
+
+                if !term::confirm("Edit again?") {
+                    anyhow::bail!("Operation aborted!");
+                }
+            }
+        }
+    }
+}
+
+/// Error message without serde's location suffix, which we display separately.
+fn invalid(err: &serde_json::Error) -> String {
+    let msg = err.to_string();
+    let suffix = format!(" at line {} column {}", err.line(), err.column());
+
+    msg.strip_suffix(&suffix).unwrap_or(&msg).to_owned()
+}
+
+/// Show the payload diff. Returns whether the identity should be updated.
+fn changed<T: Serialize>(current: &T, payload: &T, dry_run: bool) -> anyhow::Result<bool> {
+    let current = serde_json::to_string_pretty(current)?;
+    let payload = serde_json::to_string_pretty(payload)?;
+
+    if current == payload {
+        term::info!("No changes to the payload");
+        return Ok(false);
+    }
+    if dry_run {
+        term::diff(&current, &payload);
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+fn set_project_fields(mut payload: ProjectPayload, options: &Options) -> ProjectPayload {
+    if let Some(name) = &options.name {
+        payload.subject.name = Cstring::from(name.as_str());
+    }
+    if let Some(description) = &options.description {
+        payload.subject.description = Some(Cstring::from(description.as_str()));
+    }
+    if let Some(branch) = &options.default_branch {
+        payload.subject.default_branch = Some(Cstring::from(branch.as_str()));
+    }
+    payload
+}
+
+fn set_extensions<T>(payload: T, set: &[Assignment]) -> anyhow::Result<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    if set.is_empty() {
+        return Ok(payload);
+    }
+    let mut json = serde_json::to_value(payload)?;
+    payload::assign(&mut json, set).map_err(|e| anyhow!(e))?;
+
+    Ok(serde_json::from_value(json)?)
+}
+
+fn validate_project(payload: &ProjectPayload) -> anyhow::Result<()> {
+    if payload.subject.name.to_string().trim().is_empty() {
+        anyhow::bail!("name must not be empty");
+    }
+    if let Some(branch) = &payload.subject.default_branch {
+        RefLike::try_from(branch.to_string())
+            .map_err(|_| anyhow!("default branch '{}' is not a valid branch name", branch))?;
+    }
+    Ok(())
+}
+
+fn validate_person(payload: &PersonPayload) -> anyhow::Result<()> {
+    if payload.subject.name.to_string().trim().is_empty() {
+        anyhow::bail!("name must not be empty");
+    }
+    Ok(())
+}
@@ -12,6 +12,7 @@ use radicle_common::ethereum::{
     resolver::{self, PublicResolver},
     ProviderOptions, SignerOptions,
 };
+use radicle_common::seed::api::SeedApiClient;
 use radicle_common::{keys, person, seed};
 use radicle_terminal as term;
 
@@ -214,13 +215,17 @@ async fn setup(
     };
 
     let seed_host: String = term::text_input("Seed host", None)?;
-    let seed_url = url::Url::parse(&format!("https://{}", seed_host))?;
+    let seed_addr = seed::Address::new(
+        url::Host::parse(&seed_host)?,
+        seed::Protocol::Api { local: false },
+    );
+    let client = SeedApiClient::from_address(&seed_addr, std::iter::empty());
 
     let spinner = term::spinner("Querying seed...");
-    let seed_id = match seed::get_seed_id(seed_url) {
-        Ok(id) => {
+    let seed_id = match client.peer() {
+        Ok(peer) => {
             spinner.clear();
-            term::text_input("Seed ID", Some(id))?
+            term::text_input("Seed ID", Some(peer.id))?
         }
         Err(err) => {
             spinner.failed();
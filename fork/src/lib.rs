@@ -0,0 +1,151 @@
+use std::ffi::OsString;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context as _};
+
+use librad::git::Urn;
+
+use radicle_common::args::{Args, Error, Help};
+use radicle_common::{keys, project, tokio};
+use radicle_terminal as term;
+
+pub const HELP: Help = Help {
+    name: "fork",
+    description: env!("CARGO_PKG_DESCRIPTION"),
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad fork <urn> [<option>...]
+
+    Creates a new project from an existing one. The fork is a new project identity,
+    delegated to you, that records the project it was forked from. Its default branch
+    starts at the head of the parent project's default branch.
+
+    The parent project must be in local storage. From a working copy of the fork,
+    patches can be proposed to the parent project's delegates with `rad patch --parent`.
+
+Options
+
+    --name <name>           Name of the fork (default: the parent project's name)
+    --description <text>    Description of the fork (default: the parent project's description)
+    --no-sync               Don't sync the fork with the configured seeds
+    --help                  Print help
+"#,
+};
+
+#[derive(Debug)]
+pub struct Options {
+    pub urn: Urn,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub sync: bool,
+}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+        let mut urn: Option<Urn> = None;
+        let mut name: Option<String> = None;
+        let mut description: Option<String> = None;
+        let mut sync = true;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("name") => {
+                    name = Some(parser.value()?.to_string_lossy().into());
+                }
+                Long("description") => {
+                    description = Some(parser.value()?.to_string_lossy().into());
+                }
+                Long("no-sync") => {
+                    sync = false;
+                }
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                Value(val) if urn.is_none() => {
+                    let val = val.to_string_lossy();
+                    let val = Urn::from_str(&val).context(format!("invalid URN '{}'", val))?;
+
+                    urn = Some(val);
+                }
+                _ => return Err(anyhow!(arg.unexpected())),
+            }
+        }
+
+        Ok((
+            Options {
+                urn: urn.ok_or_else(|| {
+                    anyhow!("a project URN to fork must be provided; see `rad fork --help`")
+                })?,
+                name,
+                description,
+                sync,
+            },
+            vec![],
+        ))
+    }
+}
+
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let profile = ctx.profile()?;
+    let signer = term::signer(&profile)?;
+    let storage = keys::storage(&profile, signer.clone())?;
+    let parent = project::get(&storage, &options.urn)?.ok_or_else(|| {
+        anyhow!(
+            "project {} not found in local storage; sync it first with `rad sync {}`",
+            options.urn,
+            options.urn
+        )
+    })?;
+
+    let name = options.name.unwrap_or_else(|| parent.name.clone());
+    if name.trim().is_empty() {
+        anyhow::bail!("name must not be empty");
+    }
+    let description = options
+        .description
+        .unwrap_or_else(|| parent.description.clone());
+    let payload = project::payload(name, description, parent.default_branch.to_string());
+
+    let spinner = term::spinner(format!(
+        "Forking {} ({})...",
+        term::format::highlight(&parent.name),
+        term::format::tertiary(&parent.urn)
+    ));
+    let fork = match project::fork(&storage, &parent, payload) {
+        Ok(fork) => {
+            spinner.finish();
+            fork
+        }
+        Err(err) => {
+            spinner.failed();
+            return Err(err);
+        }
+    };
+    let urn = fork.urn();
+
+    term::success!(
+        "Created fork {} of {}",
+        term::format::highlight(&urn),
+        term::format::tertiary(&parent.urn)
+    );
+
+    if options.sync {
+        let rt = tokio::runtime::Runtime::new()?;
+        let seeds = term::sync::seeds(&profile, &urn)?;
+
+        term::sync::push_or_queue(urn.clone(), seeds, &profile, signer, &rt)?;
+    }
+
+    term::blank();
+    term::tip!(
+        "To get a working copy of the fork, run `rad checkout {}`",
+        urn
+    );
+
+    Ok(())
+}
@@ -9,16 +9,20 @@ pub use rad_auth;
 pub use rad_checkout;
 pub use rad_clone;
 pub use rad_comment;
+pub use rad_delegate;
 pub use rad_edit;
 #[cfg(feature = "ethereum")]
 pub use rad_ens;
+pub use rad_fork;
 #[cfg(feature = "ethereum")]
 pub use rad_gov;
+pub use rad_identity;
 pub use rad_init;
 pub use rad_inspect;
 pub use rad_issue;
 pub use rad_ls;
 pub use rad_merge;
+pub use rad_mirror;
 pub use rad_patch;
 pub use rad_path;
 pub use rad_pull;
@@ -26,10 +30,13 @@ pub use rad_push;
 pub use rad_remote;
 pub use rad_review;
 pub use rad_rm;
+pub use rad_seed;
 pub use rad_self;
+pub use rad_signers;
 pub use rad_sync;
 pub use rad_track;
 pub use rad_untrack;
+pub use rad_verify;
 
 pub const HELP: Help = Help {
     name: "help",
@@ -58,6 +65,13 @@ const COMMANDS: &[Help] = &[
     rad_account::HELP,
     rad_rm::HELP,
     rad_edit::HELP,
+    rad_seed::HELP,
+    rad_verify::HELP,
+    rad_signers::HELP,
+    rad_delegate::HELP,
+    rad_identity::HELP,
+    rad_fork::HELP,
+    rad_mirror::HELP,
     crate::HELP,
 ];
 
@@ -0,0 +1,316 @@
+use std::convert::TryFrom;
+use std::ffi::OsString;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context as _};
+use either::Either;
+
+use librad::git::identities::{self, Project};
+use librad::git::storage::Storage;
+use librad::git::Urn;
+use librad::identities::payload::ProjectPayload;
+use librad::PeerId;
+
+use radicle_common as common;
+use radicle_common::args::{Args, Error, Help};
+use radicle_common::{keys, project, sync, tokio};
+use radicle_terminal as term;
+
+pub const HELP: Help = Help {
+    name: "identity",
+    description: env!("CARGO_PKG_DESCRIPTION"),
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad identity propose [--no-sync]
+    rad identity review [<peer-id>] [--no-sync]
+    rad identity accept <peer-id> [--no-sync]
+
+    Manages updates to the identity of the current project. When a project has several
+    delegates, an update must be signed by a majority of them before it takes effect.
+
+    `propose` opens the project payload in an editor, and signs the edited payload as
+    a new revision. `review` fetches the project and shows the revisions proposed by
+    the other delegates, as a diff against the current revision. `accept` adopts a
+    delegate's proposed revision, and signs it.
+
+Options
+
+    --no-sync   Don't sync the project with the configured seeds
+    --help      Print help
+"#,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Operation {
+    Propose,
+    Review { peer: Option<PeerId> },
+    Accept { peer: PeerId },
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub op: Operation,
+    pub sync: bool,
+}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+        let mut op: Option<String> = None;
+        let mut peer: Option<PeerId> = None;
+        let mut sync = true;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("no-sync") => {
+                    sync = false;
+                }
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                Value(val) if op.is_none() => match val.to_string_lossy().as_ref() {
+                    "propose" | "review" | "accept" => {
+                        op = Some(val.to_string_lossy().into_owned());
+                    }
+                    unknown => anyhow::bail!("unknown operation '{}'", unknown),
+                },
+                Value(val) if peer.is_none() => {
+                    let val = val.to_string_lossy();
+                    let val =
+                        PeerId::from_str(&val).map_err(|_| anyhow!("invalid peer id '{}'", val))?;
+
+                    peer = Some(val);
+                }
+                _ => return Err(anyhow!(arg.unexpected())),
+            }
+        }
+
+        let op = match (op.as_deref(), peer) {
+            (Some("propose"), None) => Operation::Propose,
+            (Some("review"), peer) => Operation::Review { peer },
+            (Some("accept"), Some(peer)) => Operation::Accept { peer },
+            (Some("accept"), None) => anyhow::bail!("a peer id must be specified for 'accept'"),
+            (Some(_), Some(_)) => anyhow::bail!("unexpected peer id"),
+            (_, _) => anyhow::bail!("an operation must be specified, see `rad identity --help`"),
+        };
+
+        Ok((Options { op, sync }, vec![]))
+    }
+}
+
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let (urn, _) = project::cwd()
+        .map_err(|_| anyhow!("this command must be run in the context of a project"))?;
+    let profile = ctx.profile()?;
+    let signer = term::signer(&profile)?;
+    let storage = keys::storage(&profile, signer.clone())?;
+    let rt = tokio::runtime::Runtime::new()?;
+
+    match options.op {
+        Operation::Propose => {
+            propose(&storage, &urn)?;
+        }
+        Operation::Review { peer } => {
+            if options.sync {
+                let seeds = term::sync::seeds(&profile, &urn)?;
+                term::sync::sync(urn.clone(), seeds, sync::Mode::Fetch, &profile, signer, &rt)?;
+            }
+            return review(&storage, &urn, peer);
+        }
+        Operation::Accept { peer } => {
+            accept(&storage, &urn, peer)?;
+        }
+    }
+
+    if options.sync {
+        let seeds = term::sync::seeds(&profile, &urn)?;
+        term::sync::sync(urn, seeds, sync::Mode::Push, &profile, signer, &rt)?;
+    }
+
+    Ok(())
+}
+
+/// Propose a new revision of the project payload, edited interactively.
+pub fn propose(storage: &Storage, urn: &Urn) -> anyhow::Result<()> {
+    if let Some(pending) = project::pending(storage, urn)? {
+        anyhow::bail!(
+            "project has a pending identity update {}; review it with `rad identity review`",
+            pending.project.revision
+        );
+    }
+    let current = verified(storage, urn)?;
+    let payload = serde_json::to_string_pretty(current.payload())?;
+    let edited = term::Editor::new()
+        .edit(&payload)?
+        .ok_or_else(|| anyhow!("operation aborted"))?;
+    let payload: ProjectPayload =
+        serde_json::from_str(&edited).context("invalid project payload")?;
+
+    if serde_json::to_string_pretty(&payload)? == payload_json(&current)? {
+        term::info!("No changes to the project payload");
+        return Ok(());
+    }
+
+    let proposed = identities::project::update(storage, urn, None, payload, None)?;
+    term::diff(&document(&current)?, &document(&proposed)?);
+    term::blank();
+
+    status(storage, urn, &proposed)?;
+
+    Ok(())
+}
+
+/// Show the revisions proposed by the project delegates, or by the given peer.
+pub fn review(storage: &Storage, urn: &Urn, peer: Option<PeerId>) -> anyhow::Result<()> {
+    let current = verified(storage, urn)?;
+    let metadata = project::Metadata::try_from(current.clone())?;
+    let peers = match peer {
+        Some(peer) => vec![peer],
+        None => metadata
+            .delegates
+            .iter()
+            .flat_map(project::Delegate::peers)
+            .collect(),
+    };
+
+    term::info!(
+        "Current revision {}",
+        term::format::secondary(current.revision)
+    );
+
+    let mut proposals = 0;
+    for peer in peers {
+        let proposed = match project::peer_revision(storage, urn, &peer)? {
+            Some(proposed) if proposed.revision != current.revision => proposed,
+            _ => continue,
+        };
+        let signatures = signatures(&metadata, &proposed);
+        proposals += 1;
+
+        term::blank();
+        term::info!(
+            "Revision {} proposed by {} ({} of {} signature(s))",
+            term::format::secondary(proposed.revision),
+            term::format::tertiary(common::fmt::peer(&peer)),
+            signatures,
+            project::quorum(metadata.delegates.len())
+        );
+        term::blank();
+        term::diff(&document(&current)?, &document(&proposed)?);
+
+        if &peer != storage.peer_id() {
+            term::blank();
+            term::tip!("Run `rad identity accept {}` to sign this revision.", peer);
+        }
+    }
+
+    if proposals == 0 {
+        term::blank();
+        term::info!("No proposed revisions");
+    }
+
+    Ok(())
+}
+
+/// Accept a peer's proposed revision, and sign it.
+pub fn accept(storage: &Storage, urn: &Urn, peer: PeerId) -> anyhow::Result<()> {
+    let current = verified(storage, urn)?;
+    let metadata = project::Metadata::try_from(current.clone())?;
+
+    if !metadata
+        .delegates
+        .iter()
+        .any(|d| d.contains(storage.peer_id()))
+    {
+        anyhow::bail!("only project delegates can sign identity updates");
+    }
+    let proposed = project::peer_revision(storage, urn, &peer)?
+        .ok_or_else(|| anyhow!("no revision found for peer {}", peer))?;
+
+    if proposed.revision == current.revision {
+        anyhow::bail!("peer {} has not proposed a new revision", peer);
+    }
+    term::diff(&document(&current)?, &document(&proposed)?);
+    term::blank();
+
+    if !term::confirm(format!("Sign revision {}?", proposed.revision)) {
+        anyhow::bail!("operation aborted");
+    }
+    let merged = project::merge(storage, urn, peer)
+        .with_context(|| format!("couldn't accept revision {}", proposed.revision))?;
+
+    term::success!(
+        "Signed revision {}",
+        term::format::secondary(merged.revision)
+    );
+    status(storage, urn, &merged)?;
+
+    Ok(())
+}
+
+/// Report whether a revision is verified, or how many signatures it's missing.
+fn status(storage: &Storage, urn: &Urn, revision: &Project) -> anyhow::Result<()> {
+    if verified(storage, urn)?.revision == revision.revision {
+        term::success!(
+            "Revision {} is verified",
+            term::format::secondary(revision.revision)
+        );
+    } else if let Some(pending) = project::pending(storage, urn)? {
+        term::warning(&format!(
+            "Revision {} has {} of {} required delegate signature(s)",
+            revision.revision,
+            pending.signed.len(),
+            pending.quorum
+        ));
+        term::tip!("Other delegates can sign it with `rad identity accept`.");
+    }
+    Ok(())
+}
+
+/// Get the latest verified revision of a project.
+fn verified(storage: &Storage, urn: &Urn) -> anyhow::Result<Project> {
+    let project = identities::project::verify(storage, urn)?
+        .ok_or_else(|| anyhow!("project {} not found", urn))?;
+
+    Ok(project.into_inner())
+}
+
+/// Number of delegates of a project that signed a revision.
+fn signatures(metadata: &project::Metadata, revision: &Project) -> usize {
+    metadata
+        .delegates
+        .iter()
+        .filter(|d| {
+            revision
+                .signatures
+                .keys()
+                .any(|k| d.contains(&PeerId::from(*k)))
+        })
+        .count()
+}
+
+fn payload_json(project: &Project) -> anyhow::Result<String> {
+    Ok(serde_json::to_string_pretty(project.payload())?)
+}
+
+/// Render the reviewable parts of a project identity: its payload and delegations.
+fn document(project: &Project) -> anyhow::Result<String> {
+    let delegations = project
+        .delegations()
+        .iter()
+        .map(|d| match d {
+            Either::Left(key) => PeerId::from(*key).default_encoding(),
+            Either::Right(person) => person.urn().to_string(),
+        })
+        .collect::<Vec<_>>();
+    let doc = serde_json::json!({
+        "payload": project.payload(),
+        "delegations": delegations,
+    });
+
+    Ok(serde_json::to_string_pretty(&doc)?)
+}
@@ -2,16 +2,20 @@
 use std::convert::TryFrom;
 use std::env;
 use std::ffi::OsString;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, bail, Context as _};
 
+use librad::git::identities::Project;
+use librad::identities::payload::ProjectPayload;
 use librad::PeerId;
 
 use radicle_common::args::{Args, Error, Help};
+use radicle_common::config::{self, Config, SeedConfig};
 use radicle_common::json;
+use radicle_common::template::{GitSigners, Template};
 use radicle_common::Interactive;
-use radicle_common::{git, keys, profile, project};
+use radicle_common::{checkouts, git, keys, mirror, profile, project};
 use radicle_terminal as term;
 
 pub const HELP: Help = Help {
@@ -22,12 +26,24 @@ pub const HELP: Help = Help {
 Usage
 
     rad init [<path>] [<option>...]
+    rad init --mirror <git-url> [<path>] [<option>...]
+
+    With `--mirror`, the upstream repository is cloned into <path>, initialized as a
+    project, and its default branch and tags are pushed to storage. Run `rad mirror update`
+    in the working copy to bring in upstream changes.
+
+    With `--template`, the project is set up from a template in the profile's
+    `templates` directory, either `<name>.toml` or `<name>/template.toml`. Templates
+    set the default branch, a description pattern, issue labels, a merge policy,
+    seeds and which keys are written to `.gitsigners`. Options take precedence.
 
 Options
 
     --name               Name of the project
     --description        Description of the project
     --default-branch     The default branch of the project
+    --template <name>    Set up the project from a template
+    --mirror <git-url>   Clone and initialize a mirror of an upstream repository
     --set-upstream, -u   Setup the upstream of the default branch
     --no-confirm         Don't ask for confirmation during setup
     --help               Print help
@@ -42,6 +58,8 @@ pub struct Options {
     pub branch: Option<String>,
     pub interactive: Interactive,
     pub set_upstream: bool,
+    pub template: Option<String>,
+    pub mirror: Option<String>,
 }
 
 impl Args for Options {
@@ -56,6 +74,8 @@ impl Args for Options {
         let mut branch = None;
         let mut interactive = Interactive::Yes;
         let mut set_upstream = false;
+        let mut template = None;
+        let mut mirror = None;
 
         while let Some(arg) = parser.next()? {
             match arg {
@@ -91,6 +111,26 @@ impl Args for Options {
 
                     branch = Some(value);
                 }
+                Long("template") if template.is_none() => {
+                    let value = parser
+                        .value()?
+                        .to_str()
+                        .ok_or(anyhow::anyhow!(
+                            "invalid template specified with `--template`"
+                        ))?
+                        .to_owned();
+
+                    template = Some(value);
+                }
+                Long("mirror") if mirror.is_none() => {
+                    let value = parser
+                        .value()?
+                        .to_str()
+                        .ok_or(anyhow::anyhow!("invalid URL specified with `--mirror`"))?
+                        .to_owned();
+
+                    mirror = Some(value);
+                }
                 Long("set-upstream") | Short('u') => {
                     set_upstream = true;
                 }
@@ -115,6 +155,8 @@ impl Args for Options {
                 branch,
                 interactive,
                 set_upstream,
+                template,
+                mirror,
             },
             vec![],
         ))
@@ -131,9 +173,66 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
         ));
         term::blank();
     }
+    if let Some(url) = options.mirror.clone() {
+        return self::mirror(&url, options, &profile);
+    }
     init(options, &profile)
 }
 
+/// Clone an upstream repository, initialize it as a project, and push its default branch
+/// and tags to storage.
+pub fn mirror(url: &str, mut options: Options, profile: &profile::Profile) -> anyhow::Result<()> {
+    let path = match options.path.take() {
+        Some(path) => path,
+        None => url
+            .trim_end_matches('/')
+            .trim_end_matches(".git")
+            .rsplit(['/', ':'])
+            .next()
+            .filter(|name| !name.is_empty())
+            .map(PathBuf::from)
+            .ok_or_else(|| {
+                anyhow!(
+                    "couldn't derive a directory name from '{}', please specify a path",
+                    url
+                )
+            })?,
+    };
+
+    let spinner = term::spinner(format!("Cloning {}...", term::format::highlight(url)));
+    if let Err(err) = mirror::clone(url, &path) {
+        spinner.failed();
+        return Err(err);
+    }
+    spinner.finish();
+
+    options.path = Some(path.clone());
+    init(options, profile)?;
+
+    env::set_current_dir(&path)?;
+    let (urn, _) = project::cwd()?;
+    let storage = profile::read_only(profile)?;
+    let branch = project::get(&storage, &urn)?
+        .ok_or_else(|| anyhow!("couldn't load project {} from local state", urn))?
+        .default_branch
+        .to_string();
+
+    let spinner = term::spinner(format!(
+        "Pushing {} and tags to storage...",
+        term::format::highlight(&branch)
+    ));
+    if let Err(err) = mirror::push(Path::new("."), &branch) {
+        spinner.failed();
+        return Err(err);
+    }
+    spinner.finish();
+
+    term::success!("Mirror of {} initialized", term::format::highlight(url));
+    term::tip!("To bring in upstream changes, run `rad mirror update` in the working copy.");
+
+    Ok(())
+}
+
 pub fn init(options: Options, profile: &profile::Profile) -> anyhow::Result<()> {
     let cwd = std::env::current_dir()?;
     let path = options.path.unwrap_or_else(|| cwd.clone());
@@ -149,6 +248,12 @@ pub fn init(options: Options, profile: &profile::Profile) -> anyhow::Result<()>
         }
     ));
 
+    let template = match &options.template {
+        Some(name) => Template::load(profile, name)?,
+        None => Template::default(),
+    };
+    let seeds = template.seeds()?;
+
     let repo = git::Repository::open(&path)?;
     if let Ok(remote) = git::rad_remote(&repo) {
         bail!(
@@ -172,19 +277,24 @@ pub fn init(options: Options, profile: &profile::Profile) -> anyhow::Result<()>
     });
     let description = options
         .description
+        .or_else(|| template.description(&name))
         .unwrap_or_else(|| term::text_input("Description", None).unwrap());
-    let branch = options.branch.unwrap_or_else(|| {
-        if interactive.yes() {
-            term::text_input("Default branch", Some(head)).unwrap()
-        } else {
-            head
-        }
-    });
+    let branch = options
+        .branch
+        .or(template.default_branch.clone())
+        .unwrap_or_else(|| {
+            if interactive.yes() {
+                term::text_input("Default branch", Some(head)).unwrap()
+            } else {
+                head
+            }
+        });
 
     let mut spinner = term::spinner("Initializing...");
-    let payload = project::payload(name, description, branch.clone());
+    let mut payload = ProjectPayload::new(project::payload(name, description, branch.clone()));
+    template.apply(&mut payload)?;
 
-    match project::create(payload, &storage).and_then(|proj| {
+    match project::create_with_payload(payload, &storage).and_then(|proj| {
         project::init(&proj, &repo, &storage, profile.paths(), signer).map(|_| proj)
     }) {
         Ok(proj) => {
@@ -210,7 +320,24 @@ pub fn init(options: Options, profile: &profile::Profile) -> anyhow::Result<()>
             }
 
             // Setup radicle signing key.
-            self::setup_signing(storage.peer_id(), &repo, interactive)?;
+            match template.gitsigners {
+                GitSigners::Local => {
+                    self::setup_signing(storage.peer_id(), &repo, interactive)?;
+                }
+                GitSigners::Delegates => {
+                    self::setup_signing(storage.peer_id(), &repo, interactive)?;
+                    self::add_delegate_signers(&proj, &path)?;
+                }
+                GitSigners::None => {}
+            }
+
+            if !seeds.is_empty() {
+                self::write_seeds(&path, seeds)?;
+            }
+
+            if let Err(err) = checkouts::register(profile, urn.clone(), &path) {
+                term::warning(&format!("Could not register working copy: {:#}", err));
+            }
 
             term::blank();
             term::info!(
@@ -250,6 +377,56 @@ pub fn init(options: Options, profile: &profile::Profile) -> anyhow::Result<()>
     Ok(())
 }
 
+/// Add the keys of all the project delegates to the `.gitsigners` file.
+fn add_delegate_signers(project: &Project, path: &Path) -> anyhow::Result<()> {
+    // Signing setup may have been declined.
+    if !path.join(".gitsigners").exists() {
+        return Ok(());
+    }
+    let meta = project::Metadata::try_from(project.clone())?;
+    let existing = git::read_gitsigners(path).context("error reading .gitsigners file")?;
+    let mut missing = Vec::new();
+
+    for peer in &meta.remotes {
+        if !existing.contains(&keys::to_ssh_key(peer)?) {
+            missing.push(*peer);
+        }
+    }
+    if !missing.is_empty() {
+        git::add_gitsigners(path, &missing)?;
+        term::success!(
+            "Added {} delegate key(s) to {}",
+            missing.len(),
+            term::format::tertiary(".gitsigners")
+        );
+    }
+    Ok(())
+}
+
+/// Write the project seeds to the working copy configuration.
+fn write_seeds(path: &Path, seeds: Vec<SeedConfig>) -> anyhow::Result<()> {
+    let file = path.join(config::FILE_NAME_LOCAL);
+
+    if file.exists() {
+        term::warning(&format!(
+            "Not writing template seeds: {} already exists",
+            config::FILE_NAME_LOCAL
+        ));
+        return Ok(());
+    }
+    Config {
+        seed: seeds,
+        signer: None,
+    }
+    .write(&file)?;
+
+    term::success!(
+        "Seeds configured in {}",
+        term::format::tertiary(config::FILE_NAME_LOCAL)
+    );
+    Ok(())
+}
+
 /// Setup radicle key as commit signing key in repository.
 pub fn setup_signing(
     peer_id: &PeerId,
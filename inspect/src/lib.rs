@@ -6,7 +6,7 @@ use std::process::{Command, Stdio};
 use std::str::FromStr;
 
 use radicle_common::args::{Args, Error, Help};
-use radicle_common::{git, profile};
+use radicle_common::{git, payload, profile};
 use radicle_terminal as term;
 
 use librad::git::identities::any;
@@ -37,6 +37,7 @@ Options
 
     --id        Return the ID without the URN scheme
     --payload   Inspect the object's payload
+    --ext       Show the payload extensions, eg. the website or Matrix handle
     --refs      Inspect the object's refs on the local device (requires `tree`)
     --history   Show object's history
     --help      Print help
@@ -49,6 +50,7 @@ pub struct Options {
     pub urn: Option<Urn>,
     pub refs: bool,
     pub payload: bool,
+    pub ext: bool,
     pub history: bool,
     pub id: bool,
 }
@@ -62,6 +64,7 @@ impl Args for Options {
         let mut urn: Option<Urn> = None;
         let mut refs = false;
         let mut payload = false;
+        let mut ext = false;
         let mut history = false;
         let mut id = false;
 
@@ -76,6 +79,9 @@ impl Args for Options {
                 Long("payload") => {
                     payload = true;
                 }
+                Long("ext") => {
+                    ext = true;
+                }
                 Long("history") => {
                     history = true;
                 }
@@ -104,6 +110,7 @@ impl Args for Options {
                 id,
                 path,
                 payload,
+                ext,
                 history,
                 refs,
                 urn,
@@ -146,6 +153,25 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
             "{}",
             serde_json::to_string_pretty(&payload)?.to_colored_json_auto()?
         );
+    } else if options.ext {
+        let payload = any::get(&storage, &urn)
+            .map(|o| o.map(|p| p.payload()))
+            .map_err(|_| anyhow::anyhow!("Couldn't load project or person."))?
+            .ok_or(anyhow::anyhow!("No project or person found for this URN"))?;
+        let extensions = payload::extensions(&serde_json::to_value(&payload)?);
+
+        if extensions.is_empty() {
+            term::info!("{}", term::format::italic("No extensions found"));
+        } else {
+            let mut table = term::Table::default();
+            for ext in extensions {
+                table.push([
+                    term::format::bold(ext.label),
+                    term::format::tertiary(ext.value),
+                ]);
+            }
+            table.render();
+        }
     } else if options.history {
         let branch = Reference::try_from(&urn)?;
         match storage.reference(&branch) {
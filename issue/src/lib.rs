@@ -193,7 +193,10 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
                 title: title.unwrap_or("Enter a title".to_owned()),
                 labels: vec![],
             };
-            let yaml = serde_yaml::to_string(&meta)?;
+            let mut yaml = serde_yaml::to_string(&meta)?;
+            if let Some(labels) = project::ext::<_, project::Labels>(&storage, &project)? {
+                yaml.push_str(&format!("# Project labels: {}\n", labels.labels.join(", ")));
+            }
             let doc = format!(
                 "{}---\n\n{}",
                 yaml,
@@ -1,7 +1,19 @@
 use std::ffi::OsString;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+
+use librad::crypto::BoxedSigner;
+use librad::git::storage::ReadOnly;
+use librad::git::Urn;
 
 use radicle_common::args::{Args, Error, Help};
-use radicle_common::{profile, project};
+use radicle_common::cobs::shared::Timestamp;
+use radicle_common::profile::Profile;
+use radicle_common::signer::ToSigner;
+use radicle_common::sync::history::History;
+use radicle_common::{checkouts, cobs, git, keys, profile, project, signer};
 use radicle_terminal as term;
 
 pub const HELP: Help = Help {
@@ -13,22 +25,103 @@ Usage
 
     rad ls [<option>...]
 
+    Lists the projects on this device, with their number of tracked peers, issues
+    and patches, the last time they were synced, and whether the local head is behind
+    one of the project delegates.
+
+Filters
+
+    checked-out     Projects with a working copy on this device
+    behind          Projects whose local head is behind a delegate
+    delegate        Projects you are a delegate of
+    issues          Projects with issues
+    patches         Projects with patches
+
 Options
 
-    --help    Print help
+    --paths             List the working copies of each project
+    --filter <filter>   Only list projects matching the filter (may be given multiple times)
+    --sort <field>      Sort by `name`, `peers`, `issues`, `patches` or `synced` (default: name)
+    --help              Print help
 "#,
 };
 
-pub struct Options {}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    CheckedOut,
+    Behind,
+    Delegate,
+    Issues,
+    Patches,
+}
+
+impl FromStr for Filter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "checked-out" => Ok(Self::CheckedOut),
+            "behind" => Ok(Self::Behind),
+            "delegate" => Ok(Self::Delegate),
+            "issues" => Ok(Self::Issues),
+            "patches" => Ok(Self::Patches),
+            _ => Err(anyhow!("unknown filter '{}'", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sort {
+    Name,
+    Peers,
+    Issues,
+    Patches,
+    Synced,
+}
+
+impl FromStr for Sort {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(Self::Name),
+            "peers" => Ok(Self::Peers),
+            "issues" => Ok(Self::Issues),
+            "patches" => Ok(Self::Patches),
+            "synced" => Ok(Self::Synced),
+            _ => Err(anyhow!("unknown sort field '{}'", s)),
+        }
+    }
+}
+
+pub struct Options {
+    pub filters: Vec<Filter>,
+    pub sort: Sort,
+    pub paths: bool,
+}
 
 impl Args for Options {
     fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
         use lexopt::prelude::*;
 
         let mut parser = lexopt::Parser::from_args(args);
+        let mut filters = Vec::new();
+        let mut sort = Sort::Name;
+        let mut paths = false;
 
-        if let Some(arg) = parser.next()? {
+        while let Some(arg) = parser.next()? {
             match arg {
+                Long("filter") => {
+                    let val = parser.value()?;
+                    filters.push(val.to_string_lossy().parse()?);
+                }
+                Long("sort") => {
+                    let val = parser.value()?;
+                    sort = val.to_string_lossy().parse()?;
+                }
+                Long("paths") => {
+                    paths = true;
+                }
                 Long("help") => {
                     return Err(Error::Help.into());
                 }
@@ -36,29 +129,168 @@ impl Args for Options {
             }
         }
 
-        Ok((Options {}, vec![]))
+        Ok((
+            Options {
+                filters,
+                sort,
+                paths,
+            },
+            vec![],
+        ))
     }
 }
 
-pub fn run(_options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+/// A listed project, and its local state.
+struct Row {
+    urn: Urn,
+    meta: project::Metadata,
+    head: Option<git::Oid>,
+    peers: usize,
+    issues: Option<usize>,
+    patches: Option<usize>,
+    checkouts: Vec<PathBuf>,
+    synced: Option<Timestamp>,
+    behind: bool,
+    delegate: bool,
+}
+
+impl Row {
+    fn matches(&self, filter: Filter) -> bool {
+        match filter {
+            Filter::CheckedOut => !self.checkouts.is_empty(),
+            Filter::Behind => self.behind,
+            Filter::Delegate => self.delegate,
+            Filter::Issues => self.issues.unwrap_or_default() > 0,
+            Filter::Patches => self.patches.unwrap_or_default() > 0,
+        }
+    }
+}
+
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
     let profile = ctx.profile()?;
     let storage = profile::read_only(&profile)?;
     let projs = project::list(&storage)?;
-    let mut table = term::Table::default();
+    let history = History::load(&profile)?;
+    let checkouts = checkouts::load(&profile)?;
+    // Opening the collaborative object store requires a signer. Since listing projects
+    // shouldn't prompt for a passphrase, we only count issues and patches when a signer
+    // is available without one.
+    let writable = unattended_signer(&profile).and_then(|s| keys::storage(&profile, s).ok());
+    let cobs = writable
+        .as_ref()
+        .and_then(|s| cobs::store(&profile, s).ok());
+    let mut rows = Vec::new();
 
     for (urn, meta, head) in projs {
-        let head = head
+        let peers = project::tracked(&meta, &storage)
+            .map(|t| t.len())
+            .unwrap_or_default();
+        let issues = cobs.as_ref().and_then(|c| c.issues().count(&urn).ok());
+        let patches = cobs.as_ref().and_then(|c| c.patches().count(&urn).ok());
+        let checkouts = checkouts.get(&urn).map(|p| p.to_path_buf()).collect();
+        let behind = head.map_or(false, |h| is_behind(&storage, &meta, h));
+        let delegate = meta.delegates.iter().any(|d| d.contains(storage.peer_id()));
+
+        rows.push(Row {
+            synced: history.get(&urn),
+            urn,
+            meta,
+            head,
+            peers,
+            issues,
+            patches,
+            checkouts,
+            behind,
+            delegate,
+        });
+    }
+    rows.retain(|row| options.filters.iter().all(|f| row.matches(*f)));
+
+    match options.sort {
+        Sort::Name => rows.sort_by(|a, b| a.meta.name.cmp(&b.meta.name)),
+        Sort::Peers => rows.sort_by_key(|r| std::cmp::Reverse(r.peers)),
+        Sort::Issues => rows.sort_by_key(|r| std::cmp::Reverse(r.issues)),
+        Sort::Patches => rows.sort_by_key(|r| std::cmp::Reverse(r.patches)),
+        Sort::Synced => rows.sort_by_key(|r| std::cmp::Reverse(r.synced)),
+    }
+
+    if options.paths {
+        let mut table = term::Table::default();
+        for row in rows {
+            for path in &row.checkouts {
+                table.push([
+                    term::format::bold(&row.meta.name),
+                    term::format::tertiary(&row.urn),
+                    term::format::highlight(path.display()),
+                ]);
+            }
+        }
+        table.render();
+
+        return Ok(());
+    }
+
+    let mut table = term::Table::default();
+    for row in rows {
+        let head = row
+            .head
             .map(|h| format!("{:.7}", h.to_string()))
             .unwrap_or_else(String::new);
+        let count = |n: Option<usize>| n.map_or_else(|| String::from("-"), |n| n.to_string());
 
         table.push([
-            term::format::bold(meta.name),
-            term::format::tertiary(urn),
+            term::format::bold(row.meta.name),
+            term::format::tertiary(row.urn),
             term::format::secondary(head),
-            term::format::italic(meta.description),
+            term::format::dim(format!("{} peer(s)", row.peers)),
+            term::format::dim(format!("{} issue(s)", count(row.issues))),
+            term::format::dim(format!("{} patch(es)", count(row.patches))),
+            match row.checkouts.as_slice() {
+                [] => String::new(),
+                [path] => term::format::highlight(path.display()),
+                [path, rest @ ..] => format!(
+                    "{} {}",
+                    term::format::highlight(path.display()),
+                    term::format::dim(format!("(+{})", rest.len()))
+                ),
+            },
+            row.synced
+                .map(|t| term::format::dim(format!("synced {}", t)))
+                .unwrap_or_else(|| term::format::dim("never synced")),
+            if row.behind {
+                term::format::badge_secondary("behind")
+            } else {
+                String::new()
+            },
+            term::format::italic(row.meta.description),
         ]);
     }
     table.render();
 
     Ok(())
 }
+
+/// Get a signer without prompting the user, ie. from ssh-agent or the profile configuration.
+fn unattended_signer(profile: &Profile) -> Option<BoxedSigner> {
+    keys::ssh_auth_sock()
+        .ok()
+        .and_then(|sock| sock.to_signer(profile).ok())
+        .or_else(|| signer::configured(profile).ok().flatten())
+}
+
+/// Whether the local head is behind the head of one of the project delegates.
+fn is_behind<S: AsRef<ReadOnly>>(storage: &S, meta: &project::Metadata, head: git::Oid) -> bool {
+    let repo = match git::Repository::open_bare(storage.as_ref().path()) {
+        Ok(repo) => repo,
+        Err(_) => return false,
+    };
+    let local = storage.as_ref().peer_id();
+
+    meta.remotes
+        .iter()
+        .filter(|peer| *peer != local && meta.delegates.iter().any(|d| d.contains(peer)))
+        .filter_map(|peer| {
+            project::get_remote_head(storage, &meta.urn, peer, &meta.default_branch).ok()?
+        })
+        .any(|theirs| theirs != head && repo.graph_descendant_of(theirs, head).unwrap_or(false))
+}
@@ -1,5 +1,6 @@
 use std::ffi::OsString;
 use std::fmt::Write;
+use std::path::Path;
 use std::str::FromStr;
 
 use anyhow::{anyhow, Context};
@@ -8,6 +9,7 @@ use radicle_common as common;
 use radicle_common::args::{Args, Error, Help};
 use radicle_common::cobs::patch::{Patch, PatchId};
 use radicle_common::patch::MergeStyle;
+use radicle_common::project::MergePolicy;
 use radicle_common::{cobs, git, keys, project};
 use radicle_terminal as term;
 
@@ -29,6 +31,7 @@ Options
 
     -i, --interactive         Ask for confirmations
     -r, --revision <number>   Revision number to merge, defaults to the latest
+        --verify              Verify the signatures of the patch commits before merging
         --help                Print help
 "#,
 };
@@ -48,6 +51,7 @@ pub struct Options {
     pub id: cobs::Identifier,
     pub interactive: bool,
     pub revision: Option<RevisionIx>,
+    pub verify: bool,
 }
 
 impl Args for Options {
@@ -58,6 +62,7 @@ impl Args for Options {
         let mut id: Option<cobs::Identifier> = None;
         let mut revision: Option<RevisionIx> = None;
         let mut interactive = false;
+        let mut verify = false;
 
         while let Some(arg) = parser.next()? {
             match arg {
@@ -67,6 +72,9 @@ impl Args for Options {
                 Long("interactive") | Short('i') => {
                     interactive = true;
                 }
+                Long("verify") => {
+                    verify = true;
+                }
                 Long("revision") | Short('r') => {
                     let value = parser.value()?;
                     let id =
@@ -94,6 +102,7 @@ impl Args for Options {
                 id: id.ok_or_else(|| anyhow!("a patch id to merge must be provided"))?,
                 interactive,
                 revision,
+                verify,
             },
             vec![],
         ))
@@ -109,7 +118,7 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
     let profile = ctx.profile()?;
     let signer = term::signer(&profile)?;
     let storage = keys::storage(&profile, signer)?;
-    let _project = project::get(&storage, &urn)?
+    let project = project::get(&storage, &urn)?
         .ok_or_else(|| anyhow!("couldn't load project {} from local state", urn))?;
     let cobs = cobs::store(&profile, &storage)?;
     let patches = cobs.patches();
@@ -189,6 +198,21 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
         );
     };
 
+    // Apply the project's merge policy, if any.
+    let policy = project::ext::<_, MergePolicy>(&storage, &urn)?.unwrap_or_default();
+    let merge_style = match (policy.style, merge_style) {
+        (Some(MergeStyle::FastForward), MergeStyle::Commit) => {
+            return Err(common::Error::WithHint {
+                err: anyhow!("patch can't be fast-forwarded onto {}", branch),
+                hint: "The project merge policy is fast-forward only; the patch must be rebased before it can be merged.",
+            }
+            .into());
+        }
+        (Some(MergeStyle::Commit), _) => MergeStyle::Commit,
+        (_, style) => style,
+    };
+    let verify = options.verify || policy.verify;
+
     let merge_style_pretty = match merge_style {
         MergeStyle::FastForward => term::format::style(merge_style.to_string())
             .dim()
@@ -212,6 +236,13 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
         merge_style_pretty
     );
 
+    if verify {
+        let range = format!("{}..{}", head_oid, revision.oid);
+
+        term::blank();
+        rad_verify::verify(Path::new("."), &project, &range, false)?;
+    }
+
     if options.interactive && !term::confirm("Confirm?") {
         anyhow::bail!("merge aborted by user");
     }
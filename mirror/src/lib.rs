@@ -0,0 +1,248 @@
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::anyhow;
+
+use librad::crypto::BoxedSigner;
+
+use radicle_common::args::{Args, Error, Help};
+use radicle_common::{checkouts, mirror, profile, project, tokio};
+use radicle_terminal as term;
+
+pub const HELP: Help = Help {
+    name: "mirror",
+    description: env!("CARGO_PKG_DESCRIPTION"),
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad mirror [ls]
+    rad mirror update [--all] [--interval <seconds>] [--no-sync]
+
+    Mirrors are working copies of projects hosted on other git servers, created with
+    `rad init --mirror <git-url>`.
+
+    `update` fetches the branches and tags of the upstream repository, fast-forwards the
+    default branch, and pushes it to storage along with the tags. It then syncs the
+    project with the configured seeds. Without `--all`, the mirror in the current
+    directory is updated.
+
+Options
+
+    --all                   Update all the mirrors on this device
+    --interval <seconds>    Keep updating, waiting the given number of seconds between updates
+    --no-sync               Don't sync the mirrors with the configured seeds
+    --help                  Print help
+"#,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Operation {
+    List,
+    Update {
+        all: bool,
+        interval: Option<Duration>,
+    },
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub op: Operation,
+    pub sync: bool,
+}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+        let mut op: Option<String> = None;
+        let mut all = false;
+        let mut interval: Option<Duration> = None;
+        let mut sync = true;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("all") => {
+                    all = true;
+                }
+                Long("interval") => {
+                    let val = parser.value()?;
+                    let secs = val
+                        .to_str()
+                        .and_then(|v| v.parse::<u64>().ok())
+                        .filter(|secs| *secs > 0)
+                        .ok_or_else(|| anyhow!("invalid interval '{}'", val.to_string_lossy()))?;
+
+                    interval = Some(Duration::from_secs(secs));
+                }
+                Long("no-sync") => {
+                    sync = false;
+                }
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                Value(val) if op.is_none() => match val.to_string_lossy().as_ref() {
+                    "ls" => op = Some(String::from("ls")),
+                    "update" => op = Some(String::from("update")),
+
+                    unknown => anyhow::bail!("unknown operation '{}'", unknown),
+                },
+                _ => return Err(anyhow!(arg.unexpected())),
+            }
+        }
+
+        let op = match op.as_deref() {
+            None | Some("ls") => {
+                if all || interval.is_some() {
+                    anyhow::bail!("`--all` and `--interval` are only valid with `update`");
+                }
+                Operation::List
+            }
+            Some(_) => Operation::Update { all, interval },
+        };
+
+        Ok((Options { op, sync }, vec![]))
+    }
+}
+
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let profile = ctx.profile()?;
+
+    match options.op {
+        Operation::List => list(&profile),
+        Operation::Update { all, interval } => {
+            let signer = term::signer(&profile)?;
+            let rt = tokio::runtime::Runtime::new()?;
+            let paths = if all {
+                mirrors(&profile)?
+                    .into_iter()
+                    .map(|(path, _)| path)
+                    .collect()
+            } else {
+                if mirror::upstream(Path::new(".")).is_none() {
+                    anyhow::bail!(
+                        "this working copy is not a mirror; create one with `rad init --mirror <git-url>`"
+                    );
+                }
+                vec![env::current_dir()?]
+            };
+            if paths.is_empty() {
+                term::info!("No mirrors found; create one with `rad init --mirror <git-url>`");
+                return Ok(());
+            }
+
+            loop {
+                for path in &paths {
+                    if all {
+                        term::headline(&format!(
+                            "Updating mirror in {}",
+                            term::format::highlight(path.display())
+                        ));
+                    }
+                    let result = env::set_current_dir(path)
+                        .map_err(anyhow::Error::from)
+                        .and_then(|_| update(&profile, options.sync, signer.clone(), &rt));
+
+                    match result {
+                        Ok(()) => {}
+                        Err(err) if all || interval.is_some() => {
+                            term::error(format!(
+                                "Failed to update mirror in {}: {:#}",
+                                path.display(),
+                                err
+                            ));
+                        }
+                        Err(err) => return Err(err),
+                    }
+                }
+
+                match interval {
+                    Some(interval) => thread::sleep(interval),
+                    None => return Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// Update the mirror in the current directory.
+pub fn update(
+    profile: &profile::Profile,
+    sync: bool,
+    signer: BoxedSigner,
+    rt: &tokio::runtime::Runtime,
+) -> anyhow::Result<()> {
+    let remote = mirror::upstream(Path::new("."))
+        .ok_or_else(|| anyhow!("working copy is no longer a mirror"))?;
+    let (urn, _) = project::cwd()
+        .map_err(|_| anyhow!("this command must be run in the context of a project"))?;
+    let storage = profile::read_only(profile)?;
+    let branch = project::get(&storage, &urn)?
+        .ok_or_else(|| anyhow!("couldn't load project {} from local state", urn))?
+        .default_branch
+        .to_string();
+
+    let spinner = term::spinner(format!(
+        "Fetching {} from {}...",
+        term::format::highlight(&branch),
+        term::format::tertiary(&remote)
+    ));
+    if let Err(err) = mirror::fetch(Path::new("."), &remote, &branch) {
+        spinner.failed();
+        return Err(err);
+    }
+    spinner.finish();
+
+    let spinner = term::spinner(format!(
+        "Pushing {} and tags to storage...",
+        term::format::highlight(&branch)
+    ));
+    if let Err(err) = mirror::push(Path::new("."), &branch) {
+        spinner.failed();
+        return Err(err);
+    }
+    spinner.finish();
+
+    if sync {
+        let seeds = term::sync::seeds(profile, &urn)?;
+        term::sync::push_or_queue(urn, seeds, profile, signer, rt)?;
+    }
+    Ok(())
+}
+
+fn list(profile: &profile::Profile) -> anyhow::Result<()> {
+    let mirrors = mirrors(profile)?;
+
+    if mirrors.is_empty() {
+        term::info!("No mirrors found; create one with `rad init --mirror <git-url>`");
+        return Ok(());
+    }
+
+    let mut table = term::Table::default();
+    for (path, remote) in mirrors {
+        let url = mirror::url(&path, &remote).unwrap_or_default();
+
+        table.push([
+            term::format::highlight(path.display()),
+            term::format::tertiary(remote),
+            term::format::dim(url),
+        ]);
+    }
+    table.render();
+
+    Ok(())
+}
+
+/// Get the registered working copies that are mirrors, and their upstream remote.
+fn mirrors(profile: &profile::Profile) -> anyhow::Result<Vec<(PathBuf, String)>> {
+    let checkouts = checkouts::load(profile)?;
+
+    Ok(checkouts
+        .iter()
+        .filter_map(|e| mirror::upstream(&e.path).map(|remote| (e.path.clone(), remote)))
+        .collect())
+}
@@ -3,13 +3,15 @@
 #![allow(clippy::for_kv_map)]
 use std::convert::TryFrom;
 use std::ffi::OsString;
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-use anyhow::anyhow;
+use anyhow::{anyhow, Context as _};
 
 use common::cobs::patch::Verdict;
 use librad::git::identities::local::LocalIdentity;
+use librad::git::local::url::LocalUrl;
 use librad::git::storage::ReadOnlyStorage;
 use librad::git::Storage;
 use librad::git_ext::{Oid, RefLike};
@@ -31,6 +33,8 @@ pub const HELP: Help = Help {
 Usage
 
     rad patch [<option>...]
+    rad patch import <mbox> [--branch <name>] [<option>...]
+    rad patch export <id> [--revision <n>] [--output <file>]
 
 Create options
 
@@ -39,6 +43,16 @@ Create options
         --[no-]push            Push patch head to storage (default: true)
     -m, --message [<string>]   Provide a comment message to the patch or revision (default: prompt)
         --no-message           Leave the patch or revision comment message blank
+        --parent               Propose the patch to the delegates of the parent project, for forks
+
+Import options
+
+    -b, --branch <name>        Branch to apply the patches on (default: derived from the title)
+
+Export options
+
+    -r, --revision <n>         Revision to export (default: latest)
+    -o, --output <file>        Write the mailbox to a file (default: stdout)
 
 Options
 
@@ -79,14 +93,42 @@ impl Default for Update {
     }
 }
 
+#[derive(Debug)]
+pub enum Operation {
+    Create,
+    List,
+    Import {
+        mbox: PathBuf,
+        branch: Option<String>,
+    },
+    Export {
+        id: cobs::Identifier,
+        revision: Option<usize>,
+        output: Option<PathBuf>,
+    },
+}
+
+impl Default for Operation {
+    fn default() -> Self {
+        Self::Create
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OperationName {
+    Import,
+    Export,
+}
+
 #[derive(Default, Debug)]
 pub struct Options {
-    pub list: bool,
+    pub op: Operation,
     pub verbose: bool,
     pub sync: bool,
     pub push: bool,
     pub update: Update,
     pub message: Comment,
+    pub parent: bool,
 }
 
 impl Args for Options {
@@ -100,6 +142,13 @@ impl Args for Options {
         let mut message = Comment::default();
         let mut push = true;
         let mut update = Update::default();
+        let mut op: Option<OperationName> = None;
+        let mut mbox: Option<PathBuf> = None;
+        let mut id: Option<cobs::Identifier> = None;
+        let mut branch: Option<String> = None;
+        let mut revision: Option<usize> = None;
+        let mut output: Option<PathBuf> = None;
+        let mut parent = false;
 
         while let Some(arg) = parser.next()? {
             match arg {
@@ -141,21 +190,73 @@ impl Args for Options {
                 Long("no-push") => {
                     push = false;
                 }
+                Long("parent") => {
+                    parent = true;
+                }
+                Long("branch") | Short('b') if op == Some(OperationName::Import) => {
+                    branch = Some(parser.value()?.to_string_lossy().into());
+                }
+                Long("revision") | Short('r') if op == Some(OperationName::Export) => {
+                    let val = parser.value()?;
+                    let val = val
+                        .to_str()
+                        .and_then(|v| v.parse().ok())
+                        .ok_or_else(|| anyhow!("invalid revision '{}'", val.to_string_lossy()))?;
+
+                    revision = Some(val);
+                }
+                Long("output") | Short('o') if op == Some(OperationName::Export) => {
+                    output = Some(parser.value()?.into());
+                }
                 Long("help") => {
                     return Err(Error::Help.into());
                 }
+                Value(val) if op.is_none() => match val.to_string_lossy().as_ref() {
+                    "import" => op = Some(OperationName::Import),
+                    "export" => op = Some(OperationName::Export),
+
+                    unknown => anyhow::bail!("unknown operation '{}'", unknown),
+                },
+                Value(val) if op == Some(OperationName::Import) && mbox.is_none() => {
+                    mbox = Some(val.into());
+                }
+                Value(val) if op == Some(OperationName::Export) && id.is_none() => {
+                    let val = val
+                        .to_str()
+                        .ok_or_else(|| anyhow!("patch id specified is not UTF-8"))?;
+
+                    id = Some(
+                        cobs::Identifier::from_str(val)
+                            .map_err(|_| anyhow!("invalid patch id '{}'", val))?,
+                    );
+                }
                 _ => return Err(anyhow::anyhow!(arg.unexpected())),
             }
         }
 
+        let op = match op {
+            Some(OperationName::Import) => Operation::Import {
+                mbox: mbox.ok_or_else(|| anyhow!("a mailbox file must be specified"))?,
+                branch,
+            },
+            Some(OperationName::Export) => Operation::Export {
+                id: id.ok_or_else(|| anyhow!("a patch id must be specified"))?,
+                revision,
+                output,
+            },
+            None if list => Operation::List,
+            None => Operation::Create,
+        };
+
         Ok((
             Options {
-                list,
+                op,
                 sync,
                 message,
                 push,
                 update,
                 verbose,
+                parent,
             },
             vec![],
         ))
@@ -169,13 +270,47 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
     let profile = ctx.profile()?;
     let signer = term::signer(&profile)?;
     let storage = keys::storage(&profile, signer)?;
-    let project = project::get(&storage, &urn)?
-        .ok_or_else(|| anyhow!("couldn't load project {} from local state", urn))?;
-
-    if options.list {
-        list(&storage, Some(repo), &profile, &project, options)?;
+    let project = if options.parent {
+        let parent = project::parent(&storage, &urn)?
+            .ok_or_else(|| anyhow!("project {} is not a fork", urn))?;
+
+        project::get(&storage, &parent)?.ok_or_else(|| {
+            anyhow!(
+                "parent project {} not found in local storage; sync it first with `rad sync {}`",
+                parent,
+                parent
+            )
+        })?
     } else {
-        create(&storage, &profile, &project, &repo, options)?;
+        project::get(&storage, &urn)?
+            .ok_or_else(|| anyhow!("couldn't load project {} from local state", urn))?
+    };
+
+    match &options.op {
+        Operation::Create => {
+            create(&storage, &profile, &project, &repo, options)?;
+        }
+        Operation::List => {
+            list(&storage, Some(repo), &profile, &project, options)?;
+        }
+        Operation::Import { mbox, branch } => {
+            let (mbox, branch) = (mbox.clone(), branch.clone());
+            import(&storage, &profile, &project, &repo, &mbox, branch, options)?;
+        }
+        Operation::Export {
+            id,
+            revision,
+            output,
+        } => {
+            export(
+                &storage,
+                &profile,
+                &project,
+                id,
+                *revision,
+                output.as_deref(),
+            )?;
+        }
     }
 
     Ok(())
@@ -193,7 +328,7 @@ fn list(
 
         term::sync::sync(
             project.urn.clone(),
-            sync::seeds(profile)?,
+            term::sync::seeds(profile, &project.urn)?,
             sync::Mode::Fetch,
             profile,
             term::signer(profile)?,
@@ -295,10 +430,9 @@ fn update(
     if options.sync {
         let rt = tokio::runtime::Runtime::new()?;
 
-        term::sync::sync(
+        term::sync::push_or_queue(
             project.urn.clone(),
-            sync::seeds(profile)?,
-            sync::Mode::Push,
+            term::sync::seeds(profile, &project.urn)?,
             profile,
             term::signer(profile)?,
             &rt,
@@ -358,10 +492,39 @@ fn create(
     }
     spinner.finish();
 
+    // When proposing to the parent of a fork, the patch head must be published under the
+    // parent project, for its delegates to fetch it.
+    if options.parent {
+        let spinner = term::spinner(format!(
+            "Pushing {} to {}...",
+            term::format::highlight(&head_branch),
+            term::format::highlight(&project.name)
+        ));
+        let url = LocalUrl::from(project.urn.clone()).to_string();
+        // Don't force-push, so that a branch of the same name isn't overwritten in the
+        // parent project, unless it can be fast-forwarded.
+        let refspec = format!("HEAD:refs/heads/{}", head_branch);
+        let output = match git::git(Path::new("."), ["push", url.as_str(), refspec.as_str()]) {
+            Ok(output) => output,
+            Err(err) => {
+                spinner.failed();
+                return Err(err);
+            }
+        };
+
+        spinner.finish();
+        if options.verbose {
+            term::blob(output);
+        }
+    }
+
     // Determine the merge target for this patch. This can ben any tracked remote's "default"
     // branch, as well as your own (eg. `rad/master`).
     let mut spinner = term::spinner("Analyzing remotes...");
-    let targets = patch::find_merge_targets(&head_oid, storage, project)?;
+    let mut targets = patch::find_merge_targets(&head_oid, storage, project)?;
+    if options.parent {
+        targets.retain_delegates();
+    }
 
     // eg. `refs/namespaces/<proj>/refs/remotes/<peer>/heads/master`
     let (target_peer, target_oid) = match targets.not_merged.as_slice() {
@@ -522,10 +685,9 @@ fn create(
     if options.sync {
         let rt = tokio::runtime::Runtime::new()?;
 
-        term::sync::sync(
+        term::sync::push_or_queue(
             project.urn.clone(),
-            sync::seeds(profile)?,
-            sync::Mode::Push,
+            term::sync::seeds(profile, &project.urn)?,
             profile,
             term::signer(profile)?,
             &rt,
@@ -535,6 +697,204 @@ fn create(
     Ok(())
 }
 
+fn import(
+    storage: &Storage,
+    profile: &Profile,
+    project: &project::Metadata,
+    repo: &git::Repository,
+    path: &Path,
+    branch: Option<String>,
+    options: Options,
+) -> anyhow::Result<()> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read mailbox {:?}", path))?;
+    let mbox = patch::mbox::Mbox::parse(&contents);
+    let count = mbox.patches().count();
+
+    if count == 0 {
+        anyhow::bail!("mailbox {:?} doesn't contain any patches", path);
+    }
+    let (title, description) = mbox
+        .title()
+        .ok_or_else(|| anyhow!("mailbox {:?} doesn't contain any patches", path))?;
+
+    term::headline(&format!(
+        "🌱 Importing {} patch(es) into {}",
+        count,
+        term::format::highlight(&project.name)
+    ));
+
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| anyhow!("cannot import patches into a bare repository"))?;
+    let branch = branch.unwrap_or_else(|| branch_name(&title));
+
+    if RefLike::try_from(branch.as_str()).is_err() {
+        anyhow::bail!("invalid branch name '{}'", branch);
+    }
+    if repo.find_branch(&branch, git::BranchType::Local).is_ok() {
+        return Err(Error::WithHint {
+            err: anyhow!("branch '{}' already exists", branch),
+            hint: "hint: specify another branch with `--branch <name>`",
+        }
+        .into());
+    }
+
+    // The patches are applied on top of `HEAD`, which becomes the patch base.
+    let base_oid = repo.head()?.peel_to_commit()?.id();
+
+    let spinner = term::spinner(format!(
+        "Applying patches on {}...",
+        term::format::highlight(&branch)
+    ));
+    if let Err(err) = patch::mbox::apply_on_branch(workdir, &branch, &mbox) {
+        spinner.failed();
+        term::blank();
+
+        return Err(Error::WithHint {
+            err,
+            hint: "hint: the patches may be based on another commit, check it out and try again",
+        }
+        .into());
+    }
+    spinner.finish();
+
+    let head_oid = repo.head()?.peel_to_commit()?.id();
+    let commits = patch::patch_commits(repo, &base_oid, &head_oid)?;
+
+    term::blank();
+    term::patch::list_commits(&commits)?;
+    term::blank();
+
+    if storage.find_object(Oid::from(head_oid))?.is_none() {
+        if !options.push {
+            return Err(Error::WithHint {
+                err: anyhow!("Imported branch head was not found in storage"),
+                hint: "hint: run `git push rad` and try again",
+            }
+            .into());
+        }
+        let spinner = term::spinner("Pushing branch to storage...");
+        let output = git::git(workdir, ["push", "rad", &branch])?;
+
+        spinner.finish();
+        if options.verbose {
+            term::blob(output);
+        }
+    }
+
+    let title_pretty = &term::format::dim(format!("╭─ {} ───────", title));
+
+    term::blank();
+    term::print(title_pretty);
+    term::blank();
+
+    if description.is_empty() {
+        term::print(term::format::italic("No description provided."));
+    } else {
+        term::markdown(&description);
+    }
+
+    term::blank();
+    term::print(&term::format::dim(format!(
+        "╰{}",
+        "─".repeat(term::text_width(title_pretty) - 1)
+    )));
+    term::blank();
+
+    if !term::confirm("Create patch?") {
+        anyhow::bail!("patch import aborted by user");
+    }
+
+    let cobs = cobs::store(profile, storage)?;
+    let patches = cobs.patches();
+    let id = patches.create(
+        &project.urn,
+        &title,
+        &description,
+        MergeTarget::default(),
+        base_oid,
+        head_oid,
+        &[],
+    )?;
+
+    term::blank();
+    term::success!("Patch {} created 🌱", term::format::highlight(id));
+
+    if options.sync {
+        let rt = tokio::runtime::Runtime::new()?;
+
+        term::sync::push_or_queue(
+            project.urn.clone(),
+            term::sync::seeds(profile, &project.urn)?,
+            profile,
+            term::signer(profile)?,
+            &rt,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn export(
+    storage: &Storage,
+    profile: &Profile,
+    project: &project::Metadata,
+    id: &cobs::Identifier,
+    revision: Option<usize>,
+    output: Option<&Path>,
+) -> anyhow::Result<()> {
+    let cobs = cobs::store(profile, storage)?;
+    let patches = cobs.patches();
+    let (patch_id, patch) = patches
+        .resolve(&project.urn, id)?
+        .ok_or_else(|| anyhow!("Patch '{}' not found", id))?;
+    let version = revision.unwrap_or_else(|| patch.version());
+    let revision = patch.revisions.get(version).ok_or_else(|| {
+        anyhow!(
+            "patch {} has no revision R{}",
+            common::fmt::cob(&patch_id),
+            version
+        )
+    })?;
+
+    // Revisions are stored in the monorepo, so we format the patches from there.
+    let mbox = patch::mbox::format(
+        profile.paths().git_dir(),
+        &revision.base,
+        &revision.oid,
+        &patch.title,
+        revision.description(),
+    )?;
+
+    if let Some(path) = output {
+        fs::write(path, mbox)?;
+
+        term::success!(
+            "Patch {} {} exported to {}",
+            term::format::highlight(common::fmt::cob(&patch_id)),
+            term::format::dim(format!("R{}", version)),
+            term::format::tertiary(path.display())
+        );
+    } else {
+        print!("{}", mbox);
+    }
+
+    Ok(())
+}
+
+/// Derive a branch name from a patch title, eg. `patch/fix-the-thing`.
+fn branch_name(title: &str) -> String {
+    let slug = title
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    format!("{}{}", patch::BRANCH_PREFIX, slug)
+}
+
 /// Create a human friendly message about git's sync status.
 fn pretty_sync_status(
     repo: &git::Repository,
@@ -640,6 +1000,9 @@ pub fn print(
         if peer.id == *storage.peer_id() {
             badges.push(term::format::secondary("(you)"));
         }
+        for ext in peer.extensions() {
+            badges.push(term::format::dim(ext));
+        }
 
         timeline.push((
             merge.timestamp,
@@ -667,6 +1030,9 @@ pub fn print(
         if peer.id == *storage.peer_id() {
             badges.push(term::format::secondary("(you)"));
         }
+        for ext in peer.extensions() {
+            badges.push(term::format::dim(ext));
+        }
 
         timeline.push((
             review.timestamp,
@@ -1,6 +1,12 @@
 use std::ffi::OsString;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+
+use librad::git::Urn;
 
 use radicle_common::args::{Args, Error, Help};
+use radicle_common::checkouts;
 use radicle_terminal as term;
 
 pub const HELP: Help = Help {
@@ -10,36 +16,60 @@ pub const HELP: Help = Help {
     usage: r#"
 Usage
 
-    rad path [--help]
+    rad path [<urn>] [--help]
+
+    Without arguments, shows the path of the profile storage. With a project URN,
+    shows the paths of the project's working copies on this device.
 "#,
 };
 
 #[derive(Default, Eq, PartialEq)]
-pub struct Options {}
+pub struct Options {
+    pub urn: Option<Urn>,
+}
 
 impl Args for Options {
     fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
         use lexopt::prelude::*;
 
         let mut parser = lexopt::Parser::from_args(args);
+        let mut urn: Option<Urn> = None;
 
-        if let Some(arg) = parser.next()? {
+        while let Some(arg) = parser.next()? {
             match arg {
                 Long("help") => {
                     return Err(Error::Help.into());
                 }
+                Value(val) if urn.is_none() => {
+                    let val = val.to_string_lossy();
+                    let val = Urn::from_str(&val).map_err(|_| anyhow!("invalid URN '{}'", val))?;
+
+                    urn = Some(val);
+                }
                 _ => return Err(anyhow::anyhow!(arg.unexpected())),
             }
         }
 
-        Ok((Options {}, vec![]))
+        Ok((Options { urn }, vec![]))
     }
 }
 
-pub fn run(_options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
     let profile = ctx.profile()?;
 
-    term::info!("{}", profile.paths().git_dir().display());
+    if let Some(urn) = options.urn {
+        let checkouts = checkouts::load(&profile)?;
+        let mut paths = checkouts.get(&urn).peekable();
+
+        if paths.peek().is_none() {
+            anyhow::bail!("no working copy of {} found on this device", urn);
+        }
+        for path in paths {
+            term::info!("{}", path.display());
+        }
+    } else {
+        term::info!("{}", profile.paths().git_dir().display());
+    }
 
     Ok(())
 }
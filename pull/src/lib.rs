@@ -1,11 +1,12 @@
 #![allow(clippy::or_fun_call)]
 use std::ffi::OsString;
+use std::path::Path;
 
 use anyhow::anyhow;
 
 use radicle_common::{
     args::{Args, Error, Help},
-    git, identity, project,
+    git, identity, profile, project,
     seed::{self},
     sync,
 };
@@ -22,9 +23,13 @@ Usage
 
     Pulls changes into the current branch after optionally syncing.
 
+    With `--verify`, the signatures of the incoming commits are verified before
+    they are merged; see `rad verify`.
+
 Options
 
     --seed <addr>   Seed to sync from (may be specified multiple times)
+    --verify        Verify the signatures of incoming commits
     --help          Print help
 
 "#,
@@ -33,6 +38,7 @@ Options
 #[derive(Debug)]
 pub struct Options {
     seeds: Vec<sync::Seed<String>>,
+    verify: bool,
 }
 
 impl Args for Options {
@@ -41,13 +47,17 @@ impl Args for Options {
 
         let mut parser = lexopt::Parser::from_args(args);
         let mut seeds = Vec::new();
+        let mut verify = false;
 
-        if let Some(arg) = parser.next()? {
+        while let Some(arg) = parser.next()? {
             match arg {
                 Long("seed") => {
                     let seed = seed::parse_value(&mut parser)?;
                     seeds.push(seed);
                 }
+                Long("verify") => {
+                    verify = true;
+                }
                 Long("help") => {
                     return Err(Error::Help.into());
                 }
@@ -55,7 +65,7 @@ impl Args for Options {
             }
         }
 
-        Ok((Options { seeds }, vec![]))
+        Ok((Options { seeds, verify }, vec![]))
     }
 }
 
@@ -69,9 +79,11 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
         .and_then(|head| head.shorthand().map(|h| h.to_owned()))
         .ok_or(anyhow!("you must be on a branch to pull"))?;
 
+    let profile = ctx.profile()?;
+
     rad_sync::run(
         rad_sync::Options {
-            origin: Some(identity::Origin::from_urn(urn)),
+            origin: Some(identity::Origin::from_urn(urn.clone())),
             seeds: options.seeds,
             mode: sync::Mode::Fetch,
             ..rad_sync::Options::default()
@@ -79,10 +91,34 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
         ctx,
     )?;
 
-    term::blank();
-    term::subcommand("git pull");
+    let path = Path::new(".");
+
+    let output = if options.verify {
+        let storage = profile::read_only(&profile)?;
+        let project = project::get(&storage, &urn)?
+            .ok_or_else(|| anyhow!("couldn't load project {} from local state", urn))?;
+
+        term::blank();
+        term::subcommand("git fetch");
+        git::git(path, ["fetch"])?;
+
+        // Resolve the upstream once, so that what is merged is exactly what was verified.
+        let upstream = git::git(path, ["rev-parse", "--verify", "@{upstream}"])?;
+        let upstream = upstream.trim();
+
+        term::blank();
+        rad_verify::verify(path, &project, &format!("HEAD..{}", upstream), false)?;
+
+        term::blank();
+        term::subcommand(format!("git merge --ff-only {}", upstream));
+
+        git::git(path, ["merge", "--ff-only", upstream])?
+    } else {
+        term::blank();
+        term::subcommand("git pull");
 
-    let output = git::pull(std::path::Path::new("."), true)?;
+        git::pull(path, true)?
+    };
 
     term::info!("{}", output);
 
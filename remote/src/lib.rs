@@ -22,11 +22,16 @@ Usage
 
     rad remote add <name> <peer-id> [-f | --fetch]
     rad remote rm <name | peer-id>
+    rad remote rename <name | peer-id> <new-name>
     rad remote ls
 
+    Renaming a remote also renames its remote-tracking refs and its local tracking
+    branches, eg. `peers/alice/master` becomes `peers/<new-name>/master`.
+
 Examples
 
     rad remote add cloudhead hyn9diwfnytahjq8u3iw63h9jte1ydcatxax3saymwdxqu1zo645pe
+    rad remote rename peers/alice-2/rad bob
 
 Options
 
@@ -45,6 +50,10 @@ pub enum Operation {
     Remove {
         remote: String,
     },
+    Rename {
+        remote: String,
+        name: String,
+    },
     List,
 }
 
@@ -61,6 +70,7 @@ impl Args for Options {
         let mut parser = lexopt::Parser::from_args(args);
         let mut peer: Option<PeerId> = None;
         let mut remote: Option<String> = None;
+        let mut name: Option<String> = None;
         let mut op: Option<String> = None;
         let mut fetch = false;
 
@@ -78,6 +88,9 @@ impl Args for Options {
                 Value(val) if remote.is_none() => {
                     remote = Some(val.to_string_lossy().to_string());
                 }
+                Value(val) if op.as_deref() == Some("rename") && name.is_none() => {
+                    name = Some(val.to_string_lossy().to_string());
+                }
                 Value(val) if peer.is_none() => {
                     peer = Some(val.parse().context("invalid value specified for peer")?);
                 }
@@ -97,6 +110,14 @@ impl Args for Options {
                 "rm" => Operation::Remove {
                     remote: remote.ok_or_else(|| anyhow!("a remote name must be specified"))?,
                 },
+                "rename" => Operation::Rename {
+                    remote: remote.ok_or_else(|| anyhow!("a remote name must be specified"))?,
+                    name: match name {
+                        Some(name) if !name.is_empty() && !name.contains('/') => name,
+                        Some(name) => anyhow::bail!("invalid remote name '{}'", name),
+                        None => anyhow::bail!("a new remote name must be specified"),
+                    },
+                },
                 "ls" => Operation::List,
 
                 unknown => anyhow::bail!("unknown operation '{}'", unknown),
@@ -162,7 +183,7 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
 
             if fetch {
                 let rt = tokio::runtime::Runtime::new()?;
-                let seeds = sync::seeds(&profile)?;
+                let seeds = term::sync::seeds(&profile, &urn)?;
 
                 term::sync::sync(urn, seeds, sync::Mode::Fetch, &profile, signer.clone(), &rt)?;
                 git::fetch_remote(&mut remote, &repo, signer, &profile)?;
@@ -185,6 +206,33 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
                 anyhow::bail!("remote '{}' not found", remote)
             }
         },
+        Operation::Rename { remote, name } => {
+            // The remote may be given by its full name, eg. `peers/alice/rad`, its
+            // short name, eg. `alice`, or by peer id or person name.
+            let short = format!("{}/{}/rad", project::PEER_PREFIX, remote);
+            let from = if repo.find_remote(&remote).is_ok() {
+                remote.clone()
+            } else if repo.find_remote(&short).is_ok() {
+                short
+            } else {
+                find_remote(&remote, &storage, &repo, &urn)?
+                    .ok_or_else(|| anyhow!("remote '{}' not found", remote))?
+            };
+            let renamed = project::rename_remote(&repo, &urn, &from, &name)?;
+
+            term::success!(
+                "Remote {} renamed to {}",
+                term::format::highlight(&from),
+                term::format::highlight(&renamed.remote)
+            );
+            for (old, new) in renamed.branches {
+                term::success!(
+                    "Branch {} renamed to {}",
+                    term::format::highlight(old),
+                    term::format::highlight(new)
+                );
+            }
+        }
         Operation::List => {
             let mut table = term::Table::default();
             let proj = project::get(&storage, &urn)?
@@ -8,7 +8,7 @@ use radicle_common as common;
 use radicle_common::args::{Args, Error, Help};
 use radicle_common::cobs::patch::Patch;
 use radicle_common::tokio;
-use radicle_common::{cobs, keys, project, sync};
+use radicle_common::{cobs, keys, project};
 use radicle_terminal as term;
 use radicle_terminal::patch::Comment;
 
@@ -199,15 +199,9 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
 
     if options.sync {
         let rt = tokio::runtime::Runtime::new()?;
+        let seeds = term::sync::seeds(&profile, &urn)?;
 
-        term::sync::sync(
-            urn,
-            sync::seeds(&profile)?,
-            sync::Mode::Push,
-            &profile,
-            signer,
-            &rt,
-        )?;
+        term::sync::push_or_queue(urn, seeds, &profile, signer, &rt)?;
     }
 
     Ok(())
@@ -1,13 +1,21 @@
+use std::collections::BTreeMap;
 use std::ffi::OsString;
-use std::fs;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use anyhow::anyhow;
 use anyhow::Context as _;
+use librad::git::storage::ReadOnly;
+use librad::git::tracking;
 use librad::git::Urn;
+use librad::PeerId;
 
 use radicle_common::args::{Args, Error, Help};
-use radicle_common::{profile, project};
+use radicle_common::cobs::shared::Timestamp;
+use radicle_common::outbox::Outbox;
+use radicle_common::sync::history::{self, History};
+use radicle_common::trash::{self, Item};
+use radicle_common::{checkouts, keys, profile, project};
 use radicle_terminal as term;
 
 pub const HELP: Help = Help {
@@ -18,17 +26,39 @@ pub const HELP: Help = Help {
 Usage
 
     rad rm <urn> [<option>...]
+    rad rm --restore <urn>
+    rad rm --trash
+
+    Removes a project from storage. Before removal, what would be lost is reported:
+    local branches and collaborative objects (COBs) that changed since they were last
+    pushed to a seed, working copies, and tracked peers.
+
+    Removed projects are moved to the trash, and can be restored with `--restore` for
+    seven days, after which they are purged.
 
 Options
 
-    -i        Prompt before removal
-    --help    Print help
+    -i                  Prompt before removal
+    --keep-cobs         Keep the project's collaborative objects in storage
+    --permanent         Remove the project without moving it to the trash
+    --restore           Restore a removed project from the trash
+    --trash             List the removed projects in the trash
+    --help              Print help
 "#,
 };
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum Operation {
+    Remove { urn: Urn },
+    Restore { urn: Urn },
+    List,
+}
+
 pub struct Options {
-    urn: Urn,
-    prompt: bool,
+    pub op: Operation,
+    pub prompt: bool,
+    pub keep_cobs: bool,
+    pub permanent: bool,
 }
 
 impl Args for Options {
@@ -38,12 +68,28 @@ impl Args for Options {
         let mut parser = lexopt::Parser::from_args(args);
         let mut urn: Option<Urn> = None;
         let mut prompt = false;
+        let mut keep_cobs = false;
+        let mut permanent = false;
+        let mut restore = false;
+        let mut list = false;
 
         while let Some(arg) = parser.next()? {
             match arg {
                 Short('i') => {
                     prompt = true;
                 }
+                Long("keep-cobs") => {
+                    keep_cobs = true;
+                }
+                Long("permanent") => {
+                    permanent = true;
+                }
+                Long("restore") => {
+                    restore = true;
+                }
+                Long("trash") => {
+                    list = true;
+                }
                 Long("help") => {
                     return Err(Error::Help.into());
                 }
@@ -57,43 +103,302 @@ impl Args for Options {
             }
         }
 
+        let op = match (urn, restore, list) {
+            (None, false, true) => Operation::List,
+            (Some(_), _, true) => anyhow::bail!("unexpected URN with `--trash`"),
+            (_, true, true) => anyhow::bail!("`--restore` and `--trash` can't be combined"),
+            (Some(urn), true, false) => Operation::Restore { urn },
+            (Some(urn), false, false) => Operation::Remove { urn },
+            (None, _, false) => {
+                anyhow::bail!("a URN to remove must be provided; see `rad rm --help`")
+            }
+        };
+
         Ok((
             Options {
-                urn: urn.ok_or_else(|| {
-                    anyhow!("a URN to remove must be provided; see `rad rm --help`")
-                })?,
+                op,
                 prompt,
+                keep_cobs,
+                permanent,
             },
             vec![],
         ))
     }
 }
 
+/// What would be lost by removing a project.
+struct Report {
+    /// Local branches changed since the project was last synced.
+    branches: Vec<String>,
+    /// Local COB refs changed since the project was last synced.
+    cobs: Vec<String>,
+    /// When the project was last synced, if ever.
+    synced: Option<Timestamp>,
+    /// Whether the project is waiting to be pushed.
+    pending: bool,
+    /// Working copies of the project.
+    checkouts: Vec<PathBuf>,
+    /// Tracked peers.
+    tracked: Vec<PeerId>,
+}
+
+impl Report {
+    /// Whether removing the project would lose data that isn't available elsewhere.
+    fn has_unsynced(&self, keep_cobs: bool) -> bool {
+        !self.branches.is_empty() || (!self.cobs.is_empty() && !keep_cobs)
+    }
+}
+
 pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
     let profile = ctx.profile()?;
-    let storage = profile::read_only(&profile)?;
 
-    if project::get(&storage, &options.urn)?.is_none() {
-        anyhow::bail!("project {} does not exist", options.urn);
+    for item in trash::purge(&profile)? {
+        term::info!(
+            "Purged {} ({}) from the trash",
+            term::format::bold(&item.name),
+            term::format::tertiary(&item.urn)
+        );
+    }
+
+    match options.op {
+        Operation::Remove { urn } => remove(&profile, &urn, &options),
+        Operation::Restore { urn } => restore(&profile, &urn),
+        Operation::List => list(&profile),
     }
-    term::warning("Experimental tool; use at your own risk!");
+}
+
+fn remove(profile: &profile::Profile, urn: &Urn, options: &Options) -> anyhow::Result<()> {
+    let signer = term::signer(profile)?;
+    let storage = keys::storage(profile, signer)?;
+    let meta =
+        project::get(&storage, urn)?.ok_or_else(|| anyhow!("project {} does not exist", urn))?;
 
-    let monorepo = profile.paths().git_dir();
-    let namespace = monorepo
-        .join("refs")
-        .join("namespaces")
-        .join(options.urn.encode_id());
+    if trash::get(profile, urn)?.is_some() {
+        anyhow::bail!(
+            "project {} is already in the trash; restore it with `rad rm --restore`",
+            urn
+        );
+    }
+    let report = report(profile, &storage, &meta)?;
+    show(&report, options.keep_cobs);
 
-    if !options.prompt
-        || term::confirm(format!(
-            "Are you sure you would like to delete {}?",
-            term::format::dim(namespace.display())
+    let confirm = options.prompt || (options.permanent && report.has_unsynced(options.keep_cobs));
+    if confirm
+        && !term::confirm(format!(
+            "Are you sure you would like to {} {}?",
+            if options.permanent {
+                "permanently remove"
+            } else {
+                "remove"
+            },
+            term::format::highlight(&meta.name)
         ))
     {
-        rad_untrack::execute(&options.urn, rad_untrack::Options { peer: None }, &profile)?;
-        fs::remove_dir_all(namespace)?;
-        term::success!("Successfully removed project {}", options.urn);
+        return Ok(());
+    }
+
+    if options.permanent {
+        trash::delete_refs(profile.paths().git_dir(), urn, options.keep_cobs)?;
+    } else {
+        let item = Item {
+            urn: urn.clone(),
+            name: meta.name.clone(),
+            removed: Timestamp::now(),
+            tracked: report.tracked.clone(),
+            keep_cobs: options.keep_cobs,
+        };
+        trash::remove(profile, &item)?;
+    }
+
+    // Remote refs were already removed along with the namespace, or kept with the COBs.
+    tracking::untrack_all(
+        &storage,
+        urn,
+        tracking::UntrackAllArgs {
+            policy: tracking::policy::UntrackAll::Any,
+            prune: false,
+        },
+    )?;
+
+    if options.permanent {
+        let mut checkouts = checkouts::Checkouts::load(profile)?;
+        if !checkouts.remove(urn).is_empty() {
+            checkouts.save(profile)?;
+        }
+        term::success!(
+            "Permanently removed project {}",
+            term::format::tertiary(urn)
+        );
+    } else {
+        term::success!("Removed project {}", term::format::tertiary(urn));
+        term::tip!(
+            "To undo the removal within seven days, run `rad rm --restore {}`",
+            urn
+        );
+    }
+
+    Ok(())
+}
+
+fn restore(profile: &profile::Profile, urn: &Urn) -> anyhow::Result<()> {
+    let signer = term::signer(profile)?;
+    let storage = keys::storage(profile, signer)?;
+    let item = trash::restore(profile, urn)?;
+
+    for peer in &item.tracked {
+        // The tracking relationship may already exist, which is fine.
+        let _ = tracking::track(
+            &storage,
+            urn,
+            Some(*peer),
+            tracking::config::Config::default(),
+            tracking::policy::Track::Any,
+        )?;
+    }
+    term::success!(
+        "Restored project {} ({})",
+        term::format::bold(&item.name),
+        term::format::tertiary(urn)
+    );
+
+    Ok(())
+}
+
+fn list(profile: &profile::Profile) -> anyhow::Result<()> {
+    let items = trash::list(profile)?;
+
+    if items.is_empty() {
+        term::info!("The trash is empty");
+        return Ok(());
     }
 
+    let mut table = term::Table::default();
+    for item in items {
+        table.push([
+            term::format::bold(&item.name),
+            term::format::tertiary(&item.urn),
+            term::format::dim(format!("removed {}", item.removed)),
+            term::format::dim(format!("purged on {}", item.expires().to_rfc2822())),
+        ]);
+    }
+    table.render();
+
     Ok(())
 }
+
+/// Find out what would be lost by removing a project.
+fn report<S: AsRef<ReadOnly>>(
+    profile: &profile::Profile,
+    storage: &S,
+    meta: &project::Metadata,
+) -> anyhow::Result<Report> {
+    let urn = &meta.urn;
+    let history = History::load(profile)?;
+    let pending = Outbox::load(profile)?.contains(urn);
+    let checkouts = checkouts::load(profile)?
+        .get(urn)
+        .map(|p| p.to_path_buf())
+        .collect();
+    let tracked = project::tracked(meta, storage)?.into_keys().collect();
+    let refs = history::refs(profile.paths().git_dir(), urn)?;
+    let (branches, cobs) = unsynced(&history, urn, &refs);
+
+    Ok(Report {
+        branches,
+        cobs,
+        synced: history.get(urn),
+        pending,
+        checkouts,
+        tracked,
+    })
+}
+
+/// Get the local branches and COB refs that changed since they were last pushed.
+fn unsynced(
+    history: &History,
+    urn: &Urn,
+    refs: &BTreeMap<String, String>,
+) -> (Vec<String>, Vec<String>) {
+    let mut branches = Vec::new();
+    let mut cobs = Vec::new();
+
+    for name in history.unpushed(urn, refs) {
+        if name.starts_with("refs/heads/") {
+            branches.push(name.to_owned());
+        } else if name.starts_with("refs/cobs/") {
+            cobs.push(name.to_owned());
+        }
+    }
+    (branches, cobs)
+}
+
+fn show(report: &Report, keep_cobs: bool) {
+    match (report.pending, report.synced) {
+        (true, _) => term::warning("Project has changes waiting to be pushed to a seed"),
+        (false, None) => term::warning("Project was never synced with a seed"),
+        (false, Some(t)) => term::info!("Project was last synced {}", term::format::dim(t)),
+    }
+
+    if !report.branches.is_empty() {
+        term::blank();
+        term::info!("Local branches not synced to any seed:");
+        for branch in &report.branches {
+            term::indented(&term::format::highlight(branch));
+        }
+    }
+    if !report.cobs.is_empty() && !keep_cobs {
+        term::blank();
+        term::info!("Issue and patch changes not synced to any seed:");
+        for cob in &report.cobs {
+            term::indented(&term::format::dim(cob));
+        }
+    }
+    if !report.checkouts.is_empty() {
+        term::blank();
+        term::info!("Working copies, which will no longer be linked to the project:");
+        for path in &report.checkouts {
+            term::indented(&term::format::highlight(path.display()));
+        }
+    }
+    if !report.tracked.is_empty() {
+        term::blank();
+        term::info!("Tracked peers, which will be untracked:");
+        for peer in &report.tracked {
+            term::indented(&term::format::tertiary(peer));
+        }
+    }
+    term::blank();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unsynced() {
+        let urn = Urn::from_str("rad:git:hnrkbtw9t1of4ykjy6er4qqwxtc54k9943eto").unwrap();
+        let mut refs = [
+            ("refs/heads/master", "a"),
+            ("refs/cobs/xyz.radicle.issue/hnrk", "b"),
+            ("refs/rad/id", "c"),
+        ]
+        .iter()
+        .map(|(name, oid)| (name.to_string(), oid.to_string()))
+        .collect::<BTreeMap<_, _>>();
+        let mut history = History::default();
+
+        let (branches, cobs) = unsynced(&history, &urn, &refs);
+        assert_eq!(branches, vec!["refs/heads/master"]);
+        assert_eq!(cobs, vec!["refs/cobs/xyz.radicle.issue/hnrk"]);
+
+        history.record_push(urn.clone(), refs.clone());
+        let (branches, cobs) = unsynced(&history, &urn, &refs);
+        assert!(branches.is_empty());
+        assert!(cobs.is_empty());
+
+        refs.insert("refs/heads/master".to_owned(), "d".to_owned());
+        let (branches, cobs) = unsynced(&history, &urn, &refs);
+        assert_eq!(branches, vec!["refs/heads/master"]);
+        assert!(cobs.is_empty());
+    }
+}
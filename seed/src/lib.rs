@@ -0,0 +1,380 @@
+use std::convert::TryFrom;
+use std::ffi::OsString;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context as _};
+
+use radicle_common::args::{Args, Error, Help};
+use radicle_common::config::{self, Config, SeedConfig};
+use radicle_common::profile::Profile;
+use radicle_common::seed::health;
+use radicle_common::{project, seed};
+use radicle_terminal as term;
+
+pub const HELP: Help = Help {
+    name: "seed",
+    description: env!("CARGO_PKG_DESCRIPTION"),
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad seed ls
+    rad seed add <address> [--name <name>]
+    rad seed rm <name | address>
+    rad seed default <name | address> [--repo]
+    rad seed default --repo --unset
+    rad seed check
+
+    Seed addresses are of the form `rad://<peer-id>@<host>[:<port>]`.
+    The seed's Git and API endpoints are derived from the host.
+
+    The first seed in the profile's seed list is the default. With `--repo`,
+    the seed is set for the current repository only, via the `rad.seed` git
+    configuration key, which takes precedence over the profile's seeds.
+
+    `rad seed check` probes the P2P, Git and API endpoints of each configured
+    seed, and verifies that the seed's peer id matches the configured one.
+    Results are cached in the profile, and used to prefer healthy seeds when
+    syncing.
+
+Options
+
+    --name <name>   Name of the seed being added (default: the host)
+    --repo          Set the seed for the current repository only
+    --unset         Unset the seed of the current repository
+    --help          Print help
+"#,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OperationName {
+    Add,
+    Remove,
+    List,
+    Default,
+    Check,
+}
+
+impl Default for OperationName {
+    fn default() -> Self {
+        Self::List
+    }
+}
+
+#[derive(Debug)]
+pub enum Operation {
+    Add {
+        addr: seed::Address,
+        name: Option<String>,
+    },
+    Remove {
+        seed: String,
+    },
+    List,
+    Default {
+        seed: Option<String>,
+        repo: bool,
+    },
+    Check,
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub op: Operation,
+}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+        let mut op: Option<OperationName> = None;
+        let mut seed: Option<String> = None;
+        let mut name: Option<String> = None;
+        let mut repo = false;
+        let mut unset = false;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                Long("name") if op == Some(OperationName::Add) => {
+                    name = Some(parser.value()?.to_string_lossy().into());
+                }
+                Long("repo") if op == Some(OperationName::Default) => {
+                    repo = true;
+                }
+                Long("unset") if op == Some(OperationName::Default) => {
+                    unset = true;
+                }
+                Value(val) if op.is_none() => match val.to_string_lossy().as_ref() {
+                    "a" | "add" => op = Some(OperationName::Add),
+                    "r" | "rm" => op = Some(OperationName::Remove),
+                    "l" | "ls" => op = Some(OperationName::List),
+                    "d" | "default" => op = Some(OperationName::Default),
+                    "c" | "check" => op = Some(OperationName::Check),
+
+                    unknown => anyhow::bail!("unknown operation '{}'", unknown),
+                },
+                Value(val) if seed.is_none() => {
+                    seed = Some(val.to_string_lossy().into());
+                }
+                _ => return Err(anyhow::anyhow!(arg.unexpected())),
+            }
+        }
+
+        let op = match op.unwrap_or_default() {
+            OperationName::Add => {
+                let seed = seed.ok_or_else(|| anyhow!("a seed address must be specified"))?;
+                let addr = seed::Address::from_str(&seed).map_err(|err| Error::WithHint {
+                    err,
+                    hint: "hint: seed addresses have the format rad://<peer-id>@<host>[:<port>]",
+                })?;
+
+                Operation::Add { addr, name }
+            }
+            OperationName::Remove => Operation::Remove {
+                seed: seed.ok_or_else(|| anyhow!("a seed name or address must be specified"))?,
+            },
+            OperationName::List => Operation::List,
+            OperationName::Default => {
+                if unset && !repo {
+                    anyhow::bail!("`--unset` can only be used with `--repo`");
+                }
+                if seed.is_none() && !unset {
+                    anyhow::bail!("a seed name or address must be specified");
+                }
+                Operation::Default {
+                    seed: if unset { None } else { seed },
+                    repo,
+                }
+            }
+            OperationName::Check => Operation::Check,
+        };
+
+        Ok((Options { op }, vec![]))
+    }
+}
+
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let profile = ctx.profile()?;
+
+    match options.op {
+        Operation::Add { addr, name } => add(&profile, addr, name)?,
+        Operation::Remove { seed } => remove(&profile, &seed)?,
+        Operation::List => list(&profile)?,
+        Operation::Default { seed, repo } => default(&profile, seed.as_deref(), repo)?,
+        Operation::Check => check(&profile)?,
+    }
+
+    Ok(())
+}
+
+pub fn add(profile: &Profile, addr: seed::Address, name: Option<String>) -> anyhow::Result<()> {
+    let mut config = match Config::profile(profile) {
+        Ok(config) => config,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Config::default(),
+        Err(err) => {
+            return Err(err)
+                .with_context(|| format!("couldn't read {}", Config::path(profile).display()))
+        }
+    };
+    let mut seed = SeedConfig::from_address(&addr)?;
+
+    if let Some(name) = name {
+        seed.name = Some(name);
+    }
+    if find(&config, seed.p2p.as_str()).is_some()
+        || seed
+            .name
+            .as_deref()
+            .and_then(|n| find(&config, n))
+            .is_some()
+    {
+        anyhow::bail!("seed {} is already configured", addr);
+    }
+
+    term::success!("Added seed {}", term::format::highlight(name_of(&seed)));
+    config.seed.push(seed);
+    config.write(Config::path(profile))?;
+
+    Ok(())
+}
+
+pub fn remove(profile: &Profile, query: &str) -> anyhow::Result<()> {
+    let mut config = Config::profile(profile)?;
+    let ix = find(&config, query).ok_or_else(|| anyhow!("seed '{}' not found", query))?;
+    let seed = config.seed.remove(ix);
+
+    config.write(Config::path(profile))?;
+    term::success!("Removed seed {}", term::format::highlight(name_of(&seed)));
+
+    Ok(())
+}
+
+pub fn list(profile: &Profile) -> anyhow::Result<()> {
+    let config = Config::profile(profile)?;
+
+    let repo_seed = seed::get_seed(Path::new(".")).unwrap_or_else(|err| {
+        term::warning(&format!("{:#}", err));
+        None
+    });
+
+    if let Some(url) = repo_seed {
+        term::info!(
+            "This repository uses {} {}",
+            term::format::highlight(&url),
+            term::format::dim(format!("(set via `{}`)", seed::CONFIG_SEED_KEY))
+        );
+    } else if Config::local().is_ok() {
+        term::info!(
+            "This working copy uses the seeds in {}",
+            term::format::highlight(config::FILE_NAME_LOCAL)
+        );
+    } else {
+        term::info!(
+            "Using the seeds of profile {}",
+            term::format::highlight(profile.id())
+        );
+    }
+    term::blank();
+
+    if config.seed.is_empty() {
+        term::print(term::format::italic("No seeds configured."));
+        return Ok(());
+    }
+
+    let mut table = term::Table::default();
+    for (i, seed) in config.seeds().enumerate() {
+        table.push([
+            term::format::bold(name_of(seed)),
+            term::format::tertiary(&seed.p2p),
+            term::format::dim(&seed.git),
+            term::format::dim(&seed.api),
+            if i == 0 {
+                term::format::badge_primary("default")
+            } else {
+                String::new()
+            },
+        ]);
+    }
+    table.render();
+
+    Ok(())
+}
+
+pub fn default(profile: &Profile, query: Option<&str>, repo: bool) -> anyhow::Result<()> {
+    if repo {
+        project::cwd().map_err(|_| anyhow!("`--repo` must be used in the context of a project"))?;
+    }
+
+    let query = match query {
+        Some(query) => query,
+        None => {
+            seed::unset_seed()?;
+            term::success!("Unset the seed of this repository");
+
+            return Ok(());
+        }
+    };
+
+    let mut config = Config::profile(profile)?;
+    let seed = match find(&config, query) {
+        Some(ix) => config.seed.remove(ix),
+        None if repo => {
+            // Seeds that aren't in the profile can still be used for a single repository.
+            let addr = seed::Address::from_str(query)
+                .map_err(|_| anyhow!("seed '{}' not found", query))?;
+            SeedConfig::from_address(&addr)?
+        }
+        None => anyhow::bail!("seed '{}' not found", query),
+    };
+
+    if repo {
+        seed::set_seed(&seed.p2p)?;
+        term::success!(
+            "Seed for this repository set to {}",
+            term::format::highlight(name_of(&seed))
+        );
+    } else {
+        term::success!(
+            "Default seed set to {}",
+            term::format::highlight(name_of(&seed))
+        );
+        config.seed.insert(0, seed);
+        config.write(Config::path(profile))?;
+    }
+
+    Ok(())
+}
+
+pub fn check(profile: &Profile) -> anyhow::Result<()> {
+    let config = Config::load(profile)?;
+    let mut cache = health::Cache::load(profile)?;
+    let mut table = term::Table::default();
+
+    for seed in config.seeds() {
+        let spinner = term::spinner(format!("Checking {}...", name_of(seed)));
+        let health = health::check(seed, health::DEFAULT_TIMEOUT);
+
+        if health.is_healthy() {
+            spinner.clear();
+        } else {
+            spinner.failed();
+        }
+
+        table.push([
+            term::format::bold(name_of(seed)),
+            format!("{} {}", term::format::dim("p2p"), probe(&health.p2p)),
+            format!("{} {}", term::format::dim("git"), probe(&health.git)),
+            format!("{} {}", term::format::dim("api"), probe(&health.api)),
+            match health.verified {
+                Some(true) => term::format::positive("✓ verified"),
+                Some(false) => term::format::negative("✗ peer id mismatch"),
+                None => term::format::dim("? unverified"),
+            },
+        ]);
+        cache.insert(seed, health);
+    }
+    cache.save(profile)?;
+
+    term::blank();
+    table.render();
+    term::blank();
+
+    Ok(())
+}
+
+/// Find a seed by name, host or address.
+fn find(config: &Config, query: &str) -> Option<usize> {
+    let addr = seed::Address::from_str(query).ok();
+
+    config.seeds().position(|seed| {
+        seed.name.as_deref() == Some(query)
+            || seed.p2p.host_str() == Some(query)
+            || seed.p2p.as_str() == query
+            || addr.as_ref().map_or(false, |addr| {
+                seed::Address::try_from(seed.p2p.clone()).map_or(false, |a| {
+                    a.host == addr.host && a.peer() == addr.peer() && a.port() == addr.port()
+                })
+            })
+    })
+}
+
+fn name_of(seed: &SeedConfig) -> String {
+    seed.name
+        .clone()
+        .or_else(|| seed.p2p.host_str().map(|h| h.to_owned()))
+        .unwrap_or_else(|| seed.p2p.to_string())
+}
+
+fn probe(probe: &health::Probe) -> String {
+    match (probe.ok, probe.latency) {
+        (true, Some(ms)) => term::format::positive(format!("✓ {}ms", ms)),
+        (true, None) => term::format::positive("✓"),
+        (false, _) => term::format::negative("✗"),
+    }
+}
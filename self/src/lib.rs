@@ -1,9 +1,16 @@
 use std::ffi::OsString;
+use std::str::FromStr;
 
-use anyhow::anyhow;
+use anyhow::{anyhow, Context as _};
+
+use librad::git::storage::Storage;
+use librad::git::tracking;
+use librad::git::Urn;
+use librad::PeerId;
 
 use radicle_common::args::{Args, Error, Help};
-use radicle_common::{keys, person, profile};
+use radicle_common::outbox::Outbox;
+use radicle_common::{keys, person, profile, sync, tokio};
 use radicle_terminal as term;
 
 pub const HELP: Help = Help {
@@ -14,14 +21,29 @@ pub const HELP: Help = Help {
 Usage
 
     rad self [<option>...]
+    rad self devices [ls]
+    rad self devices add <peer-id> [--no-sync]
+    rad self devices rm <peer-id> [--no-sync]
+    rad self devices accept <peer-id> [--urn <urn>] [--no-sync]
+
+    `rad self devices` manages the devices of your identity. Each device has its own
+    key, and any device of the identity can commit and propose patches on its behalf.
+
+    To link a new device, run `rad self devices add` with the new device's peer id on
+    an existing device, then `rad self devices accept` with the existing device's peer
+    id and `--urn <urn>` on the new device. Identity updates must be signed by a
+    majority of devices: to complete the link, run `rad self devices accept` with the
+    new device's peer id on the existing device. The same applies when removing a
+    device.
 
 Options
 
-    --name       Show name
-    --urn        Show URN
-    --peer       Show Peer ID
-    --profile    Show Profile ID
-    --help       Show help
+    --name         Show name
+    --urn          Show URN
+    --peer         Show Peer ID
+    --profile      Show Profile ID
+    --no-sync      Don't sync the identity with the configured seeds
+    --help         Show help
 "#,
 };
 
@@ -34,9 +56,24 @@ enum Show {
     All,
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum DeviceOperation {
+    List,
+    Add { peer: PeerId },
+    Remove { peer: PeerId },
+    Accept { peer: PeerId, urn: Option<Urn> },
+}
+
+#[derive(Debug)]
+enum Operation {
+    Show(Show),
+    Devices(DeviceOperation),
+}
+
 #[derive(Debug)]
 pub struct Options {
-    show: Show,
+    op: Operation,
+    sync: bool,
 }
 
 impl Args for Options {
@@ -45,9 +82,23 @@ impl Args for Options {
 
         let mut parser = lexopt::Parser::from_args(args);
         let mut show: Option<Show> = None;
+        let mut devices = false;
+        let mut op: Option<String> = None;
+        let mut peer: Option<PeerId> = None;
+        let mut urn: Option<Urn> = None;
+        let mut sync = true;
 
         while let Some(arg) = parser.next()? {
             match arg {
+                Long("urn") if devices => {
+                    let val = parser.value()?;
+                    let val = val.to_string_lossy();
+
+                    urn = Some(Urn::from_str(&val).context(format!("invalid URN '{}'", val))?);
+                }
+                Long("no-sync") if devices => {
+                    sync = false;
+                }
                 Long("name") if show.is_none() => {
                     show = Some(Show::Name);
                 }
@@ -63,24 +114,59 @@ impl Args for Options {
                 Long("help") => {
                     return Err(Error::Help.into());
                 }
+                Value(val) if !devices && show.is_none() && val == "devices" => {
+                    devices = true;
+                }
+                Value(val) if devices && op.is_none() => match val.to_string_lossy().as_ref() {
+                    "ls" | "add" | "rm" | "accept" => {
+                        op = Some(val.to_string_lossy().into_owned());
+                    }
+                    unknown => anyhow::bail!("unknown operation '{}'", unknown),
+                },
+                Value(val) if devices && op.is_some() && peer.is_none() => {
+                    let val = val.to_string_lossy();
+                    let val =
+                        PeerId::from_str(&val).map_err(|_| anyhow!("invalid peer id '{}'", val))?;
+
+                    peer = Some(val);
+                }
                 _ => return Err(anyhow::anyhow!(arg.unexpected())),
             }
         }
 
-        Ok((
-            Options {
-                show: show.unwrap_or(Show::All),
-            },
-            vec![],
-        ))
+        let op = if devices {
+            if show.is_some() {
+                anyhow::bail!("`rad self devices` can't be combined with other options");
+            }
+            let op = match (op.as_deref(), peer) {
+                (None | Some("ls"), None) => DeviceOperation::List,
+                (Some("add"), Some(peer)) => DeviceOperation::Add { peer },
+                (Some("rm"), Some(peer)) => DeviceOperation::Remove { peer },
+                (Some("accept"), Some(peer)) => DeviceOperation::Accept { peer, urn },
+                (Some(op), None) => anyhow::bail!("a peer id must be specified for '{}'", op),
+                (_, Some(_)) => anyhow::bail!("unexpected peer id"),
+            };
+            if urn.is_some() && !matches!(op, DeviceOperation::Accept { .. }) {
+                anyhow::bail!("`--urn` can only be used with `rad self devices accept`");
+            }
+            Operation::Devices(op)
+        } else {
+            Operation::Show(show.unwrap_or(Show::All))
+        };
+
+        Ok((Options { op, sync }, vec![]))
     }
 }
 
 pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
     let profile = ctx.profile()?;
+    let show = match options.op {
+        Operation::Show(show) => show,
+        Operation::Devices(op) => return devices(op, options.sync, &profile),
+    };
     let storage = profile::read_only(&profile)?;
 
-    match options.show {
+    match show {
         Show::Name => {
             if let Some(urn) = storage.config()?.user()? {
                 if let Some(person) = person::get(&storage, &urn)? {
@@ -148,7 +234,189 @@ fn all(profile: &profile::Profile) -> anyhow::Result<()> {
         term::format::tertiary(keys_path.display()),
     ]);
 
+    let outbox = Outbox::load(profile)?;
+    table.push([
+        String::from("Outbox"),
+        term::format::tertiary(format!("{} pending", outbox.len())),
+    ]);
+
     table.render_tree();
 
+    if !outbox.is_empty() {
+        term::blank();
+
+        let mut table = term::Table::default();
+        for entry in outbox.iter() {
+            table.push([
+                term::format::highlight(&entry.urn),
+                term::format::dim(format!("{} attempt(s)", entry.attempts)),
+            ]);
+        }
+        table.render();
+
+        term::blank();
+        term::tip!("Run `rad sync --pending` to sync queued changes.");
+    }
+
+    Ok(())
+}
+
+fn devices(op: DeviceOperation, sync: bool, profile: &profile::Profile) -> anyhow::Result<()> {
+    let signer = term::signer(profile)?;
+    let storage = keys::storage(profile, signer)?;
+    let rt = tokio::runtime::Runtime::new()?;
+
+    match op {
+        DeviceOperation::List => {
+            let identity = person::local(&storage).context("no local identity found")?;
+            let mut table = term::Table::default();
+
+            for key in identity.delegations().iter() {
+                let peer = PeerId::from(*key);
+
+                table.push([
+                    term::format::tertiary(peer),
+                    term::format::dim(keys::to_ssh_fingerprint(&peer)?),
+                    if &peer == storage.peer_id() {
+                        term::format::badge_primary("this device")
+                    } else {
+                        String::new()
+                    },
+                ]);
+            }
+            table.render();
+        }
+        DeviceOperation::Add { peer } => {
+            let identity = person::local(&storage).context("no local identity found")?;
+            let urn = identity.urn();
+            let delegations = identity.delegations();
+
+            if delegations.iter().any(|k| k == peer.as_public_key()) {
+                anyhow::bail!("device {} is already linked to your identity", peer);
+            }
+            person::set_delegations(
+                &storage,
+                delegations
+                    .iter()
+                    .copied()
+                    .chain(std::iter::once(*peer.as_public_key())),
+            )?;
+            track(&storage, &urn, peer)?;
+
+            term::success!(
+                "Device {} added to {}",
+                term::format::tertiary(peer),
+                term::format::highlight(&urn)
+            );
+            if sync {
+                push(&urn, profile, &rt)?;
+            }
+            term::tip!(
+                "To complete the link, run `rad self devices accept {} --urn {}` on the new device.",
+                storage.peer_id(),
+                urn
+            );
+        }
+        DeviceOperation::Remove { peer } => {
+            let identity = person::local(&storage).context("no local identity found")?;
+            let urn = identity.urn();
+            let delegations = identity.delegations();
+
+            if &peer == storage.peer_id() {
+                anyhow::bail!(
+                    "you can't remove the current device; run this command on another device"
+                );
+            }
+            if !delegations.iter().any(|k| k == peer.as_public_key()) {
+                anyhow::bail!("device {} is not linked to your identity", peer);
+            }
+            person::set_delegations(
+                &storage,
+                delegations
+                    .iter()
+                    .copied()
+                    .filter(|k| k != peer.as_public_key()),
+            )?;
+
+            term::success!(
+                "Device {} removed from {}",
+                term::format::tertiary(peer),
+                term::format::highlight(&urn)
+            );
+            if sync {
+                push(&urn, profile, &rt)?;
+            }
+        }
+        DeviceOperation::Accept { peer, urn } => {
+            let urn = match urn {
+                Some(urn) => urn,
+                None => person::local(&storage)
+                    .context("no local identity found, specify one with `--urn`")?
+                    .urn(),
+            };
+            track(&storage, &urn, peer)?;
+
+            if sync {
+                let seeds = term::sync::seeds(profile, &urn)?;
+                let signer = term::signer(profile)?;
+
+                term::sync::sync(urn.clone(), seeds, sync::Mode::Fetch, profile, signer, &rt)?;
+            }
+            // Adopt the peer's view of the identity, signing it with our key.
+            let person = person::merge(&storage, &urn, peer)
+                .with_context(|| format!("couldn't merge identity {} from {}", urn, peer))?;
+
+            let this = storage.peer_id().as_public_key();
+            if !person.delegations().iter().any(|k| k == this) {
+                anyhow::bail!(
+                    "this device is not a delegation of {}; run `rad self devices add {}` on device {} first",
+                    urn,
+                    storage.peer_id(),
+                    peer
+                );
+            }
+            person::set_local(&storage, &person)?;
+
+            term::success!(
+                "Identity {} of {} accepted",
+                term::format::highlight(&urn),
+                term::format::tertiary(peer)
+            );
+            term::success!(
+                "Local identity set to {}",
+                term::format::highlight(&person.subject().name)
+            );
+            if sync {
+                push(&urn, profile, &rt)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Track a device of an identity, to fetch its updates.
+fn track(storage: &Storage, urn: &Urn, peer: PeerId) -> anyhow::Result<()> {
+    if &peer == storage.peer_id() {
+        anyhow::bail!("device {} is the current device", peer);
+    }
+    // The tracking relationship may already exist, which is fine.
+    let _ = tracking::track(
+        storage,
+        urn,
+        Some(peer),
+        tracking::config::Config::default(),
+        tracking::policy::Track::Any,
+    )?;
+
+    Ok(())
+}
+
+fn push(urn: &Urn, profile: &profile::Profile, rt: &tokio::runtime::Runtime) -> anyhow::Result<()> {
+    let seeds = term::sync::seeds(profile, urn)?;
+    let signer = term::signer(profile)?;
+
+    term::sync::sync(urn.clone(), seeds, sync::Mode::Push, profile, signer, rt)?;
+
     Ok(())
 }
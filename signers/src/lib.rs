@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::Path;
+
+use anyhow::anyhow;
+
+use librad::git::storage::ReadOnly;
+use librad::PeerId;
+
+use radicle_common as common;
+use radicle_common::args::{Args, Error, Help};
+use radicle_common::{git, keys, profile, project};
+use radicle_terminal as term;
+
+pub const HELP: Help = Help {
+    name: "signers",
+    description: env!("CARGO_PKG_DESCRIPTION"),
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad signers [ls]
+    rad signers sync [--dry-run]
+
+    Lists or updates the signers of the current project, as found in the repository's
+    `.gitsigners` file.
+
+    `rad signers sync` regenerates the `.gitsigners` file from the project delegates,
+    the local peer and the tracked peers. Peers that are no longer tracked are removed,
+    and entries that aren't labeled with a peer id are kept.
+
+    To update the file automatically when peers are tracked or untracked, set the
+    `rad.signers.autosync` git configuration key to `true`.
+
+Options
+
+    --dry-run   Show the changes that would be made, without writing them
+    --help      Print help
+"#,
+};
+
+/// Git configuration key to update `.gitsigners` on `rad track` and `rad untrack`.
+pub const CONFIG_AUTOSYNC_KEY: &str = "rad.signers.autosync";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Operation {
+    List,
+    Sync { dry_run: bool },
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub op: Operation,
+}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+        let mut op: Option<Operation> = None;
+        let mut dry_run = false;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                Long("dry-run") => {
+                    dry_run = true;
+                }
+                Value(val) if op.is_none() => match val.to_string_lossy().as_ref() {
+                    "l" | "ls" => op = Some(Operation::List),
+                    "s" | "sync" => op = Some(Operation::Sync { dry_run: false }),
+
+                    unknown => anyhow::bail!("unknown operation '{}'", unknown),
+                },
+                _ => return Err(anyhow!(arg.unexpected())),
+            }
+        }
+
+        let op = match op.unwrap_or(Operation::List) {
+            Operation::Sync { .. } => Operation::Sync { dry_run },
+            Operation::List if dry_run => {
+                anyhow::bail!("`--dry-run` can only be used with `rad signers sync`")
+            }
+            op => op,
+        };
+
+        Ok((Options { op }, vec![]))
+    }
+}
+
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let (urn, _) = project::cwd()
+        .map_err(|_| anyhow!("this command must be run in the context of a project"))?;
+    let profile = ctx.profile()?;
+    let storage = profile::read_only(&profile)?;
+    let project = project::get(&storage, &urn)?
+        .ok_or_else(|| anyhow!("couldn't load project {} from local state", urn))?;
+    let repo = Path::new(".");
+
+    match options.op {
+        Operation::List => list(repo, &project, &storage)?,
+        Operation::Sync { dry_run } => {
+            let update = sync(repo, &project, &storage, dry_run)?;
+
+            if update.is_empty() {
+                term::info!("{} is up to date", term::format::highlight(".gitsigners"));
+            } else {
+                report(&update, dry_run);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// List the signers of the repository's `.gitsigners` file.
+pub fn list(repo: &Path, project: &project::Metadata, storage: &ReadOnly) -> anyhow::Result<()> {
+    let signers = match git::gitsigners(repo) {
+        Ok(signers) => signers,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            term::print(term::format::italic("No .gitsigners file found."));
+            term::tip!("Run `rad signers sync` to create one.");
+
+            return Ok(());
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let tracked = project::tracked(project, storage)?;
+    let mut table = term::Table::default();
+
+    for (label, key) in signers {
+        let peer = label.parse::<PeerId>().ok();
+        let info = peer.and_then(|p| tracked.get(&p));
+        let name = info
+            .and_then(|info| info.person.as_ref())
+            .map(|person| person.name.clone())
+            .unwrap_or_default();
+        let extensions = info
+            .map(|info| {
+                info.extensions()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_default();
+        let delegate = peer.map_or(false, |p| project.delegates.iter().any(|d| d.contains(&p)));
+
+        table.push([
+            term::format::bold(peer.map_or(label, |p| common::fmt::peer(&p))),
+            term::format::tertiary(name),
+            term::format::dim(keys::ssh_key_fingerprint(&key).unwrap_or(key)),
+            term::format::dim(extensions),
+            if delegate {
+                term::format::badge_primary("delegate")
+            } else if peer.map_or(false, |p| tracked.contains_key(&p)) {
+                term::format::badge_secondary("tracked")
+            } else {
+                String::new()
+            },
+        ]);
+    }
+    table.render();
+
+    Ok(())
+}
+
+/// Regenerate the repository's `.gitsigners` file from the project delegates, the local
+/// peer and the tracked peers.
+pub fn sync(
+    repo: &Path,
+    project: &project::Metadata,
+    storage: &ReadOnly,
+    dry_run: bool,
+) -> anyhow::Result<git::SignersUpdate> {
+    let tracked = project::tracked(project, storage)?;
+    let mut peers: Vec<PeerId> = project
+        .delegates
+        .iter()
+        .flat_map(project::Delegate::peers)
+        .collect();
+
+    peers.push(*storage.peer_id());
+
+    let mut others = tracked.keys().copied().collect::<Vec<_>>();
+    others.sort_by_key(|p| p.default_encoding());
+    peers.extend(others);
+
+    let mut names: HashMap<PeerId, String> = HashMap::new();
+    for peer in &peers {
+        let info = match tracked.get(peer) {
+            Some(info) => info.clone(),
+            None => project::PeerInfo::get(peer, project, storage),
+        };
+        if let Some(person) = info.person {
+            names.insert(*peer, person.name);
+        }
+    }
+
+    let update = git::sync_gitsigners(
+        repo,
+        peers.iter().map(|p| (p, names.get(p).map(|n| n.as_str()))),
+        dry_run,
+    )?;
+
+    Ok(update)
+}
+
+/// Update the `.gitsigners` file if automatic updates are enabled via
+/// [`CONFIG_AUTOSYNC_KEY`].
+pub fn autosync(
+    repo: &Path,
+    project: &project::Metadata,
+    storage: &ReadOnly,
+) -> anyhow::Result<()> {
+    let enabled = git::git(repo, ["config", "--bool", CONFIG_AUTOSYNC_KEY])
+        .map(|v| v.trim() == "true")
+        .unwrap_or(false);
+
+    if enabled {
+        let update = sync(repo, project, storage, false)?;
+        if !update.is_empty() {
+            report(&update, false);
+        }
+    }
+    Ok(())
+}
+
+fn report(update: &git::SignersUpdate, dry_run: bool) {
+    let (add, remove) = if dry_run {
+        ("Would add", "Would remove")
+    } else {
+        ("Added", "Removed")
+    };
+    for peer in &update.added {
+        term::success!(
+            "{} {} to {}",
+            add,
+            term::format::tertiary(common::fmt::peer(peer)),
+            term::format::highlight(".gitsigners")
+        );
+    }
+    for peer in &update.removed {
+        term::success!(
+            "{} {} from {}",
+            remove,
+            term::format::tertiary(common::fmt::peer(peer)),
+            term::format::highlight(".gitsigners")
+        );
+    }
+}
@@ -4,6 +4,7 @@ use std::ffi::OsString;
 use std::iter;
 use std::str::FromStr;
 
+use librad::crypto::BoxedSigner;
 use librad::git::Storage;
 use librad::git::Urn;
 use librad::profile::Profile;
@@ -11,6 +12,7 @@ use librad::profile::Profile;
 use radicle_common::args;
 use radicle_common::args::{Args, Error, Help};
 use radicle_common::nonempty::NonEmpty;
+use radicle_common::outbox::Outbox;
 use radicle_common::sync::Mode;
 use radicle_common::{identity, keys, person, project, sync, tokio};
 use radicle_terminal as term;
@@ -28,16 +30,22 @@ Usage
 
     rad sync [<urn> | <url>] [--seed <address>]... [<options>...]
     rad sync --self [--seed <address>]...
+    rad sync --pending [--seed <address>]...
 
     If a <urn> is specified, seeds may be given via the `--seed` option.
     If a <url> is specified, the seed is implied.
     If neither is specified, the URN and seed of the current project is used.
     If the project has no configured seed, the active profile's default seed list is used.
 
+    When no seed can be reached, pushes are queued in the profile's outbox. Use
+    `--pending` to sync all queued projects, each with its own seeds unless `--seed`
+    is given.
+
 Options
 
     --seed <address>    Sync to the given seed (may be specified multiple times)
     --self              Sync your local identity only
+    --pending           Sync all projects queued in the outbox
     --help              Print help
 
 Seed addresses
@@ -57,6 +65,7 @@ pub struct Options {
     pub mode: Mode,
     pub verbose: bool,
     pub sync_self: bool,
+    pub pending: bool,
 }
 
 impl Args for Options {
@@ -67,6 +76,7 @@ impl Args for Options {
         let mut verbose = false;
         let mut origin = None;
         let mut sync_self = false;
+        let mut pending = false;
         let mut unparsed = Vec::new();
         let mut seeds = Vec::new();
 
@@ -81,6 +91,9 @@ impl Args for Options {
                 Long("self") => {
                     sync_self = true;
                 }
+                Long("pending") => {
+                    pending = true;
+                }
                 Long("seed") => {
                     let value = parser.value()?;
                     let value = value.to_string_lossy();
@@ -127,6 +140,7 @@ impl Args for Options {
                 seeds,
                 mode: Mode::default(),
                 sync_self,
+                pending,
                 verbose,
             },
             unparsed,
@@ -137,8 +151,15 @@ impl Args for Options {
 pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
     let profile = ctx.profile()?;
     let signer = term::signer(&profile)?;
-    let storage = keys::storage(&profile, signer)?;
+    let storage = keys::storage(&profile, signer.clone())?;
     let rt = tokio::runtime::Runtime::new()?;
+
+    if options.pending {
+        let seeds = options.seeds.clone().try_into().ok();
+
+        return sync_pending(&profile, seeds, signer, rt);
+    }
+
     let urn = if let Some(origin) = &options.origin {
         origin.urn.clone()
     } else {
@@ -150,7 +171,7 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
     } else if let Ok(seeds) = options.seeds.clone().try_into() {
         seeds
     } else {
-        sync::seeds(&profile)?
+        term::sync::seeds(&profile, &urn)?
     };
 
     if options.sync_self {
@@ -201,14 +222,21 @@ pub fn sync(
 
     let storage = storage.read_only();
     let signer = term::signer(profile)?;
-    let _result = term::sync::sync(
-        urn.clone(),
-        seeds.clone(),
-        options.mode,
-        profile,
-        signer,
-        &rt,
-    )?;
+
+    if let Mode::Push = options.mode {
+        if term::sync::push_or_queue(urn.clone(), seeds.clone(), profile, signer, &rt)?.is_none() {
+            return Ok(());
+        }
+    } else {
+        let _result = term::sync::sync(
+            urn.clone(),
+            seeds.clone(),
+            options.mode,
+            profile,
+            signer,
+            &rt,
+        )?;
+    }
     term::blank();
 
     if options.verbose {
@@ -279,3 +307,53 @@ pub fn sync(
 
     Ok(())
 }
+
+/// Push all projects queued in the outbox, to the given seeds or to the seeds of each
+/// project.
+pub fn sync_pending(
+    profile: &Profile,
+    seeds: Option<NonEmpty<sync::Seed<String>>>,
+    signer: BoxedSigner,
+    rt: tokio::runtime::Runtime,
+) -> anyhow::Result<()> {
+    let outbox = Outbox::load(profile)?;
+
+    if outbox.is_empty() {
+        term::info!("Nothing to sync, the outbox is empty.");
+        return Ok(());
+    }
+
+    term::headline(&format!(
+        "Syncing 🌱 {} pending project(s)",
+        term::format::dim(outbox.len())
+    ));
+
+    let mut synced = 0;
+    for entry in outbox.iter() {
+        term::info!("{}", term::format::highlight(&entry.urn));
+
+        let seeds = match &seeds {
+            Some(seeds) => seeds.clone(),
+            None => term::sync::seeds(profile, &entry.urn)?,
+        };
+        // The project stays queued when syncing fails, so we move on to the next one.
+        match term::sync::push_or_queue(entry.urn.clone(), seeds, profile, signer.clone(), &rt) {
+            Ok(Some(_)) => synced += 1,
+            Ok(None) => {}
+            Err(err) => term::error(format!("Failed to sync {}: {:#}", entry.urn, err)),
+        }
+    }
+    term::blank();
+
+    let remaining = Outbox::load(profile)?.len();
+    if remaining == 0 {
+        term::success!("Synced {} pending project(s)", synced);
+    } else {
+        term::warning(&format!(
+            "Synced {} pending project(s), {} remaining",
+            synced, remaining
+        ));
+    }
+
+    Ok(())
+}
@@ -9,6 +9,7 @@ use dialoguer::{console::style, console::Style, theme::ColorfulTheme, Input, Pas
 
 use radicle_common::cobs::issue::Issue;
 use radicle_common::cobs::shared::CommentId;
+use radicle_common::diff;
 use radicle_common::signer::ToSigner;
 
 use super::command;
@@ -177,10 +178,17 @@ pub fn abort<D: fmt::Display>(prompt: D) -> bool {
     ask(format::tertiary(prompt), false)
 }
 
-/// Get the signer. First we try getting it from ssh-agent, otherwise we prompt the user.
+/// Get the signer. First we try ssh-agent, then the signer configured in the profile,
+/// if any, otherwise we prompt the user.
 pub fn signer(profile: &Profile) -> anyhow::Result<BoxedSigner> {
-    let signer = if let Ok(sock) = keys::ssh_auth_sock() {
-        sock.to_signer(profile)?
+    if let Some(signer) = keys::ssh_auth_sock()
+        .ok()
+        .and_then(|sock| sock.to_signer(profile).ok())
+    {
+        return Ok(signer);
+    }
+    let signer = if let Some(signer) = radicle_common::signer::configured(profile)? {
+        signer
     } else {
         secret_key(profile)?.to_signer(profile)?
     };
@@ -386,6 +394,17 @@ pub fn markdown(content: &str) {
     }
 }
 
+/// Print the line diff between two texts.
+pub fn diff(old: &str, new: &str) {
+    for line in diff::lines(old, new) {
+        match line {
+            diff::Line::Unchanged(l) => println!("{}", format::dim(format!("  {}", l))),
+            diff::Line::Added(l) => println!("{}", format::positive(format!("+ {}", l))),
+            diff::Line::Removed(l) => println!("{}", format::negative(format!("- {}", l))),
+        }
+    }
+}
+
 fn _info(args: std::fmt::Arguments) {
     println!("{}", args);
 }
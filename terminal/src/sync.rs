@@ -1,3 +1,4 @@
+use std::cell::{Cell, RefCell};
 use std::convert::TryInto;
 use std::time;
 
@@ -5,13 +6,26 @@ use librad::git::Urn;
 
 use radicle_common as common;
 use radicle_common::nonempty::NonEmpty;
+use radicle_common::outbox::Outbox;
 use radicle_common::profile::Profile;
 use radicle_common::signer::ToSigner;
 use radicle_common::sync;
+use radicle_common::sync::history::{self, History};
 use radicle_common::sync::SyncResult;
 
 use crate as term;
 
+/// Get the seeds to sync a project with. Invalid seed configurations of the project's
+/// working copies are reported, and ignored.
+pub fn seeds(profile: &Profile, urn: &Urn) -> anyhow::Result<NonEmpty<sync::Seed<String>>> {
+    let (seeds, errors) = sync::project_seeds(profile, urn)?;
+
+    for err in errors {
+        term::warning(&format!("Ignoring seed configuration: {:#}", err));
+    }
+    Ok(seeds)
+}
+
 pub fn sync(
     urn: Urn,
     seeds: NonEmpty<sync::Seed<String>>,
@@ -22,21 +36,53 @@ pub fn sync(
 ) -> anyhow::Result<NonEmpty<SyncResult>> {
     let signer = signer.to_signer(profile)?;
     let timeout = time::Duration::from_secs(9);
-    let spinner = term::spinner("Syncing...");
+    // The refs that are about to be pushed.
+    let refs = match mode {
+        sync::Mode::Push | sync::Mode::All => history::refs(profile.paths().git_dir(), &urn).ok(),
+        sync::Mode::Fetch => None,
+    };
+    let spinner = RefCell::new(term::spinner("Syncing..."));
     let result = rt.block_on(async {
         let (seeds, _errors) = sync::Seeds::resolve(seeds.iter()).await;
         let client = sync::client(signer, profile).await?;
-        let result = sync::sync(&client, urn, seeds, mode, timeout).await;
+        let total = seeds.len();
+        let synced = Cell::new(0);
+        let result = sync::sync(
+            &client,
+            urn.clone(),
+            seeds,
+            mode,
+            timeout,
+            |event| match event {
+                sync::Event::Progress { seed, message } => {
+                    spinner.borrow_mut().message(format!(
+                        "Syncing... {} {}",
+                        term::format::dim(common::fmt::peer(seed)),
+                        message.trim()
+                    ));
+                }
+                sync::Event::Synced(_) => {
+                    synced.set(synced.get() + 1);
+                    spinner.borrow_mut().message(format!(
+                        "Syncing... ({}/{})",
+                        synced.get(),
+                        total
+                    ));
+                }
+            },
+        )
+        .await;
 
         Ok::<Vec<SyncResult>, anyhow::Error>(result)
     })?;
+    let mut spinner = spinner.into_inner();
+    spinner.message("Syncing...");
 
     let results = if let Ok(results) = result.try_into() {
         results
     } else {
-        return Err(anyhow::anyhow!(
-            "No seeds attempted: all seeds failed to resolve"
-        ));
+        spinner.failed();
+        return Err(sync::UnresolvedSeeds.into());
     };
 
     match mode {
@@ -44,5 +90,85 @@ pub fn sync(
         sync::Mode::Fetch => spinner.clear(),
     }
 
+    // Failing to record the sync shouldn't fail the sync. Only pushes mark refs as
+    // synced, since fetching doesn't make local changes available to others.
+    if results.iter().any(|r| !r.is_unreachable()) {
+        if let Ok(mut history) = History::load(profile) {
+            match refs {
+                Some(refs) if results.iter().any(|r| matches!(r.push, Some(Ok(_)))) => {
+                    history.record_push(urn, refs)
+                }
+                _ => history.record(urn),
+            }
+            history.save(profile).ok();
+        }
+    }
+
     Ok(results)
 }
+
+/// Push the given URN to the seeds. If none of the seeds can be resolved or reached, the
+/// URN is queued in the profile's outbox instead of failing, and `None` is returned.
+/// Other errors, eg. signer or storage errors, are returned as is.
+pub fn push_or_queue(
+    urn: Urn,
+    seeds: NonEmpty<sync::Seed<String>>,
+    profile: &Profile,
+    signer: impl ToSigner,
+    rt: &common::tokio::runtime::Runtime,
+) -> anyhow::Result<Option<NonEmpty<SyncResult>>> {
+    let mut outbox = Outbox::load(profile)?;
+    match sync(urn.clone(), seeds, sync::Mode::Push, profile, signer, rt) {
+        Ok(results) if !results.iter().all(|r| r.is_unreachable()) => {
+            if outbox.remove(&urn).is_some() {
+                outbox.save(profile)?;
+            }
+            return Ok(Some(results));
+        }
+        Ok(_) => {}
+        Err(err) if err.is::<sync::UnresolvedSeeds>() => {}
+        Err(err) => return Err(err),
+    }
+    let entry = outbox.enqueue(urn);
+
+    term::warning(&format!(
+        "No seeds could be reached, {} was queued for syncing (attempt {})",
+        term::format::highlight(&entry.urn),
+        entry.attempts,
+    ));
+    outbox.save(profile)?;
+    term::tip!("Run `rad sync --pending` to sync queued changes when back online.");
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    use librad::crypto::keystore::crypto::{Pwhash, KDF_PARAMS_TEST};
+    use librad::crypto::keystore::pinentry::SecUtf8;
+    use librad::PeerId;
+
+    use radicle_common::test;
+
+    #[test]
+    fn test_push_or_queue_unresolved() {
+        let (_, profile, _, project) = test::setup::profile();
+        let pass = Pwhash::new(SecUtf8::from(test::USER_PASS), *KDF_PARAMS_TEST);
+        let signer = test::signer(&profile, pass).unwrap();
+        let rt = common::tokio::runtime::Runtime::new().unwrap();
+        let urn = project.urn();
+        let seed = sync::Seed {
+            peer: PeerId::from_str("hyb5to4rshftx4apgmu9s6wnsp4ddmp1mz6ijh4qqey7fb8wrpawxa")
+                .unwrap(),
+            addrs: String::from("seed.invalid:8776"),
+            label: None,
+        };
+
+        let results = push_or_queue(urn.clone(), NonEmpty::new(seed), &profile, signer, &rt);
+        assert!(results.unwrap().is_none());
+        assert!(Outbox::load(&profile).unwrap().contains(&urn));
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::convert::TryInto;
+use std::path::Path;
 
 use anyhow::anyhow;
 use anyhow::Context as _;
@@ -11,8 +12,10 @@ use librad::profile::Profile;
 use librad::PeerId;
 
 use radicle_common::args::Help;
+use radicle_common::config::Config;
 use radicle_common::nonempty::NonEmpty;
 use radicle_common::project::PeerInfo;
+use radicle_common::seed::api::SeedApiClient;
 use radicle_common::tokio;
 use radicle_common::Url;
 use radicle_common::{git, keys, project, seed, sync, Urn};
@@ -35,7 +38,6 @@ pub struct Branch {
     message: String,
 }
 
-// TODO: Add `--upstream-prefix` to specify a branch prefix, eg. `remotes/`.
 pub const HELP: Help = Help {
     name: "track",
     description: env!("CARGO_PKG_DESCRIPTION"),
@@ -46,11 +48,16 @@ Usage
     rad track           [--local | --remote]
     rad track           [--seed <url>]
     rad track <peer-id> [--seed <url>] [--no-sync] [--no-upstream] [--no-fetch]
+                        [--name <name>] [--upstream-prefix <prefix>]
 
     If a peer id is supplied, track this peer in the context of the current project. By default,
     a remote is created in the repository and an upstream tracking branch is setup. If a seed
     is supplied as well, the seed will be associated with this peer in the local git configuration.
 
+    The remote is named after the peer, eg. `peers/alice/rad`. If another peer's remote
+    already uses that name, a numeric suffix is added, eg. `peers/alice-2/rad`. Use `--name`
+    to choose the remote name, and `rad remote rename` to rename it later.
+
     If no peer id is supplied, show the local or remote tracking graph of the current project.
 
 Options
@@ -61,6 +68,8 @@ Options
     --no-upstream          Don't setup a tracking branch for the remote
     --no-sync              Don't sync the peer's refs
     --no-fetch             Don't fetch the peer's refs into the working copy
+    --name <name>          Name of the remote (default: the peer's name)
+    --upstream-prefix <p>  Prefix of the upstream tracking branch, eg. `remotes` (default: `peers`)
     --verbose, -v          Verbose output
     --help                 Print help
 "#,
@@ -81,7 +90,7 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
         track(peer, proj, repo, storage, profile, signer, options)?;
     } else {
         // Show tracking graph.
-        show(proj, repo, storage.read_only(), options)?;
+        show(proj, repo, storage.read_only(), &profile, options)?;
     }
 
     Ok(())
@@ -131,7 +140,7 @@ pub fn track(
                 .map_err(|e| anyhow!("invalid seed specified: {}", e))?;
             NonEmpty::new(seed)
         } else {
-            sync::seeds(&profile)?
+            term::sync::seeds(&profile, &project.urn)?
         };
 
         let rt = tokio::runtime::Runtime::new()?;
@@ -145,6 +154,10 @@ pub fn track(
         )?;
     }
 
+    if let Err(err) = rad_signers::autosync(Path::new("."), &project, storage.read_only()) {
+        term::warning(&format!("Could not update .gitsigners: {:#}", err));
+    }
+
     // If a seed is explicitly specified, associate it with the peer being tracked.
     if let Some(addr) = &options.seed {
         let seed = addr
@@ -160,21 +173,35 @@ pub fn track(
     }
 
     if options.upstream {
-        let name = if let Some(person) = project::person(&storage, urn.clone(), &peer)? {
+        let name = if let Some(name) = &options.name {
+            name.clone()
+        } else if let Some(person) = project::person(&storage, urn.clone(), &peer)? {
             person.subject().name.to_string()
         } else {
             term::warning("peer identity document not found, using id as remote name");
             peer.default_encoding()
         };
 
-        let branch = project::SetupRemote {
+        let setup = project::SetupRemote {
             project: &project,
             repo: &repo,
             signer,
             fetch: options.fetch,
             upstream: options.upstream,
+            upstream_prefix: options.upstream_prefix.clone(),
+        };
+        let remote_name = setup.remote_name(&peer, &name)?;
+
+        if remote_name != name {
+            if options.name.is_some() {
+                anyhow::bail!("remote name '{}' is already in use by another peer", name);
+            }
+            term::warning(&format!(
+                "remote name '{}' is already in use, using '{}' instead",
+                name, remote_name
+            ));
         }
-        .run(&peer, &name, &profile)?;
+        let branch = setup.run(&peer, &remote_name, &profile)?;
 
         if let Some((remote, branch)) = branch {
             term::success!("Remote {} set", term::format::highlight(remote.name),);
@@ -192,6 +219,7 @@ pub fn show(
     project: project::Metadata,
     repo: git::Repository,
     storage: &ReadOnly,
+    profile: &Profile,
     options: Options,
 ) -> anyhow::Result<()> {
     let peers = if options.local {
@@ -221,7 +249,9 @@ pub fn show(
             &project.urn,
             term::format::dim(format!("({})", seed.host)),
         ));
-        let peers = show_remote(&project, &repo, &seed.url())?;
+        let config = Config::load(profile).unwrap_or_default();
+        let api = SeedApiClient::from_address(&seed, config.seeds());
+        let peers = show_remote(&project, &repo, &seed.url(), &api)?;
 
         spinner.done();
 
@@ -241,6 +271,9 @@ pub fn show(
             if let Some(name) = meta.person.as_ref().map(|p| &p.name) {
                 header.push(term::format::tertiary(name));
             }
+            for ext in meta.extensions() {
+                header.push(term::format::dim(ext));
+            }
             if meta.delegate {
                 header.push(term::format::badge_primary("delegate"));
             }
@@ -344,21 +377,32 @@ pub fn show_remote(
     project: &project::Metadata,
     repo: &git::Repository,
     seed: &Url,
+    api: &SeedApiClient,
 ) -> anyhow::Result<Vec<Peer>> {
-    let urn = &project.urn;
-    let remotes = project::list_seed_heads(repo, seed, urn)?;
+    let remotes = project::list_seed_heads(repo, seed, &project.urn)?;
+
+    Ok(remote_peers(&project.urn, remotes, api))
+}
+
+/// Get the remote peers of a project, given the branch heads found on a seed.
+/// Peer and commit metadata is fetched from the seed's API, if available.
+pub fn remote_peers(
+    urn: &Urn,
+    remotes: HashMap<PeerId, Vec<(String, git::Oid)>>,
+    api: &SeedApiClient,
+) -> Vec<Peer> {
     let mut commits: HashMap<_, String> = HashMap::new();
 
-    let remote_metadata = if let Ok(meta) = seed::get_remotes(seed.clone(), urn) {
+    if remotes.is_empty() {
+        return Vec::new();
+    }
+
+    let remote_metadata = if let Ok(meta) = api.remotes(urn) {
         meta.into_iter().map(|r| (r.id, r)).collect()
     } else {
         HashMap::new() // Support old seeds that don't have metadata.
     };
 
-    if remotes.is_empty() {
-        return Ok(Vec::new());
-    }
-
     let mut peers = Vec::new();
 
     for (id, branches) in remotes {
@@ -371,7 +415,7 @@ pub fn show_remote(
         for (branch, oid) in branches {
             let message: String = if let Some(m) = commits.get(&oid) {
                 m.to_owned()
-            } else if let Ok(commit) = seed::get_commit(seed.clone(), urn, &oid) {
+            } else if let Ok(commit) = api.commit(urn, &oid) {
                 commits.insert(oid, commit.header.summary.clone());
                 commit.header.summary
             } else {
@@ -386,5 +430,58 @@ pub fn show_remote(
         }
         peers.push(peer);
     }
-    Ok(peers)
+    peers
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use radicle_common::json::json;
+    use radicle_common::test::seed::FakeSeed;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_remote_peers() {
+        let urn = Urn::from_str("rad:git:hnrkfbrd7y9674d8ow8uioki16fniwcyoz67y").unwrap();
+        let peer =
+            PeerId::from_str("hyb5to4rshftx4apgmu9s6wnsp4ddmp1mz6ijh4qqey7fb8wrpawxa").unwrap();
+        let oid = git::Oid::from_str("4b825dc642cb6eb9a060e54bf8d69288fbee4904").unwrap();
+        let seed = FakeSeed::spawn().unwrap();
+
+        seed.route(
+            &format!("/v1/projects/{}/remotes", urn),
+            json!([{ "id": peer.default_encoding(), "delegate": true }]),
+        )
+        .route(
+            &format!("/v1/projects/{}/commits/{}", urn, oid),
+            json!({ "header": { "sha1": oid.to_string(), "summary": "Initial commit" } }),
+        );
+
+        let api = SeedApiClient::new(seed.url());
+        let remotes = HashMap::from([(peer, vec![(String::from("master"), oid)])]);
+        let peers = remote_peers(&urn, remotes, &api);
+
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].id, peer);
+        assert!(peers[0].meta.as_ref().unwrap().delegate);
+        assert_eq!(peers[0].branches[0].name, "master");
+        assert_eq!(peers[0].branches[0].message, "Initial commit");
+    }
+
+    #[test]
+    fn test_remote_peers_old_seed() {
+        let urn = Urn::from_str("rad:git:hnrkfbrd7y9674d8ow8uioki16fniwcyoz67y").unwrap();
+        let peer =
+            PeerId::from_str("hyb5to4rshftx4apgmu9s6wnsp4ddmp1mz6ijh4qqey7fb8wrpawxa").unwrap();
+        let oid = git::Oid::from_str("4b825dc642cb6eb9a060e54bf8d69288fbee4904").unwrap();
+        let seed = FakeSeed::spawn().unwrap();
+
+        let api = SeedApiClient::new(seed.url());
+        let remotes = HashMap::from([(peer, vec![(String::from("master"), oid)])]);
+        let peers = remote_peers(&urn, remotes, &api);
+
+        assert_eq!(peers.len(), 1);
+        assert!(peers[0].meta.is_none());
+        assert!(peers[0].branches[0].message.is_empty());
+    }
 }
@@ -18,6 +18,10 @@ pub struct Options {
     pub fetch: bool,
     pub local: bool,
     pub seed: Option<seed::Address>,
+    /// Remote name to use, instead of the peer's name.
+    pub name: Option<String>,
+    /// Prefix of the upstream tracking branch.
+    pub upstream_prefix: Option<String>,
     pub verbose: bool,
 }
 
@@ -33,6 +37,8 @@ impl Args for Options {
         let mut fetch = true;
         let mut verbose = false;
         let mut seed = None;
+        let mut name = None;
+        let mut upstream_prefix = None;
 
         while let Some(arg) = parser.next()? {
             match arg {
@@ -51,6 +57,25 @@ impl Args for Options {
 
                     seed = Some(seed::Address::from_str(value)?);
                 }
+                Long("name") => {
+                    let value = parser.value()?;
+                    let value = value.to_string_lossy();
+
+                    if value.is_empty() || value.contains('/') {
+                        return Err(anyhow!("invalid remote name '{}'", value));
+                    }
+                    name = Some(value.into_owned());
+                }
+                Long("upstream-prefix") => {
+                    let value = parser.value()?;
+                    let value = value.to_string_lossy();
+                    let value = value.trim_matches('/');
+
+                    if value.is_empty() {
+                        return Err(anyhow!("invalid value specified for '--upstream-prefix'"));
+                    }
+                    upstream_prefix = Some(value.to_owned());
+                }
                 Long("sync") => sync = true,
                 Long("local") => local = Some(true),
                 Long("remote") => local = Some(false),
@@ -93,6 +118,8 @@ impl Args for Options {
                 upstream,
                 local,
                 seed,
+                name,
+                upstream_prefix,
                 verbose,
             },
             vec![],
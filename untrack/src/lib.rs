@@ -1,4 +1,5 @@
 use std::ffi::OsString;
+use std::path::Path;
 use std::str::FromStr;
 
 use anyhow::anyhow;
@@ -10,7 +11,7 @@ use librad::PeerId;
 
 use radicle_common::args::{Args, Error, Help};
 use radicle_common::profile::Profile;
-use radicle_common::{fmt, keys, project};
+use radicle_common::{fmt, keys, profile, project};
 use radicle_terminal as term;
 
 pub const HELP: Help = Help {
@@ -80,7 +81,15 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
         project::cwd().context("this command must be run in the context of a project")?;
     let profile = ctx.profile()?;
 
-    execute(&urn, options, &profile)
+    execute(&urn, options, &profile)?;
+
+    let storage = profile::read_only(&profile)?;
+    if let Some(project) = project::get(&storage, &urn)? {
+        if let Err(err) = rad_signers::autosync(Path::new("."), &project, &storage) {
+            term::warning(&format!("Could not update .gitsigners: {:#}", err));
+        }
+    }
+    Ok(())
 }
 
 pub fn execute(urn: &Urn, options: Options, profile: &Profile) -> anyhow::Result<()> {
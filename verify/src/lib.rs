@@ -0,0 +1,159 @@
+use std::ffi::OsString;
+use std::path::Path;
+
+use anyhow::anyhow;
+
+use radicle_common as common;
+use radicle_common::args::{Args, Error, Help};
+use radicle_common::git::verify::{self, Signers, Status};
+use radicle_common::{profile, project};
+use radicle_terminal as term;
+
+pub const HELP: Help = Help {
+    name: "verify",
+    description: env!("CARGO_PKG_DESCRIPTION"),
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad verify [<range>] [<option>...]
+
+    Verifies the SSH signatures of the commits in the given range, eg. `master..HEAD`.
+    If no range is given, all commits reachable from `HEAD` are verified.
+
+    Signatures are checked against the keys of the project delegates and the keys
+    listed in the repository's `.gitsigners` file. Unsigned commits and commits signed
+    by unknown keys fail verification.
+
+Options
+
+    --delegates     Only accept signatures from project delegates
+    --help          Print help
+"#,
+};
+
+#[derive(Debug)]
+pub struct Options {
+    pub range: String,
+    pub delegates: bool,
+}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+        let mut range: Option<String> = None;
+        let mut delegates = false;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("delegates") => {
+                    delegates = true;
+                }
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                Value(val) if range.is_none() => {
+                    range = Some(val.to_string_lossy().into());
+                }
+                _ => return Err(anyhow!(arg.unexpected())),
+            }
+        }
+
+        Ok((
+            Options {
+                range: range.unwrap_or_else(|| String::from("HEAD")),
+                delegates,
+            },
+            vec![],
+        ))
+    }
+}
+
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let (urn, _) = project::cwd()
+        .map_err(|_| anyhow!("this command must be run in the context of a project"))?;
+    let profile = ctx.profile()?;
+    let storage = profile::read_only(&profile)?;
+    let project = project::get(&storage, &urn)?
+        .ok_or_else(|| anyhow!("couldn't load project {} from local state", urn))?;
+
+    verify(Path::new("."), &project, &options.range, options.delegates)
+}
+
+/// Verify the commit signatures of a range, and print a report. Fails if any commit
+/// doesn't pass verification.
+pub fn verify(
+    repo: &Path,
+    project: &project::Metadata,
+    range: &str,
+    delegates: bool,
+) -> anyhow::Result<()> {
+    let signers = Signers::load(repo, project)?;
+    let commits = verify::verify(repo, range, &signers)?;
+
+    if commits.is_empty() {
+        term::info!("No commits to verify in {}", term::format::highlight(range));
+        return Ok(());
+    }
+
+    let mut table = term::Table::default();
+    let mut failed = 0;
+
+    for commit in &commits {
+        let (ok, status) = match &commit.status {
+            Status::Verified { signer } if signer.delegate => (
+                true,
+                format!(
+                    "{} {}",
+                    term::format::positive(format!("✓ {}", signer.label)),
+                    term::format::dim("(delegate)")
+                ),
+            ),
+            Status::Verified { signer } if delegates => (
+                false,
+                format!(
+                    "{} {}",
+                    term::format::negative(format!("✗ {}", signer.label)),
+                    term::format::dim("(not a delegate)")
+                ),
+            ),
+            Status::Verified { signer } => {
+                (true, term::format::positive(format!("✓ {}", signer.label)))
+            }
+            Status::Unknown { fingerprint } => (
+                false,
+                format!(
+                    "{} {}",
+                    term::format::negative("✗ unknown signer"),
+                    term::format::dim(fingerprint)
+                ),
+            ),
+            Status::Unsigned => (false, term::format::negative("✗ unsigned")),
+            Status::Bad => (false, term::format::negative("✗ bad signature")),
+        };
+        if !ok {
+            failed += 1;
+        }
+
+        table.push([
+            term::format::secondary(common::fmt::oid(&commit.oid)),
+            commit.summary.clone(),
+            status,
+        ]);
+    }
+    table.render();
+    term::blank();
+
+    if failed > 0 {
+        anyhow::bail!(
+            "{} of {} commit(s) failed signature verification",
+            failed,
+            commits.len()
+        );
+    }
+    term::success!("{} commit(s) verified", commits.len());
+
+    Ok(())
+}